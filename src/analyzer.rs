@@ -0,0 +1,3 @@
+pub mod orthography;
+pub mod passage_text;
+pub mod statistics;
@@ -0,0 +1,421 @@
+use std::{cmp::Reverse, collections::HashSet, fmt, fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    tree::{
+        function::{LitedownFunction, PassageElement},
+        function_argument::FunctionArgumentValue,
+        litedown::LitedownAst,
+    },
+    utility::{csv::parse_csv, text_normalization::is_japanese},
+};
+
+use super::passage_text::{collect_passage_texts, PassageText, PassageTextKind};
+
+// 表記ゆれの組 (先頭の表記を優先する)
+const BUILTIN_VARIANTS: [&[&str]; 12] = [
+    &["行う", "行なう"],
+    &["できる", "出来る"],
+    &["ください", "下さい"],
+    &["いたします", "致します"],
+    &["いただく", "頂く"],
+    &["すべて", "全て"],
+    &["および", "及び"],
+    &["または", "又は"],
+    &["さまざま", "様々"],
+    &["ほとんど", "殆ど"],
+    &["あらかじめ", "予め"],
+    &["わかる", "分かる", "解る"],
+];
+
+// 送り仮名の揺れを探すときに，助詞とみなして無視するひらがな
+const PARTICLES: [char; 11] = [
+    'の', 'が', 'を', 'に', 'へ', 'と', 'で', 'や', 'は', 'も', 'か',
+];
+
+pub struct OrthographyReport {
+    pub line: usize,
+    pub column: usize,
+    pub word: String,
+    // 同じ組でより多く使われている表記
+    pub preferred: String,
+}
+
+impl fmt::Display for OrthographyReport {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{}:{}: '{}' is also written as '{}'",
+            self.line, self.column, self.word, self.preferred
+        )
+    }
+}
+
+struct OrthographyOptions {
+    variants: Vec<Vec<String>>,
+    ignored: Vec<String>,
+    builtin: bool,
+}
+
+struct Occurrence {
+    line: usize,
+    column: usize,
+    variant: usize,
+}
+
+// @nocheck{...} の中身は検査しない
+pub fn check_orthography(
+    ast: &LitedownAst,
+    source_path: Option<&Path>,
+) -> Result<Vec<OrthographyReport>> {
+    let options = evaluate_options(ast, source_path)?;
+    let texts: Vec<PassageText> = collect_passage_texts(&ast.body, &["nocheck"])
+        .into_iter()
+        .filter(|text| text.kind == PassageTextKind::Text)
+        .collect();
+
+    let mut variants = options.variants.clone();
+    if options.builtin {
+        for builtin in BUILTIN_VARIANTS {
+            variants.push(builtin.iter().map(|variant| variant.to_string()).collect());
+        }
+        variants.extend(find_long_vowel_variants(&texts));
+        variants.extend(find_okurigana_variants(&texts));
+    }
+
+    let mut reports = Vec::new();
+    for group in &variants {
+        if group
+            .iter()
+            .any(|variant| options.ignored.contains(variant))
+        {
+            continue;
+        }
+        let occurrences = find_occurrences(&texts, group);
+        let mut counts = vec![0; group.len()];
+        for occurrence in &occurrences {
+            counts[occurrence.variant] += 1;
+        }
+        if counts.iter().filter(|&&count| 0 < count).count() < 2 {
+            continue;
+        }
+        // 最も多い表記に揃える (同数なら組の先頭に近い方)
+        let preferred = (0..group.len())
+            .max_by_key(|&i| (counts[i], Reverse(i)))
+            .unwrap();
+        for occurrence in occurrences {
+            if occurrence.variant != preferred {
+                reports.push(OrthographyReport {
+                    line: occurrence.line,
+                    column: occurrence.column,
+                    word: group[occurrence.variant].clone(),
+                    preferred: group[preferred].clone(),
+                });
+            }
+        }
+    }
+    reports.sort_by_key(|report| (report.line, report.column));
+    // 辞書と推測で同じ組が重なることがある
+    reports.dedup_by_key(|report| (report.line, report.column));
+    Ok(reports)
+}
+
+// @preamble の中の @orthography を読む
+// @orthography["サーバー", "サーバ"] で組を追加し，
+// @orthography[dictionary="variants.csv", ignore=["できる"], builtin=false] で辞書や無視する表記を指定する
+fn evaluate_options(ast: &LitedownAst, source_path: Option<&Path>) -> Result<OrthographyOptions> {
+    let mut options = OrthographyOptions {
+        variants: Vec::new(),
+        ignored: Vec::new(),
+        builtin: true,
+    };
+    let preamble = match ast.body.iter().find(|function| function.name == "preamble") {
+        Some(preamble) => preamble,
+        None => return Ok(options),
+    };
+    for passage in &preamble.body.value {
+        for element in &passage.elements {
+            if let PassageElement::Function(function) = element {
+                if function.name == "orthography" {
+                    evaluate_orthography(function, source_path, &mut options)?;
+                }
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn evaluate_orthography(
+    function: &LitedownFunction,
+    source_path: Option<&Path>,
+    options: &mut OrthographyOptions,
+) -> Result<()> {
+    let mut group = Vec::new();
+    while let Some(variant) = function.arguments.get_by_index(group.len()) {
+        group.push(variant.try_into_string()?);
+    }
+    match group.len() {
+        0 => {}
+        1 => bail!("orthography variants must have at least 2 spellings"),
+        _ => options.variants.push(group),
+    }
+
+    if let Some(dictionary) = function.arguments.get_by_name("dictionary") {
+        let dictionary = dictionary.try_into_string()?;
+        let path = if dictionary.starts_with('/') {
+            Path::new(&dictionary).to_path_buf()
+        } else {
+            source_path
+                .context("cannot use relative path")?
+                .with_file_name(&dictionary)
+        };
+        let source =
+            fs::read_to_string(&path).with_context(|| format!("failed to read {}", dictionary))?;
+        // 1 行に 1 組の表記を並べる
+        for record in parse_csv(&source, ',')? {
+            let group: Vec<String> = record
+                .into_iter()
+                .map(|variant| variant.trim().to_string())
+                .filter(|variant| !variant.is_empty())
+                .collect();
+            if 2 <= group.len() {
+                options.variants.push(group);
+            }
+        }
+    }
+    // ignore="できる" または ignore=["できる", "全て"]
+    if let Some(ignore) = function.arguments.get_by_name("ignore") {
+        match &ignore.value {
+            FunctionArgumentValue::Array { value } => {
+                options
+                    .ignored
+                    .extend(value.iter().map(|variant| variant.to_string()));
+            }
+            _ => options.ignored.push(ignore.try_into_string()?),
+        }
+    }
+    if let Some(builtin) = function.arguments.get_by_name("builtin") {
+        options.builtin = builtin.try_into_boolean()?;
+    }
+    Ok(())
+}
+
+fn is_katakana(c: char) -> bool {
+    matches!(c, '\u{30A1}'..='\u{30FA}' | 'ー')
+}
+
+fn is_kanji(c: char) -> bool {
+    is_japanese(c) && !matches!(c, '\u{3040}'..='\u{30FF}')
+}
+
+fn is_hiragana(c: char) -> bool {
+    matches!(c, '\u{3041}'..='\u{3096}')
+}
+
+// 組のいずれかの表記が現れる位置 (長い表記を優先し，カタカナ語の途中には一致させない)
+fn find_occurrences(texts: &[PassageText], group: &[String]) -> Vec<Occurrence> {
+    let mut order: Vec<usize> = (0..group.len()).collect();
+    order.sort_by_key(|&i| Reverse(group[i].chars().count()));
+    let group: Vec<Vec<char>> = group
+        .iter()
+        .map(|variant| variant.chars().collect())
+        .collect();
+
+    let mut occurrences = Vec::new();
+    for text in texts {
+        let chars: Vec<char> = text.text.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let found = order.iter().copied().find(|&variant| {
+                let word = &group[variant];
+                if !chars[i..].starts_with(word) {
+                    return false;
+                }
+                let before = if i == 0 { None } else { chars.get(i - 1) };
+                let after = chars.get(i + word.len());
+                let inside_word_start =
+                    is_katakana(word[0]) && before.is_some_and(|&c| is_katakana(c));
+                let inside_word_end =
+                    is_katakana(word[word.len() - 1]) && after.is_some_and(|&c| is_katakana(c));
+                !inside_word_start && !inside_word_end
+            });
+            match found {
+                Some(variant) => {
+                    let (line, column) = text.position.locate(text.text, i);
+                    occurrences.push(Occurrence {
+                        line,
+                        column,
+                        variant,
+                    });
+                    i += group[variant].len();
+                }
+                None => i += 1,
+            }
+        }
+    }
+    occurrences
+}
+
+// サーバー / サーバ のような長音符の有無
+fn find_long_vowel_variants(texts: &[PassageText]) -> Vec<Vec<String>> {
+    let mut words = HashSet::new();
+    for text in texts {
+        let mut word = String::new();
+        for c in text.text.chars().chain(std::iter::once(' ')) {
+            if is_katakana(c) {
+                word.push(c);
+            } else if !word.is_empty() {
+                words.insert(std::mem::take(&mut word));
+            }
+        }
+    }
+    let mut variants: Vec<Vec<String>> = words
+        .iter()
+        .filter_map(|word| {
+            let short = word.strip_suffix('ー')?;
+            if 2 <= short.chars().count() && words.contains(short) {
+                Some(vec![word.clone(), short.to_string()])
+            } else {
+                None
+            }
+        })
+        .collect();
+    variants.sort();
+    variants
+}
+
+// 取り扱い / 取扱い のような送り仮名の有無 (漢字 + ひらがな 1 字 + 漢字)
+fn find_okurigana_variants(texts: &[PassageText]) -> Vec<Vec<String>> {
+    let mut variants = Vec::new();
+    for text in texts {
+        let chars: Vec<char> = text.text.chars().collect();
+        for window in chars.windows(3) {
+            let (first, kana, second) = (window[0], window[1], window[2]);
+            if !(is_kanji(first) && is_hiragana(kana) && is_kanji(second)) {
+                continue;
+            }
+            if PARTICLES.contains(&kana) {
+                continue;
+            }
+            let long = format!("{}{}{}", first, kana, second);
+            let short = format!("{}{}", first, second);
+            let variant = vec![long, short];
+            if !variants.contains(&variant)
+                && texts.iter().any(|text| text.text.contains(&variant[1]))
+            {
+                variants.push(variant);
+            }
+        }
+    }
+    variants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::litedown::parse_litedown;
+
+    fn check(preamble: &str, document: &str) -> Vec<String> {
+        let source = format!(
+            "@preamble@\n  @page_size[\"a4\"]\n{}\n\n@document@\n{}\n",
+            preamble, document
+        );
+        let ast = parse_litedown(&source).unwrap();
+        check_orthography(&ast, None)
+            .unwrap()
+            .iter()
+            .map(|report| report.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn builtin_variants() {
+        // 多い方の表記に揃え，同数なら組の先頭を優先する
+        assert_eq!(
+            check(
+                "",
+                "  処理を行う。処理を行なう。次に行う。\n  できる、出来る。"
+            ),
+            [
+                "6:12: '行なう' is also written as '行う'",
+                "7:7: '出来る' is also written as 'できる'",
+            ]
+        );
+        assert_eq!(
+            check("", "  全て。全て。すべて。"),
+            ["6:9: 'すべて' is also written as '全て'"]
+        );
+        assert!(check("", "  行う。行う。").is_empty());
+    }
+
+    #[test]
+    fn long_vowel() {
+        assert_eq!(
+            check("", "  サーバーを設定する。サーバを再起動する。"),
+            ["6:13: 'サーバ' is also written as 'サーバー'"]
+        );
+        // カタカナ語の途中には一致させない
+        assert!(check("", "  サーバー。サーバント。").is_empty());
+        // 2 文字未満の語は長音符の揺れとみなさない
+        assert!(check("", "  ルー。ル。").is_empty());
+    }
+
+    #[test]
+    fn okurigana() {
+        assert_eq!(
+            check("", "  取り扱いに注意。取扱いは別。"),
+            ["6:11: '取扱' is also written as '取り扱'"]
+        );
+        // 助詞を挟んだ漢字は送り仮名とみなさない
+        assert!(check("", "  東京の大学。東京大学。").is_empty());
+    }
+
+    #[test]
+    fn user_variants() {
+        assert_eq!(
+            check(
+                "  @orthography[\"コンピュータ\", \"コンピューター\"]",
+                "  コンピュータとコンピューターとコンピューター。"
+            ),
+            ["6:3: 'コンピュータ' is also written as 'コンピューター'"]
+        );
+        let source =
+            "@preamble@\n  @page_size[\"a4\"]\n  @orthography[\"一つ\"]\n\n@document@\n  x\n";
+        assert!(check_orthography(&parse_litedown(source).unwrap(), None).is_err());
+    }
+
+    #[test]
+    fn nocheck() {
+        assert!(check("", "  サーバー。@nocheck{サーバ}。").is_empty());
+        assert!(check("", "  サーバー。@code{サーバ}。").is_empty());
+    }
+
+    #[test]
+    fn ignore() {
+        let document = "  行う。行なう。できる。出来る。全て。すべて。";
+        assert_eq!(
+            check("  @orthography[ignore=\"できる\"]", document).len(),
+            2
+        );
+        assert_eq!(
+            check("  @orthography[ignore=[\"できる\", \"全て\"]]", document),
+            ["6:6: '行なう' is also written as '行う'"]
+        );
+        // 複数の @orthography の ignore はまとめて適用する
+        assert!(check(
+            "  @orthography[ignore=[\"できる\", \"全て\"]]\n  @orthography[ignore=\"行う\"]",
+            document
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn without_builtin() {
+        assert!(check(
+            "  @orthography[builtin=false]",
+            "  行う。行なう。サーバー。サーバ。取り扱い。取扱い。"
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+use crate::tree::function::{LitedownFunction, PassageElement, SourcePosition};
+
+// 本文として扱わない関数 (中身がパスなど)
+const NON_PASSAGE_FUNCTIONS: [&str; 2] = ["preamble", "image"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PassageTextKind {
+    Text,
+    Code,
+    Math,
+    // $...$ や : で書かれた，そのままの文字列
+    Raw,
+}
+
+pub struct PassageText<'a> {
+    pub text: &'a str,
+    pub position: &'a SourcePosition,
+    pub kind: PassageTextKind,
+}
+
+// 本文の文字列を出現順に集める (excluded の関数の中身は除く)
+pub fn collect_passage_texts<'a>(
+    functions: &'a [LitedownFunction],
+    excluded: &[&str],
+) -> Vec<PassageText<'a>> {
+    let mut texts = Vec::new();
+    for function in functions {
+        collect_function(function, excluded, &mut texts);
+    }
+    texts
+}
+
+fn collect_function<'a>(
+    function: &'a LitedownFunction,
+    excluded: &[&str],
+    texts: &mut Vec<PassageText<'a>>,
+) {
+    let name = function.name.as_str();
+    if NON_PASSAGE_FUNCTIONS.contains(&name) || excluded.contains(&name) {
+        return;
+    }
+    // 引数のない @link{...} の中身は URL
+    if name == "link" && function.arguments.is_empty() {
+        return;
+    }
+    let kind = match name {
+        "code" => PassageTextKind::Code,
+        "math" => PassageTextKind::Math,
+        _ if function.body.raw => PassageTextKind::Raw,
+        _ => PassageTextKind::Text,
+    };
+    for passage in &function.body.value {
+        for element in &passage.elements {
+            match element {
+                PassageElement::String(text, position) => texts.push(PassageText {
+                    text,
+                    position,
+                    kind: kind.clone(),
+                }),
+                PassageElement::Function(child_function) => {
+                    collect_function(child_function, excluded, texts)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,397 @@
+use std::fmt;
+
+use anyhow::Result;
+
+use crate::{
+    tree::{
+        function::{LitedownFunction, PassageElement},
+        litedown::LitedownAst,
+    },
+    utility::text_normalization::is_japanese,
+};
+
+use super::passage_text::{collect_passage_texts, PassageTextKind};
+
+// 原稿用紙 1 枚 (20 字 × 20 行) の文字数
+const MANUSCRIPT_PAGE_CHARACTERS: usize = 400;
+
+#[derive(Debug, Clone, Default)]
+pub struct Statistics {
+    // 空白と改行を除いた文字数
+    pub characters: usize,
+    // 欧文の単語数
+    pub words: usize,
+}
+
+impl Statistics {
+    pub fn get_manuscript_pages(&self) -> f64 {
+        self.characters as f64 / MANUSCRIPT_PAGE_CHARACTERS as f64
+    }
+
+    fn add_text(&mut self, text: &str) {
+        let mut in_word = false;
+        for c in text.chars() {
+            if c.is_whitespace() {
+                in_word = false;
+                continue;
+            }
+            self.characters += 1;
+            // it's や well-known は 1 語とみなす
+            let is_word_char = c.is_alphanumeric() && !is_japanese(c);
+            if is_word_char && !in_word {
+                self.words += 1;
+            }
+            in_word = is_word_char || (in_word && (c == '\'' || c == '-'));
+        }
+    }
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{} characters, {} words, {:.1} manuscript pages",
+            self.characters,
+            self.words,
+            self.get_manuscript_pages()
+        )
+    }
+}
+
+pub struct StatisticsEntry {
+    // 0 が全体，1 以上が節やスライドの階層
+    pub level: usize,
+    pub name: String,
+    pub statistics: Statistics,
+}
+
+struct StatisticsOptions {
+    code: bool,
+    math: bool,
+    raw: bool,
+    notes: bool,
+}
+
+// 全体と，節 (@section) またはスライド (@slide) ごとの文字数を数える
+// 数える対象は @preamble の中の @statistics[code=true, math=true, raw=true, notes=true] で変えられる
+pub fn count_statistics(ast: &LitedownAst) -> Result<Vec<StatisticsEntry>> {
+    let options = evaluate_options(ast)?;
+
+    let mut entries = Vec::new();
+    let mut total = Statistics::default();
+    for function in &ast.body {
+        let statistics = count_function(function, &options);
+        total.characters += statistics.characters;
+        total.words += statistics.words;
+    }
+    entries.push(StatisticsEntry {
+        level: 0,
+        name: "Total".to_string(),
+        statistics: total,
+    });
+    for function in &ast.body {
+        match function.name.as_str() {
+            "document" => collect_sections(function, 1, &options, &mut entries)?,
+            "presentation" => collect_slides(function, &options, &mut entries)?,
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
+
+fn evaluate_options(ast: &LitedownAst) -> Result<StatisticsOptions> {
+    let mut options = StatisticsOptions {
+        code: false,
+        math: false,
+        raw: false,
+        notes: false,
+    };
+    let preamble = match ast.body.iter().find(|function| function.name == "preamble") {
+        Some(preamble) => preamble,
+        None => return Ok(options),
+    };
+    for function in get_child_functions(preamble) {
+        if function.name != "statistics" {
+            continue;
+        }
+        for (name, option) in [
+            ("code", &mut options.code),
+            ("math", &mut options.math),
+            ("raw", &mut options.raw),
+            ("notes", &mut options.notes),
+        ] {
+            if let Some(value) = function.arguments.get_by_name(name) {
+                *option = value.try_into_boolean()?;
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn get_child_functions(function: &LitedownFunction) -> impl Iterator<Item = &LitedownFunction> {
+    function
+        .body
+        .value
+        .iter()
+        .flat_map(|passage| passage.elements.iter())
+        .filter_map(|element| match element {
+            PassageElement::Function(function) => Some(function),
+            PassageElement::String(..) => None,
+        })
+}
+
+fn count_function(function: &LitedownFunction, options: &StatisticsOptions) -> Statistics {
+    let mut statistics = Statistics::default();
+    let mut titles = Vec::new();
+    collect_section_titles(function, &mut titles);
+    for title in titles {
+        statistics.add_text(&title);
+    }
+
+    let excluded: &[&str] = if options.notes { &[] } else { &["notes"] };
+    for text in collect_passage_texts(std::slice::from_ref(function), excluded) {
+        let included = match text.kind {
+            PassageTextKind::Text => true,
+            PassageTextKind::Code => options.code,
+            PassageTextKind::Math => options.math,
+            PassageTextKind::Raw => options.raw,
+        };
+        if included {
+            statistics.add_text(text.text);
+        }
+    }
+    statistics
+}
+
+// 節の見出しは本文ではなく引数に書かれる
+fn collect_section_titles(function: &LitedownFunction, titles: &mut Vec<String>) {
+    if function.name == "section" {
+        if let Some(Ok(title)) = function
+            .arguments
+            .get_by_name("title")
+            .map(|title| title.try_into_string())
+        {
+            titles.push(title);
+        }
+    }
+    for child_function in get_child_functions(function) {
+        collect_section_titles(child_function, titles);
+    }
+}
+
+fn collect_sections(
+    function: &LitedownFunction,
+    level: usize,
+    options: &StatisticsOptions,
+    entries: &mut Vec<StatisticsEntry>,
+) -> Result<()> {
+    for child_function in get_child_functions(function) {
+        if child_function.name != "section" {
+            continue;
+        }
+        let name = match child_function.arguments.get_by_name("title") {
+            Some(title) => title.try_into_string()?,
+            None => "(untitled section)".to_string(),
+        };
+        entries.push(StatisticsEntry {
+            level,
+            name,
+            statistics: count_function(child_function, options),
+        });
+        collect_sections(child_function, level + 1, options, entries)?;
+    }
+    Ok(())
+}
+
+fn collect_slides(
+    function: &LitedownFunction,
+    options: &StatisticsOptions,
+    entries: &mut Vec<StatisticsEntry>,
+) -> Result<()> {
+    let mut index = 0;
+    for child_function in get_child_functions(function) {
+        let name = match child_function.name.as_str() {
+            "title" => "Title slide".to_string(),
+            "slide" => {
+                // PDF に出力しないスライドには番号が振られない
+                let pdf = match child_function.arguments.get_by_name("pdf") {
+                    Some(pdf) => pdf.try_into_boolean()?,
+                    None => true,
+                };
+                let number = if pdf {
+                    index += 1;
+                    index.to_string()
+                } else {
+                    "-".to_string()
+                };
+                // 見出しは @header{...} に書かれる
+                let header = get_child_functions(child_function)
+                    .find(|function| function.name == "header")
+                    .and_then(|header| header.body.try_get_as_string().ok());
+                match header {
+                    Some(header) => format!("Slide {} ({})", number, header),
+                    None => format!("Slide {}", number),
+                }
+            }
+            _ => continue,
+        };
+        entries.push(StatisticsEntry {
+            level: 1,
+            name,
+            statistics: count_function(child_function, options),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::litedown::parse_litedown;
+
+    fn count(text: &str) -> Statistics {
+        let mut statistics = Statistics::default();
+        statistics.add_text(text);
+        statistics
+    }
+
+    // (階層, 名前, 文字数, 単語数) の一覧
+    fn count_entries(preamble: &str, body: &str) -> Vec<(usize, String, usize, usize)> {
+        let source = format!(
+            "@preamble@\n  @page_size[\"a4\"]\n{}\n\n{}\n",
+            preamble, body
+        );
+        count_statistics(&parse_litedown(&source).unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|entry| {
+                (
+                    entry.level,
+                    entry.name,
+                    entry.statistics.characters,
+                    entry.statistics.words,
+                )
+            })
+            .collect()
+    }
+
+    fn entry(
+        level: usize,
+        name: &str,
+        characters: usize,
+        words: usize,
+    ) -> (usize, String, usize, usize) {
+        (level, name.to_string(), characters, words)
+    }
+
+    const DOCUMENT: &str = r#"@document@
+  前書き。
+  @section[title="第一"]@
+    本文です。
+    @section[title="Sub"]@
+      Hello world.
+  @section@
+    @code{ab} @math{cd} @strong$ef$"#;
+
+    const PRESENTATION: &str = r#"@presentation@
+  @title@
+    Deck
+
+  @slide@
+    @header{First}
+    hello
+
+  @slide[pdf=false]@
+    @header{Hidden}
+    x
+
+  @slide@
+    本文
+
+    @notes@
+      Say this."#;
+
+    #[test]
+    fn characters_and_words() {
+        let statistics = count("Hello, world!\n  It's a well-known test.");
+        assert_eq!(statistics.characters, 32);
+        assert_eq!(statistics.words, 6);
+
+        let statistics = count("日本語の文章。 Rust言語");
+        assert_eq!(statistics.characters, 13);
+        assert_eq!(statistics.words, 1);
+
+        let statistics = count(" \n\t　");
+        assert_eq!(statistics.characters, 0);
+        assert_eq!(statistics.words, 0);
+    }
+
+    #[test]
+    fn manuscript_pages() {
+        let statistics = count(&"あ".repeat(600));
+        assert_eq!(statistics.get_manuscript_pages(), 1.5);
+        assert_eq!(
+            statistics.to_string(),
+            "600 characters, 0 words, 1.5 manuscript pages"
+        );
+    }
+
+    #[test]
+    fn sections() {
+        assert_eq!(
+            count_entries("", DOCUMENT),
+            [
+                entry(0, "Total", 25, 3),
+                entry(1, "第一", 21, 3),
+                entry(2, "Sub", 14, 3),
+                entry(1, "(untitled section)", 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn code_math_and_raw() {
+        assert_eq!(
+            count_entries("  @statistics[code=true]", DOCUMENT)[3],
+            entry(1, "(untitled section)", 2, 1)
+        );
+        assert_eq!(
+            count_entries("  @statistics[code=true, math=true, raw=true]", DOCUMENT),
+            [
+                entry(0, "Total", 31, 6),
+                entry(1, "第一", 21, 3),
+                entry(2, "Sub", 14, 3),
+                entry(1, "(untitled section)", 6, 3),
+            ]
+        );
+        // 後の @statistics で上書きする
+        assert_eq!(
+            count_entries(
+                "  @statistics[math=true]\n  @statistics[math=false, raw=true]",
+                DOCUMENT
+            )[3],
+            entry(1, "(untitled section)", 2, 1)
+        );
+    }
+
+    #[test]
+    fn slides() {
+        assert_eq!(
+            count_entries("", PRESENTATION),
+            [
+                entry(0, "Total", 23, 5),
+                entry(1, "Title slide", 4, 1),
+                entry(1, "Slide 1 (First)", 10, 2),
+                entry(1, "Slide - (Hidden)", 7, 2),
+                entry(1, "Slide 2", 2, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn notes() {
+        let entries = count_entries("  @statistics[notes=true]", PRESENTATION);
+        assert_eq!(entries[0], entry(0, "Total", 31, 7));
+        assert_eq!(entries[4], entry(1, "Slide 2", 10, 2));
+    }
+}
@@ -1,6 +1,9 @@
+mod bibliography;
 mod common;
+mod counter;
 mod document;
 mod evaluate_macro;
 pub mod litedown;
 mod preamble;
 mod presentation;
+mod reference;
@@ -0,0 +1,338 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+use crate::utility::bibtex::{clean_bibtex_text, parse_bibtex};
+
+#[derive(Clone, Debug)]
+pub struct Person {
+    pub family: String,
+    pub given: Option<String>,
+}
+
+impl Person {
+    pub fn get_full_name(&self) -> String {
+        match &self.given {
+            // 日本語の氏名などは姓を先に書く
+            Some(given) if self.family.chars().any(is_cjk) => {
+                format!("{} {}", self.family, given)
+            }
+            Some(given) => format!("{} {}", given, self.family),
+            None => self.family.clone(),
+        }
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}' // ひらがな・カタカナ
+        | '\u{3400}'..='\u{9fff}' // 漢字
+        | '\u{ac00}'..='\u{d7af}' // ハングル
+    )
+}
+
+#[derive(Clone, Debug)]
+pub struct BibliographyEntry {
+    pub key: String,
+    pub authors: Vec<Person>,
+    pub title: Option<String>,
+    // 雑誌名・書籍名など
+    pub container: Option<String>,
+    pub publisher: Option<String>,
+    pub volume: Option<String>,
+    pub issue: Option<String>,
+    pub pages: Option<String>,
+    pub year: Option<String>,
+    pub url: Option<String>,
+    pub doi: Option<String>,
+}
+
+impl BibliographyEntry {
+    // 著者年方式の引用に用いる短い著者表記
+    pub fn get_short_authors(&self) -> String {
+        match self.authors.as_slice() {
+            [] => self.title.clone().unwrap_or_else(|| self.key.clone()),
+            [author] => author.family.clone(),
+            [first, second] => format!("{} and {}", first.family, second.family),
+            [first, ..] => format!("{} et al.", first.family),
+        }
+    }
+
+    pub fn get_year(&self) -> String {
+        self.year.clone().unwrap_or_else(|| "n.d.".to_string())
+    }
+}
+
+#[derive(Default)]
+pub struct Bibliography {
+    entries: HashMap<String, BibliographyEntry>,
+}
+
+impl Bibliography {
+    pub fn load(path: &Path) -> Result<Bibliography> {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => bail!("could not read bibliography: {}", e),
+        };
+        let extension = path.extension().and_then(|str| str.to_str()).unwrap_or("");
+        Bibliography::parse(&source, extension)
+    }
+
+    // extension は "bib" (BibTeX) または "json" (CSL-JSON)
+    fn parse(source: &str, extension: &str) -> Result<Bibliography> {
+        let entries = match extension {
+            "bib" => parse_bibtex(source)?
+                .into_iter()
+                .map(|entry| {
+                    let get = |name: &str| entry.fields.get(name).map(|s| clean_bibtex_text(s));
+                    let authors = match entry.fields.get("author").or(entry.fields.get("editor")) {
+                        Some(names) => parse_bibtex_names(names),
+                        None => Vec::new(),
+                    };
+                    BibliographyEntry {
+                        key: entry.key.clone(),
+                        authors,
+                        title: get("title"),
+                        container: get("journal").or(get("booktitle")),
+                        publisher: get("publisher")
+                            .or(get("school"))
+                            .or(get("institution"))
+                            .or(get("organization")),
+                        volume: get("volume"),
+                        issue: get("number"),
+                        pages: get("pages"),
+                        year: get("year"),
+                        url: get("url"),
+                        doi: get("doi"),
+                    }
+                })
+                .collect(),
+            "json" => {
+                let value: Value =
+                    serde_json::from_str(source).context("failed to parse CSL-JSON")?;
+                let items = value.as_array().context("CSL-JSON must be an array")?;
+                items
+                    .iter()
+                    .map(parse_csl_json_item)
+                    .collect::<Result<Vec<_>>>()?
+            }
+            _ => bail!("unknown bibliography extension: {}", extension),
+        };
+
+        let mut bibliography = Bibliography::default();
+        for entry in entries {
+            if bibliography.entries.contains_key(&entry.key) {
+                bail!("duplicate bibliography key: '{}'", entry.key);
+            }
+            bibliography.entries.insert(entry.key.clone(), entry);
+        }
+        Ok(bibliography)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&BibliographyEntry> {
+        self.entries.get(key)
+    }
+}
+
+// "Knuth, Donald E. and Leslie Lamport" のような並び
+fn parse_bibtex_names(names: &str) -> Vec<Person> {
+    split_at_top_level(names, " and ")
+        .iter()
+        .map(|name| {
+            let parts = split_at_top_level(name, ",");
+            match parts.as_slice() {
+                // Last, First または Last, Jr, First
+                [family, .., given] => Person {
+                    family: clean_bibtex_text(family),
+                    given: Some(clean_bibtex_text(given)).filter(|given| !given.is_empty()),
+                },
+                _ => {
+                    let words = split_at_top_level(name.trim(), " ");
+                    match words.split_last() {
+                        Some((family, given)) if !given.is_empty() => Person {
+                            family: clean_bibtex_text(family),
+                            given: Some(clean_bibtex_text(&given.join(" "))),
+                        },
+                        _ => Person {
+                            family: clean_bibtex_text(name),
+                            given: None,
+                        },
+                    }
+                }
+            }
+        })
+        .filter(|person| !person.family.is_empty())
+        .collect()
+}
+
+// 括弧の内側は区切らない
+fn split_at_top_level(text: &str, separator: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if depth == 0 && rest.starts_with(separator) {
+            parts.push(current.trim().to_string());
+            current = String::new();
+            rest = &rest[separator.len()..];
+            continue;
+        }
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+        current.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    parts.push(current.trim().to_string());
+    parts.into_iter().filter(|part| !part.is_empty()).collect()
+}
+
+fn parse_csl_json_item(item: &Value) -> Result<BibliographyEntry> {
+    let key = match item.get("id") {
+        Some(Value::String(id)) => id.clone(),
+        Some(Value::Number(id)) => id.to_string(),
+        _ => bail!("CSL-JSON item must have 'id'"),
+    };
+    let get = |name: &str| match item.get(name) {
+        Some(Value::String(value)) => Some(value.clone()),
+        Some(Value::Number(value)) => Some(value.to_string()),
+        _ => None,
+    };
+
+    let persons = item
+        .get("author")
+        .or(item.get("editor"))
+        .and_then(|persons| persons.as_array());
+    let authors = match persons {
+        Some(persons) => persons
+            .iter()
+            .filter_map(|person| {
+                let family = person.get("family").and_then(|s| s.as_str());
+                let given = person.get("given").and_then(|s| s.as_str());
+                let literal = person.get("literal").and_then(|s| s.as_str());
+                match (family, literal) {
+                    (Some(family), _) => Some(Person {
+                        family: family.to_string(),
+                        given: given.map(|s| s.to_string()),
+                    }),
+                    (None, Some(literal)) => Some(Person {
+                        family: literal.to_string(),
+                        given: None,
+                    }),
+                    (None, None) => None,
+                }
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let year = item.get("issued").and_then(|issued| {
+        match issued
+            .get("date-parts")
+            .and_then(|parts| parts.get(0))
+            .and_then(|parts| parts.get(0))
+        {
+            Some(Value::Number(year)) => Some(year.to_string()),
+            Some(Value::String(year)) => Some(year.clone()),
+            _ => issued
+                .get("literal")
+                .and_then(|s| s.as_str())
+                .map(|s| s.to_string()),
+        }
+    });
+
+    Ok(BibliographyEntry {
+        key,
+        authors,
+        title: get("title"),
+        container: get("container-title"),
+        publisher: get("publisher"),
+        volume: get("volume"),
+        issue: get("issue"),
+        pages: get("page"),
+        year,
+        url: get("URL"),
+        doi: get("DOI"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bibtex() {
+        let bibliography = Bibliography::parse(
+            r#"@article{knuth1984,
+  author = {Knuth, Donald E. and Lamport, Leslie},
+  title = {Literate {P}rogramming},
+  journal = {The Computer Journal},
+  year = 1984,
+  pages = {97--111},
+}"#,
+            "bib",
+        )
+        .unwrap();
+        let entry = bibliography.get("knuth1984").unwrap();
+        assert_eq!(entry.authors.len(), 2);
+        assert_eq!(entry.authors[0].get_full_name(), "Donald E. Knuth");
+        assert_eq!(entry.get_short_authors(), "Knuth and Lamport");
+        assert_eq!(entry.title.as_deref(), Some("Literate Programming"));
+        assert_eq!(entry.container.as_deref(), Some("The Computer Journal"));
+        assert_eq!(entry.pages.as_deref(), Some("97–111"));
+        assert_eq!(entry.get_year(), "1984");
+    }
+
+    #[test]
+    fn csl_json() {
+        let bibliography = Bibliography::parse(
+            r#"[
+  {
+    "id": "lamport1994",
+    "type": "book",
+    "title": "LaTeX",
+    "author": [{"family": "Lamport", "given": "Leslie"}],
+    "publisher": "Addison-Wesley",
+    "issued": {"date-parts": [[1994, 6]]}
+  },
+  {
+    "id": 42,
+    "author": [{"family": "山田", "given": "太郎"}, {"literal": "Example Org"}, {"given": "Nobody"}],
+    "issued": {"literal": "2020"}
+  },
+  {
+    "id": "undated"
+  }
+]"#,
+            "json",
+        )
+        .unwrap();
+        let entry = bibliography.get("lamport1994").unwrap();
+        assert_eq!(entry.authors[0].get_full_name(), "Leslie Lamport");
+        assert_eq!(entry.publisher.as_deref(), Some("Addison-Wesley"));
+        assert_eq!(entry.get_year(), "1994");
+
+        // 数値の id，literal の著者，姓のない著者の除外
+        let entry = bibliography.get("42").unwrap();
+        assert_eq!(entry.authors.len(), 2);
+        assert_eq!(entry.authors[0].get_full_name(), "山田 太郎");
+        assert_eq!(entry.authors[1].get_full_name(), "Example Org");
+        assert_eq!(entry.get_year(), "2020");
+
+        let entry = bibliography.get("undated").unwrap();
+        assert_eq!(entry.get_year(), "n.d.");
+        assert_eq!(entry.get_short_authors(), "undated");
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Bibliography::parse(r#"{"id": "a"}"#, "json").is_err());
+        assert!(Bibliography::parse(r#"[{"title": "no id"}]"#, "json").is_err());
+        assert!(Bibliography::parse("@misc{a,}\n@misc{a,}", "bib").is_err());
+        assert!(Bibliography::parse("", "ris").is_err());
+    }
+}
@@ -1,7 +1,14 @@
+pub(super) mod callout;
+pub(super) mod citation;
 pub(super) mod code;
 pub(super) mod decorators;
 pub(super) mod figure;
 pub(super) mod grid;
 pub(super) mod image;
+pub(super) mod japanese;
+pub(super) mod language;
 pub(super) mod list;
 pub(super) mod math;
+pub(super) mod reference;
+pub(super) mod table;
+pub(super) mod theorem;
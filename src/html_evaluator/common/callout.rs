@@ -0,0 +1,94 @@
+use anyhow::Result;
+
+use crate::{
+    evaluate_with_ld2html_evaluator, html_evaluator::litedown::Ld2HtmlEvaluator,
+    tree::function::LitedownFunction, utility::html::HtmlElement,
+};
+
+use super::decorators::validate_color;
+
+// 色はテーマ変数 (--note-color など) から決まる
+const CALLOUT_KINDS: [&str; 4] = ["note", "tip", "warning", "danger"];
+
+pub fn evaluate_note(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let title = &evaluator.get_preamble().locale.note;
+    evaluate_callout(evaluator, function, "note", Some(title))
+}
+
+pub fn evaluate_tip(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let title = &evaluator.get_preamble().locale.tip;
+    evaluate_callout(evaluator, function, "tip", Some(title))
+}
+
+pub fn evaluate_warning(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let title = &evaluator.get_preamble().locale.warning;
+    evaluate_callout(evaluator, function, "warning", Some(title))
+}
+
+pub fn evaluate_danger(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let title = &evaluator.get_preamble().locale.danger;
+    evaluate_callout(evaluator, function, "danger", Some(title))
+}
+
+pub fn evaluate_box(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    evaluate_callout(evaluator, function, "box", None)
+}
+
+fn evaluate_callout(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+    kind: &str,
+    default_title: Option<&str>,
+) -> Result<Option<HtmlElement>> {
+    let title = match function.arguments.get_by_name("title") {
+        Some(title) => Some(title.try_into_string()?),
+        None => default_title.map(|title| title.to_string()),
+    };
+
+    let mut callout_html = HtmlElement::new("div");
+    callout_html.set_attr("class", &format!("callout callout-{}", kind));
+    if let Some(color) = function.arguments.get_by_name("color") {
+        let color = color.try_into_string()?;
+        callout_html.set_attr("style", &format!("--callout-color: {}", get_color(&color)?));
+    }
+
+    // title="" で見出しを省略できる
+    if let Some(title) = title.filter(|title| !title.is_empty()) {
+        callout_html.append({
+            let mut heading_html = HtmlElement::new("div");
+            heading_html.set_attr("class", "heading");
+            heading_html.append_text(&title);
+            heading_html
+        });
+    }
+
+    let mut content_html = HtmlElement::new("div");
+    content_html.set_attr("class", "content");
+    evaluate_with_ld2html_evaluator!(function to content_html with evaluator);
+    callout_html.append(content_html);
+
+    Ok(Some(callout_html))
+}
+
+// "warning" などはテーマの色，それ以外は CSS の色として扱う
+fn get_color(color: &str) -> Result<String> {
+    if CALLOUT_KINDS.contains(&color) {
+        return Ok(format!("var(--{}-color)", color));
+    }
+    Ok(validate_color(color)?.to_string())
+}
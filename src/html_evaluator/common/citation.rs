@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+
+use crate::{
+    html_evaluator::{
+        bibliography::BibliographyEntry, litedown::Ld2HtmlEvaluator,
+        preamble::citation::CitationStyle,
+    },
+    tree::function::LitedownFunction,
+    utility::html::HtmlElement,
+};
+
+pub fn evaluate_cite(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    if !function.body.is_empty() {
+        bail!("cannot write body in 'cite'");
+    }
+
+    let mut keys = Vec::new();
+    for i in 0..function.arguments.len() {
+        if let Some(key) = function.arguments.get_by_index(i) {
+            keys.push(key.try_into_string()?);
+        }
+    }
+    if keys.is_empty() {
+        bail!("'cite' requires at least one key");
+    }
+    // ページ番号などの補足
+    let note = match function.arguments.get_by_name("note") {
+        Some(note) => Some(note.try_into_string()?),
+        None => None,
+    };
+
+    let style = &evaluator.get_preamble().citation.style;
+    let years = get_author_year_labels(evaluator);
+
+    let mut citation_html = HtmlElement::new("span");
+    citation_html.set_attr("class", "citation");
+    citation_html.append_text(match style {
+        CitationStyle::Numeric => "[",
+        CitationStyle::AuthorYear => "(",
+    });
+    for (i, key) in keys.iter().enumerate() {
+        let (number, entry) = match evaluator.cite(key) {
+            Ok(cited) => cited,
+            Err(e) => bail!(
+                "{}:{}: {} in 'cite[\"{}\"]'",
+                function.position.line,
+                function.position.column,
+                e,
+                keys.join("\", \"")
+            ),
+        };
+        if 0 < i {
+            citation_html.append_text(match style {
+                CitationStyle::Numeric => ", ",
+                CitationStyle::AuthorYear => "; ",
+            });
+        }
+        citation_html.append({
+            let mut anchor_html = HtmlElement::new("a");
+            anchor_html.set_attr("href", &format!("#{}", get_bibliography_entry_id(key)));
+            anchor_html.append_text(&match style {
+                CitationStyle::Numeric => number.to_string(),
+                CitationStyle::AuthorYear => format!(
+                    "{}, {}",
+                    entry.get_short_authors(),
+                    years.get(key).cloned().unwrap_or_else(|| entry.get_year()),
+                ),
+            });
+            anchor_html
+        });
+    }
+    if let Some(note) = note {
+        citation_html.append_text(&format!(", {}", note));
+    }
+    citation_html.append_text(match style {
+        CitationStyle::Numeric => "]",
+        CitationStyle::AuthorYear => ")",
+    });
+
+    Ok(Some(citation_html))
+}
+
+pub fn evaluate_bibliography(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    if !function.body.is_empty() {
+        bail!("cannot write body in 'bibliography'");
+    }
+
+    let title = match function.arguments.get_by_name("title") {
+        Some(title) => title.try_into_string()?,
+        None => evaluator.get_preamble().locale.bibliography.clone(),
+    };
+
+    let style = &evaluator.get_preamble().citation.style;
+    let years = get_author_year_labels(evaluator);
+
+    let mut bibliography_html = HtmlElement::new("div");
+    bibliography_html.set_attr("class", "bibliography");
+
+    bibliography_html.append({
+        let mut heading_html = HtmlElement::new("div");
+        heading_html.set_attr("class", "heading");
+        heading_html.append_text(&title);
+        heading_html
+    });
+
+    // 1 回目の評価では引用が未収集のため空になる
+    let mut entries: Vec<(usize, BibliographyEntry)> = evaluator
+        .get_cited_entries()
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| (i + 1, entry))
+        .collect();
+    if *style == CitationStyle::AuthorYear {
+        entries.sort_by_cached_key(|(_, entry)| {
+            (
+                entry.get_short_authors(),
+                years
+                    .get(&entry.key)
+                    .cloned()
+                    .unwrap_or_else(|| entry.get_year()),
+            )
+        });
+    }
+
+    let mut entries_html = HtmlElement::new("ol");
+    for (number, entry) in &entries {
+        let year = years
+            .get(&entry.key)
+            .cloned()
+            .unwrap_or_else(|| entry.get_year());
+        entries_html.append({
+            let mut entry_html = HtmlElement::new("li");
+            entry_html.set_attr("id", &get_bibliography_entry_id(&entry.key));
+            if *style == CitationStyle::Numeric {
+                entry_html.append({
+                    let mut number_html = HtmlElement::new("span");
+                    number_html.set_attr("class", "number");
+                    number_html.append_text(&format!("[{}]", number));
+                    number_html
+                });
+            }
+            entry_html.append(evaluate_bibliography_entry(entry, style, &year));
+            entry_html
+        });
+    }
+    bibliography_html.append(entries_html);
+
+    Ok(Some(bibliography_html))
+}
+
+fn get_bibliography_entry_id(key: &str) -> String {
+    format!("bibliography-{}", key)
+}
+
+fn evaluate_bibliography_entry(
+    entry: &BibliographyEntry,
+    style: &CitationStyle,
+    year: &str,
+) -> HtmlElement {
+    let mut entry_html = HtmlElement::new("span");
+    entry_html.set_attr("class", "entry");
+
+    let authors = join_authors(entry);
+    match style {
+        CitationStyle::Numeric => {
+            if !authors.is_empty() {
+                entry_html.append_text(&terminate(&authors));
+                entry_html.append_text(" ");
+            }
+        }
+        CitationStyle::AuthorYear => {
+            if !authors.is_empty() {
+                entry_html.append_text(&format!("{} ", authors));
+            }
+            entry_html.append_text(&format!("({}). ", year));
+        }
+    }
+
+    if let Some(title) = &entry.title {
+        entry_html.append({
+            let mut title_html = HtmlElement::new("span");
+            title_html.set_attr("class", "title");
+            title_html.append_text(&terminate(title));
+            title_html
+        });
+        entry_html.append_text(" ");
+    }
+
+    let mut details = Vec::new();
+    if let Some(container) = &entry.container {
+        entry_html.append({
+            let mut container_html = HtmlElement::new("span");
+            container_html.set_attr("class", "container");
+            container_html.append_text(container);
+            container_html
+        });
+    }
+    match (&entry.volume, &entry.issue) {
+        (Some(volume), Some(issue)) => details.push(format!("{}({})", volume, issue)),
+        (Some(volume), None) => details.push(volume.clone()),
+        (None, Some(issue)) => details.push(format!("({})", issue)),
+        (None, None) => {}
+    }
+    if let Some(pages) = &entry.pages {
+        details.push(format!("pp. {}", pages));
+    }
+    if let Some(publisher) = &entry.publisher {
+        details.push(publisher.clone());
+    }
+    if *style == CitationStyle::Numeric {
+        if let Some(year) = &entry.year {
+            details.push(year.clone());
+        }
+    }
+    match (entry.container.is_some(), details.is_empty()) {
+        (true, true) => {
+            entry_html.append_text(". ");
+        }
+        (true, false) => {
+            entry_html.append_text(&format!(", {}. ", details.join(", ")));
+        }
+        (false, false) => {
+            entry_html.append_text(&format!("{}. ", details.join(", ")));
+        }
+        (false, true) => {}
+    }
+
+    let link = match (&entry.doi, &entry.url) {
+        (Some(doi), _) => Some(format!("https://doi.org/{}", doi)),
+        (None, Some(url)) => Some(url.clone()),
+        (None, None) => None,
+    };
+    if let Some(link) = link {
+        entry_html.append({
+            let mut anchor_html = HtmlElement::new("a");
+            anchor_html.set_attr("href", &link);
+            anchor_html.append_text(&link);
+            anchor_html
+        });
+    }
+
+    entry_html
+}
+
+fn join_authors(entry: &BibliographyEntry) -> String {
+    let names: Vec<String> = entry
+        .authors
+        .iter()
+        .map(|author| author.get_full_name())
+        .collect();
+    match names.as_slice() {
+        [] => String::new(),
+        [name] => name.clone(),
+        [first, second] => format!("{} and {}", first, second),
+        [rest @ .., last] => format!("{}, and {}", rest.join(", "), last),
+    }
+}
+
+// 末尾に句点がなければ補う
+fn terminate(text: &str) -> String {
+    if text.ends_with(['.', '?', '!']) {
+        text.to_string()
+    } else {
+        format!("{}.", text)
+    }
+}
+
+// 著者と年が同じ文献を 2020a, 2020b のように区別する
+fn get_author_year_labels(evaluator: &Ld2HtmlEvaluator) -> HashMap<String, String> {
+    let mut groups: HashMap<(String, String), Vec<BibliographyEntry>> = HashMap::new();
+    for entry in evaluator.get_cited_entries().unwrap_or_default() {
+        groups
+            .entry((entry.get_short_authors(), entry.get_year()))
+            .or_default()
+            .push(entry);
+    }
+
+    let mut labels = HashMap::new();
+    for ((_, year), mut entries) in groups {
+        if entries.len() < 2 {
+            continue;
+        }
+        entries.sort_by_cached_key(|entry| entry.title.clone());
+        for (i, entry) in entries.iter().enumerate() {
+            let suffix = (b'a' + (i % 26) as u8) as char;
+            labels.insert(entry.key.clone(), format!("{}{}", year, suffix));
+        }
+    }
+    labels
+}
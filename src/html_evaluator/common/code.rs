@@ -1,6 +1,6 @@
-use std::{fs, path::PathBuf};
+use std::fs;
 
-use anyhow::{bail, Context, Result};
+use anyhow::{bail, Result};
 
 use crate::{
     html_evaluator::litedown::Ld2HtmlEvaluator,
@@ -20,14 +20,7 @@ pub fn evaluate_code(
     let code = match function.arguments.get_by_name("src") {
         Some(src) => {
             let src = src.try_into_string()?;
-            let path = if src.starts_with("/") {
-                PathBuf::from(src)
-            } else {
-                let source_path = evaluator
-                    .get_source_path()
-                    .context("cannot use relative path")?;
-                source_path.with_file_name(src)
-            };
+            let path = evaluator.resolve_path(&src)?;
 
             let extension = path.extension().and_then(|str| str.to_str()).unwrap_or("");
             if lang.is_none() {
@@ -1,4 +1,4 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 
 use crate::{
     deconstruct_required_arguments, evaluate_with_ld2html_evaluator,
@@ -25,6 +25,98 @@ pub fn evaluate_attention(
     Ok(Some(attention_html))
 }
 
+pub fn evaluate_emphasis(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let mut emphasis_html = HtmlElement::new("em");
+    evaluate_with_ld2html_evaluator!(function to emphasis_html with evaluator);
+    Ok(Some(emphasis_html))
+}
+
+pub fn evaluate_underline(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let mut underline_html = HtmlElement::new("u");
+    evaluate_with_ld2html_evaluator!(function to underline_html with evaluator);
+    Ok(Some(underline_html))
+}
+
+pub fn evaluate_strikethrough(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let mut strikethrough_html = HtmlElement::new("s");
+    evaluate_with_ld2html_evaluator!(function to strikethrough_html with evaluator);
+    Ok(Some(strikethrough_html))
+}
+
+pub fn evaluate_subscript(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let mut subscript_html = HtmlElement::new("sub");
+    evaluate_with_ld2html_evaluator!(function to subscript_html with evaluator);
+    Ok(Some(subscript_html))
+}
+
+pub fn evaluate_superscript(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let mut superscript_html = HtmlElement::new("sup");
+    evaluate_with_ld2html_evaluator!(function to superscript_html with evaluator);
+    Ok(Some(superscript_html))
+}
+
+pub fn evaluate_small_caps(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let mut small_caps_html = HtmlElement::new("span");
+    small_caps_html.set_attr("class", "small-caps");
+    evaluate_with_ld2html_evaluator!(function to small_caps_html with evaluator);
+    Ok(Some(small_caps_html))
+}
+
+pub fn evaluate_marker(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let color = match function.arguments.get_by_name("color") {
+        Some(color) => color.try_into_string()?,
+        None => "yellow".to_string(),
+    };
+    // 蛍光ペンらしい淡い色を用意しておく
+    let color = match color.as_str() {
+        "yellow" => "#fff176",
+        "green" => "#b9f6ca",
+        "blue" => "#b3e5fc",
+        "pink" => "#f8bbd0",
+        "orange" => "#ffcc80",
+        color => validate_color(color)?,
+    };
+
+    let mut marker_html = HtmlElement::new("mark");
+    marker_html.set_attr("style", &format!("--marker-color: {}", color));
+    evaluate_with_ld2html_evaluator!(function to marker_html with evaluator);
+    Ok(Some(marker_html))
+}
+
+pub fn evaluate_color(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    deconstruct_required_arguments!((color) from function);
+    let color = color.try_into_string()?;
+
+    let mut color_html = HtmlElement::new("span");
+    color_html.set_attr("style", &format!("color: {}", validate_color(&color)?));
+    evaluate_with_ld2html_evaluator!(function to color_html with evaluator);
+    Ok(Some(color_html))
+}
+
 pub fn evaluate_divider(_: &Ld2HtmlEvaluator, _: &LitedownFunction) -> Result<Option<HtmlElement>> {
     let hr = HtmlElement::new_void("hr");
     Ok(Some(hr))
@@ -55,3 +147,25 @@ pub fn evaluate_link(
 
     Ok(Some(anchor_html))
 }
+
+// style 属性に埋め込むため，CSS の色として使える文字のみ許す
+pub(super) fn validate_color(color: &str) -> Result<&str> {
+    let valid = !color.is_empty()
+        && color
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "#(),.% ".contains(c));
+    if !valid {
+        bail!("invalid color: '{}'", color);
+    }
+    Ok(color)
+}
+
+// 表記ゆれの検査 (--check) の対象から外す
+pub fn evaluate_nocheck(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let mut span_html = HtmlElement::new("span");
+    evaluate_with_ld2html_evaluator!(function to span_html with evaluator);
+    Ok(Some(span_html))
+}
@@ -1,55 +1,73 @@
 use anyhow::{bail, Result};
 
 use crate::{
-    evaluate_with_ld2html_evaluator, html_evaluator::litedown::Ld2HtmlEvaluator,
-    tree::function::LitedownFunction, utility::html::HtmlElement,
+    evaluate_with_ld2html_evaluator,
+    html_evaluator::{
+        document::section::format_section_prefix,
+        litedown::Ld2HtmlEvaluator,
+        preamble::caption::CaptionNumbering,
+        reference::{ListingEntry, ListingKind, Reference},
+    },
+    tree::function::LitedownFunction,
+    utility::html::HtmlElement,
 };
 
+pub(super) enum FigureKind {
+    Figure,
+    Table,
+}
+
+impl FigureKind {
+    fn to_counter_name(&self) -> &str {
+        match self {
+            FigureKind::Figure => "figure",
+            FigureKind::Table => "table",
+        }
+    }
+
+    fn to_listing_kind(&self) -> ListingKind {
+        match self {
+            FigureKind::Figure => ListingKind::Figure,
+            FigureKind::Table => ListingKind::Table,
+        }
+    }
+}
+
 pub fn evaluate_figure(
     evaluator: &Ld2HtmlEvaluator,
     function: &LitedownFunction,
 ) -> Result<Option<HtmlElement>> {
+    let kind = match function.arguments.get_by_name("kind") {
+        Some(kind) => match kind.try_into_string()?.as_str() {
+            "figure" => FigureKind::Figure,
+            "table" => FigureKind::Table,
+            kind => bail!("unknown figure kind: {}", kind),
+        },
+        None => FigureKind::Figure,
+    };
     let mut figure_content_html = HtmlElement::new("div");
     figure_content_html.set_attr("class", "content");
 
+    let id = evaluator.generate_id("figure");
+
     let mut figcaption_html = None;
 
     evaluate_with_ld2html_evaluator!(function to figure_content_html with evaluator;
         function: {
             caption: (child_function) => {
-                figcaption_html = {
-                    let mut figcaption_html = HtmlElement::new("figcaption");
-
-                    figcaption_html.append({
-                        let mut figcaption_tag_html = HtmlElement::new("div");
-                        if let Some(raw_tag) = child_function.arguments.get_by_name("raw_tag") {
-                            let raw_tag = raw_tag.try_into_string()?;
-                            figcaption_tag_html.append_text(&raw_tag);
-                        } else {
-                            bail!("no tag found");
-                        }
-
-                        figcaption_tag_html
-                    });
-
-                    if !child_function.body.is_empty() {
-                        figcaption_html.append_text("：");
-                        figcaption_html.append({
-                            let mut figcaption_content_html = HtmlElement::new("div");
-                            figcaption_content_html.set_attr("class", "content");
-                            evaluate_with_ld2html_evaluator!(child_function to figcaption_content_html with evaluator);
-
-                            figcaption_content_html
-                        });
-                    }
-
-                    Some(figcaption_html)
-                }
+                figcaption_html = Some(evaluate_caption(
+                    evaluator,
+                    child_function,
+                    &kind,
+                    &id,
+                    HtmlElement::new("figcaption"),
+                )?);
             }
         }
     );
 
     let mut figure_html = HtmlElement::new("figure");
+    figure_html.set_attr("id", &id);
 
     figure_html.append(figure_content_html);
 
@@ -60,3 +78,74 @@ pub fn evaluate_figure(
 
     Ok(Some(figure_html))
 }
+
+// 番号を振り，ラベルと図目次・表目次の項目を登録する
+pub(super) fn evaluate_caption(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+    kind: &FigureKind,
+    id: &str,
+    mut caption_html: HtmlElement,
+) -> Result<HtmlElement> {
+    let caption = &evaluator.get_preamble().caption;
+    let locale = &evaluator.get_preamble().locale;
+
+    let tag = if let Some(raw_tag) = function.arguments.get_by_name("raw_tag") {
+        raw_tag.try_into_string()?
+    } else {
+        let label = match kind {
+            FigureKind::Figure => &locale.figure,
+            FigureKind::Table => &locale.table,
+        };
+        let number = locale
+            .caption_number
+            .format(evaluator.step_counter(kind.to_counter_name()));
+        let number = match caption.numbering {
+            CaptionNumbering::Document => number,
+            CaptionNumbering::Section => format!(
+                "{}.{}",
+                format_section_prefix(evaluator, kind.to_counter_name())?,
+                number
+            ),
+        };
+        locale.format_tag(label, &number)
+    };
+
+    caption_html.append({
+        let mut caption_tag_html = HtmlElement::new("div");
+        caption_tag_html.append_text(&tag);
+        caption_tag_html
+    });
+
+    let caption_content_html = if function.body.is_empty() {
+        None
+    } else {
+        let mut caption_content_html = HtmlElement::new("div");
+        caption_content_html.set_attr("class", "content");
+        evaluate_with_ld2html_evaluator!(function to caption_content_html with evaluator);
+
+        caption_html.append_text(&locale.separator);
+        caption_html.append(caption_content_html.clone());
+        Some(caption_content_html)
+    };
+
+    if let Some(label) = function.arguments.get_by_name("label") {
+        evaluator.define_label(
+            &label.try_into_string()?,
+            Reference {
+                id: id.to_string(),
+                number: tag.clone(),
+            },
+        )?;
+    }
+
+    evaluator.add_listing_entry(ListingEntry {
+        kind: kind.to_listing_kind(),
+        id: id.to_string(),
+        number: Some(tag),
+        title: caption_content_html,
+        level: 1,
+    });
+
+    Ok(caption_html)
+}
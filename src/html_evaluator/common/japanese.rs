@@ -0,0 +1,114 @@
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    deconstruct_required_arguments, evaluate_with_ld2html_evaluator,
+    html_evaluator::litedown::Ld2HtmlEvaluator, tree::function::LitedownFunction,
+    utility::html::HtmlElement,
+};
+
+// @ruby["かんじ"]{漢字} は熟語全体に，@ruby["かん じ"]{漢字} は 1 文字ずつにルビを振る
+pub fn evaluate_ruby(
+    _: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    deconstruct_required_arguments!((reading) from function);
+    let reading = reading.try_into_string()?;
+    let base = function
+        .body
+        .try_get_as_string()
+        .context("ruby base must be plain text")?;
+    if base.is_empty() {
+        bail!("ruby base cannot be empty");
+    }
+
+    let readings: Vec<&str> = reading.split_whitespace().collect();
+    let pairs: Vec<(String, &str)> = match readings.as_slice() {
+        [] => bail!("ruby reading cannot be empty"),
+        [reading] => vec![(base, reading)],
+        readings => {
+            let chars: Vec<char> = base.chars().collect();
+            if chars.len() != readings.len() {
+                bail!(
+                    "ruby has {} readings but base '{}' has {} characters",
+                    readings.len(),
+                    base,
+                    chars.len()
+                );
+            }
+            chars
+                .iter()
+                .map(|c| c.to_string())
+                .zip(readings.iter().copied())
+                .collect()
+        }
+    };
+
+    let mut ruby_html = HtmlElement::new("ruby");
+    for (base, reading) in pairs {
+        ruby_html.append_text(&base);
+        // ruby に対応していない環境では括弧書きになる
+        ruby_html.append({
+            let mut rp_html = HtmlElement::new("rp");
+            rp_html.append_text("(");
+            rp_html
+        });
+        ruby_html.append({
+            let mut rt_html = HtmlElement::new("rt");
+            rt_html.append_text(reading);
+            rt_html
+        });
+        ruby_html.append({
+            let mut rp_html = HtmlElement::new("rp");
+            rp_html.append_text(")");
+            rp_html
+        });
+    }
+    Ok(Some(ruby_html))
+}
+
+// 傍点 (圏点)
+pub fn evaluate_bouten(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let style = match function.arguments.get_by_name("style") {
+        Some(style) => style.try_into_string()?,
+        None => "sesame".to_string(),
+    };
+    // "open-circle" のように open- を付けると白抜きになる
+    let (fill, shape) = match style.strip_prefix("open-") {
+        Some(shape) => ("open", shape),
+        None => ("filled", style.as_str()),
+    };
+    if !["sesame", "dot", "circle", "double-circle", "triangle"].contains(&shape) {
+        bail!("unknown bouten style: '{}'", style);
+    }
+
+    let mut bouten_html = HtmlElement::new("span");
+    bouten_html.set_attr("class", "bouten");
+    bouten_html.set_attr("style", &format!("text-emphasis-style: {} {}", fill, shape));
+    evaluate_with_ld2html_evaluator!(function to bouten_html with evaluator);
+    Ok(Some(bouten_html))
+}
+
+// 縦中横 (縦書きの中で数字などを横に並べる)
+pub fn evaluate_tcy(
+    _: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let text = function
+        .body
+        .try_get_as_string()
+        .context("tcy must be plain text")?;
+    if text.is_empty() {
+        bail!("tcy cannot be empty");
+    }
+    if 4 < text.chars().count() {
+        bail!("tcy text is too long: '{}'", text);
+    }
+
+    let mut tcy_html = HtmlElement::new("span");
+    tcy_html.set_attr("class", "tcy");
+    tcy_html.append_text(&text);
+    Ok(Some(tcy_html))
+}
@@ -0,0 +1,35 @@
+use anyhow::Result;
+
+use crate::{
+    deconstruct_required_arguments, evaluate_with_ld2html_evaluator,
+    html_evaluator::{litedown::Ld2HtmlEvaluator, preamble::language::validate_language},
+    tree::function::{FunctionBodyForm, LitedownFunction},
+    utility::html::HtmlElement,
+};
+
+// @lang["en"]{...} は本文と異なる言語の部分を示す
+pub fn evaluate_lang(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    deconstruct_required_arguments!((language) from function);
+    let language = language.try_into_string()?;
+
+    let mut lang_html = match function.body.form {
+        FunctionBodyForm::Block => HtmlElement::new("div"),
+        FunctionBodyForm::Inline => HtmlElement::new("span"),
+    };
+    lang_html.set_attr("lang", validate_language(&language)?);
+    evaluate_with_ld2html_evaluator!(function to lang_html with evaluator);
+    Ok(Some(lang_html))
+}
+
+// 引用符は言語ごとに CSS の quotes で決まる
+pub fn evaluate_quote(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let mut quote_html = HtmlElement::new("q");
+    evaluate_with_ld2html_evaluator!(function to quote_html with evaluator);
+    Ok(Some(quote_html))
+}
@@ -1,13 +1,35 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 
 use crate::{
-    html_evaluator::litedown::Ld2HtmlEvaluator,
+    html_evaluator::{
+        document::section::format_section_prefix,
+        litedown::Ld2HtmlEvaluator,
+        preamble::{equation::EquationNumbering, math::Math},
+        reference::Reference,
+    },
     tree::function::{FunctionBodyForm, LitedownFunction, PassageElement},
-    utility::html::HtmlElement,
+    utility::{html::HtmlElement, mathml::tex_to_mathml},
 };
 
+// 行ごとに番号を振る環境
+const MULTILINE_ENVIRONMENTS: [&str; 10] = [
+    "align",
+    "align*",
+    "alignat",
+    "alignat*",
+    "flalign",
+    "flalign*",
+    "gather",
+    "gather*",
+    "eqnarray",
+    "eqnarray*",
+];
+
+// 全体に 1 つの番号を振る環境
+const SINGLE_LINE_ENVIRONMENTS: [&str; 4] = ["equation", "equation*", "multline", "multline*"];
+
 pub fn evaluate_math(
-    _: &Ld2HtmlEvaluator,
+    evaluator: &Ld2HtmlEvaluator,
     function: &LitedownFunction,
 ) -> Result<Option<HtmlElement>> {
     if function.body.is_empty() {
@@ -25,11 +47,12 @@ pub fn evaluate_math(
             container
         }
     };
+    let mut tex = String::new();
     for passage in &function.body.value {
         for passage_element in &passage.elements {
             match passage_element {
-                PassageElement::String(string) => {
-                    container.append_raw_text(string);
+                PassageElement::String(string, _) => {
+                    tex.push_str(string);
                 }
                 PassageElement::Function(_) => {
                     bail!("cannot write function in function 'math'");
@@ -37,5 +60,324 @@ pub fn evaluate_math(
             }
         }
     }
+
+    match function.body.form {
+        FunctionBodyForm::Block => {
+            let id = evaluator.generate_id("equation");
+            if let Some(numbered_tex) = number_equation(evaluator, function, &tex, &id)? {
+                container.set_attr("id", &id);
+                tex = numbered_tex;
+            }
+        }
+        FunctionBodyForm::Inline => {
+            if function.arguments.get_by_name("label").is_some() {
+                bail!("cannot put label on inline 'math'");
+            }
+        }
+    }
+    match evaluator.get_preamble().math {
+        Some(Math::Mathml) => {
+            let display = function.body.form == FunctionBodyForm::Block;
+            let macros = &evaluator.get_preamble().math_macros.macros;
+            let mathml = tex_to_mathml(&tex, display, macros)
+                .with_context(|| format!("invalid math: {}", tex.trim()))?;
+            container.append_raw_text(&mathml);
+        }
+        _ => {
+            container.append_raw_text(&tex);
+        }
+    }
+
     Ok(Some(container))
 }
+
+// 番号を振らない場合は None を返す
+fn number_equation(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+    tex: &str,
+    id: &str,
+) -> Result<Option<String>> {
+    let nonumber = match function.arguments.get_by_name("nonumber") {
+        Some(nonumber) => nonumber.try_into_boolean()?,
+        None => false,
+    };
+    let label = match function.arguments.get_by_name("label") {
+        Some(label) => Some(label.try_into_string()?),
+        None => None,
+    };
+    if nonumber {
+        if label.is_some() || tex.contains("\\label{") {
+            bail!("cannot put label on 'math' with 'nonumber'");
+        }
+        return Ok(None);
+    }
+
+    // align* などの * 付きの環境は，\label を書いた行だけに番号を振る
+    let starred = get_environment(tex.trim()).is_some_and(|(name, ..)| name.ends_with('*'));
+    let numbered_by_default =
+        evaluator.get_preamble().equation_numbering != EquationNumbering::None && !starred;
+    if !numbered_by_default && label.is_none() && !tex.contains("\\label{") {
+        return Ok(None);
+    }
+
+    let define_label = |label: &str, tag: &str| {
+        evaluator.define_label(
+            label,
+            Reference {
+                id: id.to_string(),
+                number: format!("({})", tag),
+            },
+        )
+    };
+
+    let trimmed = tex.trim();
+    match get_environment(trimmed) {
+        Some((name, begin, body, end)) if MULTILINE_ENVIRONMENTS.contains(&name) => {
+            if label.is_some() {
+                bail!("use '\\label' in each line of multi-line 'math'");
+            }
+            let mut rows = Vec::new();
+            for row in split_rows(body) {
+                let (row, row_label) = extract_label(&row)?;
+                let numbered = (numbered_by_default || row_label.is_some())
+                    && !row.trim().is_empty()
+                    && !has_custom_tag(&row);
+                if numbered {
+                    let tag = step_equation_number(evaluator)?;
+                    if let Some(row_label) = row_label {
+                        define_label(&row_label, &tag)?;
+                    }
+                    rows.push(format!("{} \\tag{{{}}} ", row, tag));
+                } else {
+                    if let Some(row_label) = row_label {
+                        bail!("cannot put label '{}' on unnumbered line", row_label);
+                    }
+                    rows.push(row);
+                }
+            }
+            Ok(Some(format!("{}{}{}", begin, rows.join("\\\\"), end)))
+        }
+        _ => {
+            let (tex, inner_label) = extract_label(trimmed)?;
+            let label = match (label, inner_label) {
+                (Some(_), Some(_)) => bail!("'math' got multiple labels"),
+                (label, inner_label) => label.or(inner_label),
+            };
+            if has_custom_tag(&tex) {
+                if let Some(label) = label {
+                    bail!("cannot put label '{}' on equation with custom tag", label);
+                }
+                return Ok(None);
+            }
+
+            let tag = step_equation_number(evaluator)?;
+            if let Some(label) = label {
+                define_label(&label, &tag)?;
+            }
+            // \tag は equation 環境などの内側に置く
+            match get_environment(&tex) {
+                Some((name, begin, body, end)) if SINGLE_LINE_ENVIRONMENTS.contains(&name) => {
+                    Ok(Some(format!("{}{} \\tag{{{}}} {}", begin, body, tag, end)))
+                }
+                _ => Ok(Some(format!("{} \\tag{{{}}}", tex, tag))),
+            }
+        }
+    }
+}
+
+fn step_equation_number(evaluator: &Ld2HtmlEvaluator) -> Result<String> {
+    let number = evaluator.step_counter("equation");
+    Ok(match evaluator.get_preamble().equation_numbering {
+        EquationNumbering::Section => {
+            format!("{}.{}", format_section_prefix(evaluator, "math")?, number)
+        }
+        _ => number.to_string(),
+    })
+}
+
+fn has_custom_tag(tex: &str) -> bool {
+    ["\\tag{", "\\tag*{", "\\notag", "\\nonumber"]
+        .iter()
+        .any(|command| tex.contains(command))
+}
+
+// 全体が 1 つの環境で囲まれていれば (環境名, \begin{...}, 中身, \end{...}) を返す
+fn get_environment(tex: &str) -> Option<(&str, &str, &str, &str)> {
+    let rest = tex.strip_prefix("\\begin{")?;
+    let name = &rest[..rest.find('}')?];
+    let begin_length = "\\begin{}".len() + name.len();
+    let end = format!("\\end{{{}}}", name);
+    if !tex.ends_with(&end) || tex.len() < begin_length + end.len() {
+        return None;
+    }
+    Some((
+        name,
+        &tex[..begin_length],
+        &tex[begin_length..tex.len() - end.len()],
+        &tex[tex.len() - end.len()..],
+    ))
+}
+
+// 括弧や入れ子の環境の内側にない \\ で区切る
+fn split_rows(body: &str) -> Vec<String> {
+    let mut rows = Vec::new();
+    let mut row = String::new();
+    let mut depth = 0;
+    let mut rest = body;
+    while let Some(c) = rest.chars().next() {
+        if c == '\\' {
+            if rest.starts_with("\\\\") && depth == 0 {
+                rows.push(std::mem::take(&mut row));
+                rest = &rest[2..];
+                continue;
+            }
+            if rest.starts_with("\\begin{") {
+                depth += 1;
+            } else if rest.starts_with("\\end{") {
+                depth -= 1;
+            }
+            // \{ や \\ などを 1 つの単位として扱う
+            let length = 1 + rest[1..].chars().next().map_or(0, |c| c.len_utf8());
+            row.push_str(&rest[..length]);
+            rest = &rest[length..];
+            continue;
+        }
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+        row.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    rows.push(row);
+    rows
+}
+
+// \label{...} を取り除き，ラベル名を返す
+fn extract_label(tex: &str) -> Result<(String, Option<String>)> {
+    let start = match tex.find("\\label{") {
+        Some(start) => start,
+        None => return Ok((tex.to_string(), None)),
+    };
+    let name_start = start + "\\label{".len();
+    let name_end = match tex[name_start..].find('}') {
+        Some(length) => name_start + length,
+        None => bail!("unclosed '\\label' in 'math'"),
+    };
+    let rest = &tex[name_end + 1..];
+    if rest.contains("\\label{") {
+        bail!("equation got multiple '\\label'");
+    }
+    Ok((
+        format!("{}{}", &tex[..start], rest),
+        Some(tex[name_start..name_end].trim().to_string()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        html_evaluator::litedown::{evaluate_litedown_to_html, Ld2HtmlInput},
+        parser::litedown::parse_litedown,
+    };
+
+    fn evaluate(numbering: &str, body: &str) -> String {
+        let source = format!(
+            "@preamble@\n  @page_size[\"a4\"]\n  @equation[numbering=\"{}\"]\n\n@document@\n  @section[title=\"A\"]@\n{}\n",
+            numbering, body
+        );
+        let output = evaluate_litedown_to_html(Ld2HtmlInput {
+            ast: parse_litedown(&source).unwrap(),
+            source_path: None,
+        })
+        .unwrap();
+        output.html.to_string().get_body()
+    }
+
+    fn count_tags(html: &str) -> Vec<String> {
+        html.split("\\tag{")
+            .skip(1)
+            .map(|rest| rest[..rest.find('}').unwrap()].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn numbered_environments() {
+        let html = evaluate(
+            "document",
+            r#"    @math@
+      \begin{align}
+      a &= b \\
+      c &= d \notag \\
+      e &= f
+      \end{align}
+
+    @math@
+      \begin{equation} x \end{equation}
+
+    @math@
+      y"#,
+        );
+        assert_eq!(count_tags(&html), ["1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn starred_environments() {
+        for numbering in ["document", "section"] {
+            let html = evaluate(
+                numbering,
+                r#"    @math@
+      \begin{align*}
+      a &= b \\
+      c &= d
+      \end{align*}
+
+    @math@
+      \begin{gather*} x \\ y \end{gather*}
+
+    @math@
+      \begin{equation*} z \end{equation*}
+
+    @math@
+      \begin{multline*} p \\ q \end{multline*}
+
+    @math@
+      w"#,
+            );
+            // * 付きの環境は番号を消費しない
+            let expected = if numbering == "document" { "1" } else { "1.1" };
+            assert_eq!(count_tags(&html), [expected], "{}", numbering);
+        }
+    }
+
+    #[test]
+    fn starred_environments_with_label() {
+        let html = evaluate(
+            "document",
+            r#"    @math@
+      \begin{align*}
+      a &= b \label{first} \\
+      c &= d \\
+      e &= f \tag{A}
+      \end{align*}
+
+    @math[label="second"]@
+      \begin{equation*} z \end{equation*}
+
+    @math@
+      \begin{equation*} w \label{third} \end{equation*}
+
+    See @ref["first"], @ref["second"], @ref["third"]."#,
+        );
+        assert_eq!(count_tags(&html), ["1", "A", "2", "3"]);
+        for (id, number) in [
+            ("equation-1", "1"),
+            ("equation-2", "2"),
+            ("equation-3", "3"),
+        ] {
+            assert!(html.contains(&format!("href=\"#{}\">({})</a>", id, number)));
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use anyhow::{bail, Result};
+
+use crate::{
+    deconstruct_required_arguments, html_evaluator::litedown::Ld2HtmlEvaluator,
+    tree::function::LitedownFunction, utility::html::HtmlElement,
+};
+
+pub fn evaluate_reference(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    if !function.body.is_empty() {
+        bail!("cannot write body in 'ref'");
+    }
+
+    deconstruct_required_arguments!((label) from function);
+    let label = label.try_into_string()?;
+
+    let mut anchor_html = HtmlElement::new("a");
+    anchor_html.set_attr("class", "reference");
+    match evaluator.resolve_label(&label)? {
+        Some(reference) => {
+            anchor_html.set_attr("href", &format!("#{}", reference.id));
+            anchor_html.append_text(&reference.number);
+        }
+        None => {
+            anchor_html.append_text("??");
+        }
+    }
+
+    Ok(Some(anchor_html))
+}
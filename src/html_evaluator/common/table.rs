@@ -0,0 +1,592 @@
+use std::fs;
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    evaluate_litedown_function, evaluate_with_ld2html_evaluator,
+    html_evaluator::{
+        common::figure::{evaluate_caption, FigureKind},
+        litedown::Ld2HtmlEvaluator,
+    },
+    tree::{function::LitedownFunction, function_argument::FunctionArgumentValue},
+    utility::{csv::parse_csv, html::HtmlElement},
+};
+
+pub fn evaluate_table(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    // 列ごとの揃え
+    let alignments = match function.arguments.get_by_name("align") {
+        Some(align) => align
+            .try_into_array()?
+            .iter()
+            .map(|align| parse_alignment(&align.to_string()))
+            .collect::<Result<Vec<_>>>()?,
+        None => Vec::new(),
+    };
+    let striped = match function.arguments.get_by_name("striped") {
+        Some(striped) => striped.try_into_boolean()?,
+        None => false,
+    };
+    // CSV または TSV ファイル
+    let src = match function.arguments.get_by_name("src") {
+        Some(src) => Some(src.try_into_string()?),
+        None => None,
+    };
+
+    let id = evaluator.generate_id("table");
+
+    let mut table_html = HtmlElement::new("table");
+    table_html.set_attr("id", &id);
+    table_html.set_attr("class", if striped { "table striped" } else { "table" });
+
+    let mut caption_html = None;
+    let mut thead_html = HtmlElement::new("thead");
+    let mut tbody_html = HtmlElement::new("tbody");
+
+    // rowspan によって埋まっている残りの行数 (列ごと)
+    let mut occupied: Vec<usize> = Vec::new();
+    let mut column_count = None;
+    let mut row_index = 0;
+
+    evaluate_litedown_function!(function;
+        caption: (child_function) => {
+            if caption_html.is_some() {
+                bail!("'table' got multiple 'caption'");
+            }
+            caption_html = Some(evaluate_caption(
+                evaluator,
+                child_function,
+                &FigureKind::Table,
+                &id,
+                HtmlElement::new("caption"),
+            )?);
+        }
+        row: (child_function) => {
+            if src.is_some() {
+                bail!("cannot write 'row' in 'table' with 'src'");
+            }
+            row_index += 1;
+            let header = match child_function.arguments.get_by_name("header") {
+                Some(header) => header.try_into_boolean()?,
+                None => false,
+            };
+            if header && !tbody_html.is_child_empty() {
+                bail!("header row must precede body rows (row {})", row_index);
+            }
+
+            let mut row_html = HtmlElement::new("tr");
+            let mut column = 0;
+            let mut cell_index = 0;
+
+            evaluate_litedown_function!(child_function;
+                cell: (cell_function) => {
+                    cell_index += 1;
+                    while occupied.get(column).is_some_and(|&rows| 0 < rows) {
+                        column += 1;
+                    }
+
+                    let colspan = get_span(cell_function, "colspan")?;
+                    let rowspan = get_span(cell_function, "rowspan")?;
+                    // colspan で広げた先が，前の行の rowspan で埋まっていてはいけない
+                    if occupied.iter().skip(column).take(colspan).any(|&rows| 0 < rows) {
+                        bail!(
+                            "cell {} in row {} overlaps a cell spanning from a previous row",
+                            cell_index,
+                            row_index
+                        );
+                    }
+                    let header_cell = header || match cell_function.arguments.get_by_name("header") {
+                        Some(header) => header.try_into_boolean()?,
+                        None => false,
+                    };
+
+                    let mut cell_html = HtmlElement::new(if header_cell { "th" } else { "td" });
+                    if header_cell {
+                        cell_html.set_attr("scope", if header { "col" } else { "row" });
+                    }
+                    if 1 < colspan {
+                        cell_html.set_attr("colspan", &colspan.to_string());
+                    }
+                    if 1 < rowspan {
+                        cell_html.set_attr("rowspan", &rowspan.to_string());
+                    }
+
+                    let alignment = match cell_function.arguments.get_by_name("align") {
+                        Some(align) => Some(parse_alignment(&align.try_into_string()?)?),
+                        None => alignments.get(column).cloned(),
+                    };
+                    if let Some(alignment) = alignment {
+                        cell_html.set_attr("data-align", &alignment);
+                    }
+
+                    evaluate_with_ld2html_evaluator!(cell_function to cell_html with evaluator);
+                    row_html.append(cell_html);
+
+                    if occupied.len() < column + colspan {
+                        occupied.resize(column + colspan, 0);
+                    }
+                    for rows in &mut occupied[column..column + colspan] {
+                        *rows = rowspan;
+                    }
+                    column += colspan;
+                }
+            );
+
+            // 前の行の rowspan で埋まっている末尾の列
+            while occupied.get(column).is_some_and(|&rows| 0 < rows) {
+                column += 1;
+            }
+            match column_count {
+                Some(column_count) if column_count != column => {
+                    bail!(
+                        "row {} has {} columns, but previous rows have {}",
+                        row_index,
+                        column,
+                        column_count
+                    );
+                }
+                _ => column_count = Some(column),
+            }
+            for rows in &mut occupied {
+                *rows = rows.saturating_sub(1);
+            }
+
+            if header {
+                thead_html.append(row_html);
+            } else {
+                tbody_html.append(row_html);
+            }
+        }
+    );
+
+    if let Some(src) = &src {
+        evaluate_csv_table(
+            evaluator,
+            function,
+            src,
+            &alignments,
+            &mut thead_html,
+            &mut tbody_html,
+        )?;
+    }
+
+    if occupied.iter().any(|&rows| 0 < rows) {
+        bail!("rowspan exceeds the number of rows in 'table'");
+    }
+
+    if let Some(caption_html) = caption_html {
+        table_html.append(caption_html);
+    }
+    if !thead_html.is_child_empty() {
+        table_html.append(thead_html);
+    }
+    table_html.append(tbody_html);
+
+    Ok(Some(table_html))
+}
+
+fn evaluate_csv_table(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+    src: &str,
+    alignments: &[String],
+    thead_html: &mut HtmlElement,
+    tbody_html: &mut HtmlElement,
+) -> Result<()> {
+    let path = evaluator.resolve_path(src)?;
+    let extension = path.extension().and_then(|str| str.to_str()).unwrap_or("");
+    let delimiter = match extension {
+        "csv" => ',',
+        "tsv" => '\t',
+        _ => bail!("unknown extension: {}", extension),
+    };
+    let source = match fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(e) => bail!("could not read table: {}", e),
+    };
+    let mut records =
+        parse_csv(&source, delimiter).with_context(|| format!("failed to parse {}", src))?;
+
+    let header = match function.arguments.get_by_name("header") {
+        Some(header) => header.try_into_boolean()?,
+        None => true,
+    };
+    let header_record = if header {
+        if records.is_empty() {
+            bail!("header row not found in {}", src);
+        }
+        Some(records.remove(0))
+    } else {
+        None
+    };
+
+    let width = records
+        .iter()
+        .chain(header_record.iter())
+        .map(|record| record.len())
+        .max()
+        .unwrap_or(0);
+
+    // 列名 (文字列) または 1 始まりの列番号で選択する
+    let columns = match function.arguments.get_by_name("columns") {
+        Some(columns) => columns
+            .try_into_array()?
+            .iter()
+            .map(|column| match column {
+                FunctionArgumentValue::Integer { number, .. } => {
+                    if *number < 1 || width < *number as usize {
+                        bail!("column {} is out of range in {}", number, src);
+                    }
+                    Ok(*number as usize - 1)
+                }
+                column => {
+                    let name = column.to_string();
+                    header_record
+                        .as_ref()
+                        .and_then(|record| record.iter().position(|cell| *cell == name))
+                        .with_context(|| format!("column '{}' not found in {}", name, src))
+                }
+            })
+            .collect::<Result<Vec<_>>>()?,
+        None => (0..width).collect(),
+    };
+
+    let names = match function.arguments.get_by_name("rename") {
+        Some(rename) => {
+            let names: Vec<String> = rename
+                .try_into_array()?
+                .iter()
+                .map(|name| name.to_string())
+                .collect();
+            if names.len() != columns.len() {
+                bail!("'rename' must have {} names", columns.len());
+            }
+            Some(names)
+        }
+        None => header_record.map(|record| {
+            columns
+                .iter()
+                .map(|&column| record.get(column).cloned().unwrap_or_default())
+                .collect()
+        }),
+    };
+
+    // 全列共通の桁数，または列ごとの桁数
+    let decimals = match function.arguments.get_by_name("decimals") {
+        Some(decimals) => match &decimals.value {
+            FunctionArgumentValue::Array { value } => {
+                let decimals = value
+                    .iter()
+                    .map(|decimals| match decimals {
+                        FunctionArgumentValue::Integer { number, .. } if 0 <= *number => {
+                            Ok(Some(*number as usize))
+                        }
+                        _ => bail!("invalid decimals: {}", decimals),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                if decimals.len() != columns.len() {
+                    bail!("'decimals' must have {} values", columns.len());
+                }
+                decimals
+            }
+            _ => vec![Some(decimals.try_into_bare_unsigned_integer()?); columns.len()],
+        },
+        None => Vec::new(),
+    };
+    let thousands = match function.arguments.get_by_name("thousands") {
+        Some(thousands) => Some(thousands.try_into_string()?),
+        None => None,
+    };
+
+    let rows: Vec<Vec<String>> = records
+        .iter()
+        .map(|record| {
+            columns
+                .iter()
+                .map(|&column| record.get(column).cloned().unwrap_or_default())
+                .collect()
+        })
+        .collect();
+
+    // 揃えの指定がなければ数値のみの列を右揃えにする
+    let alignments: Vec<Option<String>> = (0..columns.len())
+        .map(|i| match alignments.get(i) {
+            Some(alignment) => Some(alignment.clone()),
+            None if alignments.is_empty()
+                && rows.iter().any(|row| !row[i].trim().is_empty())
+                && rows.iter().all(|row| {
+                    row[i].trim().is_empty() || format_number(&row[i], None, None).is_some()
+                }) =>
+            {
+                Some("right".to_string())
+            }
+            None => None,
+        })
+        .collect();
+
+    if let Some(names) = names {
+        let mut row_html = HtmlElement::new("tr");
+        for (i, name) in names.iter().enumerate() {
+            let mut cell_html = HtmlElement::new("th");
+            cell_html.set_attr("scope", "col");
+            if let Some(alignment) = &alignments[i] {
+                cell_html.set_attr("data-align", alignment);
+            }
+            cell_html.append_text(name);
+            row_html.append(cell_html);
+        }
+        thead_html.append(row_html);
+    }
+
+    for row in &rows {
+        let mut row_html = HtmlElement::new("tr");
+        for (i, cell) in row.iter().enumerate() {
+            let mut cell_html = HtmlElement::new("td");
+            if let Some(alignment) = &alignments[i] {
+                cell_html.set_attr("data-align", alignment);
+            }
+            let decimals = decimals.get(i).cloned().flatten();
+            let cell =
+                format_number(cell, decimals, thousands.as_deref()).unwrap_or_else(|| cell.clone());
+            cell_html.append_text(&cell);
+            row_html.append(cell_html);
+        }
+        tbody_html.append(row_html);
+    }
+
+    Ok(())
+}
+
+// 数値でなければ None を返す
+fn format_number(text: &str, decimals: Option<usize>, thousands: Option<&str>) -> Option<String> {
+    let text = text.trim();
+    if !text
+        .chars()
+        .all(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+    {
+        return None;
+    }
+    let value: f64 = text.parse().ok()?;
+
+    let formatted = match decimals {
+        Some(decimals) => format!("{:.*}", decimals, value),
+        None => text.to_string(),
+    };
+    let thousands = match thousands {
+        Some(thousands) if !formatted.contains(['e', 'E']) => thousands,
+        _ => return Some(formatted),
+    };
+
+    let (sign, unsigned) = match formatted.strip_prefix(['-', '+']) {
+        Some(unsigned) => (&formatted[..1], unsigned),
+        None => ("", formatted.as_str()),
+    };
+    let (integer, fraction) = match unsigned.find('.') {
+        Some(index) => unsigned.split_at(index),
+        None => (unsigned, ""),
+    };
+    let mut grouped = String::new();
+    for (i, c) in integer.chars().enumerate() {
+        if 0 < i && (integer.len() - i) % 3 == 0 {
+            grouped.push_str(thousands);
+        }
+        grouped.push(c);
+    }
+    Some(format!("{}{}{}", sign, grouped, fraction))
+}
+
+fn parse_alignment(alignment: &str) -> Result<String> {
+    match alignment {
+        "left" | "center" | "right" => Ok(alignment.to_string()),
+        _ => bail!("unknown alignment: {}", alignment),
+    }
+}
+
+fn get_span(function: &LitedownFunction, name: &str) -> Result<usize> {
+    match function.arguments.get_by_name(name) {
+        Some(span) => {
+            let span = span.try_into_bare_unsigned_integer()?;
+            if span == 0 {
+                bail!("'{}' must be positive", name);
+            }
+            Ok(span)
+        }
+        None => Ok(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        html_evaluator::litedown::{evaluate_litedown_to_html, Ld2HtmlInput},
+        parser::litedown::parse_litedown,
+    };
+
+    fn evaluate(body: &str) -> Result<String> {
+        let source = format!("@preamble@\n  @page_size[\"a4\"]\n\n@document@\n{}\n", body);
+        let output = evaluate_litedown_to_html(Ld2HtmlInput {
+            ast: parse_litedown(&source).unwrap(),
+            source_path: None,
+        })?;
+        Ok(output.html.to_string().get_body())
+    }
+
+    // 一時ファイルに CSV を書き，その絶対パスを返す
+    fn write_csv(name: &str, source: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, source).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn spanned_cells() {
+        let html = evaluate(
+            r#"  @table@
+    @row@
+      @cell[rowspan=2]{a}
+      @cell[colspan=2]{b}
+    @row@
+      @cell{c}
+      @cell[rowspan=2]{d}
+    @row@
+      @cell[colspan=2]{e}"#,
+        )
+        .unwrap();
+        assert!(html.contains(
+            "<tbody><tr><td rowspan=\"2\"><span>a</span></td><td colspan=\"2\"><span>b</span></td></tr><tr><td><span>c</span></td><td rowspan=\"2\"><span>d</span></td></tr><tr><td colspan=\"2\"><span>e</span></td></tr></tbody>"
+        ));
+    }
+
+    #[test]
+    fn overlapping_cells() {
+        // 2 行目の colspan が 1 行目の rowspan と重なる
+        let error = evaluate(
+            r#"  @table@
+    @row@
+      @cell{a}
+      @cell[rowspan=2]{b}
+      @cell{c}
+    @row@
+      @cell[colspan=2]{d}
+      @cell{e}"#,
+        )
+        .unwrap_err();
+        assert!(format!("{:#}", error)
+            .contains("cell 1 in row 2 overlaps a cell spanning from a previous row"));
+
+        let error = evaluate(
+            r#"  @table@
+    @row@
+      @cell[rowspan=2]{a}
+      @cell{b}
+      @cell[rowspan=2]{c}
+    @row@
+      @cell[colspan=2]{d}"#,
+        )
+        .unwrap_err();
+        assert!(format!("{:#}", error)
+            .contains("cell 1 in row 2 overlaps a cell spanning from a previous row"));
+    }
+
+    #[test]
+    fn wrong_row_width() {
+        let error = evaluate(
+            r#"  @table@
+    @row@
+      @cell[rowspan=2]{a}
+      @cell{b}
+    @row@
+      @cell{c}
+      @cell{d}"#,
+        )
+        .unwrap_err();
+        assert!(format!("{:#}", error).contains("row 2 has 3 columns, but previous rows have 2"));
+
+        // rowspan で埋まった列の間が空いている
+        let error = evaluate(
+            r#"  @table@
+    @row@
+      @cell[rowspan=2]{a}
+      @cell{b}
+      @cell[rowspan=2]{c}
+    @row@"#,
+        )
+        .unwrap_err();
+        assert!(format!("{:#}", error).contains("row 2 has 1 columns, but previous rows have 3"));
+
+        let error = evaluate(
+            r#"  @table@
+    @row@
+      @cell[rowspan=3]{a}
+    @row@"#,
+        )
+        .unwrap_err();
+        assert!(format!("{:#}", error).contains("rowspan exceeds the number of rows in 'table'"));
+    }
+
+    #[test]
+    fn csv_decimals() {
+        let src = write_csv("litedown-table-decimals.csv", "a,b\n1.234,5.678\n");
+        let html = evaluate(&format!("  @table[src=\"{}\", decimals=[1, 2]]@", src)).unwrap();
+        assert!(
+            html.contains("<td data-align=\"right\">1.2</td><td data-align=\"right\">5.68</td>")
+        );
+
+        let html = evaluate(&format!("  @table[src=\"{}\", decimals=0]@", src)).unwrap();
+        assert!(html.contains("<td data-align=\"right\">1</td><td data-align=\"right\">6</td>"));
+
+        // 列の数と合わない
+        for decimals in ["[1]", "[1, 2, 3]"] {
+            let error = evaluate(&format!(
+                "  @table[src=\"{}\", decimals={}]@",
+                src, decimals
+            ))
+            .unwrap_err();
+            assert!(format!("{:#}", error).contains("'decimals' must have 2 values"));
+        }
+        let error = evaluate(&format!(
+            "  @table[src=\"{}\", columns=[\"b\"], decimals=[1, 2]]@",
+            src
+        ))
+        .unwrap_err();
+        assert!(format!("{:#}", error).contains("'decimals' must have 1 values"));
+    }
+
+    #[test]
+    fn format_number_decimals() {
+        assert_eq!(format_number("3.14159", Some(2), None).unwrap(), "3.14");
+        assert_eq!(format_number("2", Some(3), None).unwrap(), "2.000");
+        assert_eq!(format_number("-0.5", Some(0), None).unwrap(), "-0");
+        assert_eq!(format_number(" 1.50 ", None, None).unwrap(), "1.50");
+    }
+
+    #[test]
+    fn format_number_thousands() {
+        assert_eq!(
+            format_number("1234567", None, Some(",")).unwrap(),
+            "1,234,567"
+        );
+        assert_eq!(format_number("123", None, Some(",")).unwrap(), "123");
+        assert_eq!(
+            format_number("-1234.5", Some(2), Some(",")).unwrap(),
+            "-1,234.50"
+        );
+        assert_eq!(
+            format_number("+999999", None, Some(" ")).unwrap(),
+            "+999 999"
+        );
+        // 指数表記は区切らない
+        assert_eq!(format_number("1e10", None, Some(",")).unwrap(), "1e10");
+    }
+
+    #[test]
+    fn format_number_non_numeric() {
+        assert_eq!(format_number("abc", Some(2), Some(",")), None);
+        assert_eq!(format_number("1,000", Some(2), None), None);
+        assert_eq!(format_number("1-2", None, Some(",")), None);
+        assert_eq!(format_number("", Some(2), None), None);
+    }
+}
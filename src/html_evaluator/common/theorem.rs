@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+
+use crate::{
+    evaluate_with_ld2html_evaluator,
+    html_evaluator::{
+        document::section::format_section_prefix, litedown::Ld2HtmlEvaluator,
+        preamble::theorem::TheoremNumbering, reference::Reference,
+    },
+    tree::function::LitedownFunction,
+    utility::html::HtmlElement,
+};
+
+// プリアンブルで定義された定理環境 (definition, theorem, proof など) の共通の評価関数
+pub fn evaluate_theorem(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let theorems = &evaluator.get_preamble().theorems;
+    let locale = &evaluator.get_preamble().locale;
+    let environment = theorems
+        .get(&function.name)
+        .with_context(|| format!("unknown theorem environment: '{}'", function.name))?;
+
+    let title = match function.arguments.get_by_name("title") {
+        Some(title) => Some(title.try_into_string()?),
+        None => None,
+    };
+    let label = match function.arguments.get_by_name("label") {
+        Some(label) => Some(label.try_into_string()?),
+        None => None,
+    };
+
+    let id = evaluator.generate_id("theorem");
+    let tag = match &environment.counter {
+        Some(counter) => {
+            let counter = get_theorem_counter_name(counter);
+            let number = locale
+                .theorem_number
+                .format(evaluator.step_counter(&counter));
+            let number = match theorems.numbering {
+                TheoremNumbering::Document => number,
+                TheoremNumbering::Section => format!(
+                    "{}.{}",
+                    format_section_prefix(evaluator, &function.name)?,
+                    number
+                ),
+            };
+            Some(locale.format_tag(&environment.title, &number))
+        }
+        None => None,
+    };
+    if let Some(label) = label {
+        let tag = tag
+            .clone()
+            .with_context(|| format!("cannot put label on unnumbered '{}'", function.name))?;
+        evaluator.define_label(
+            &label,
+            Reference {
+                id: id.clone(),
+                number: tag,
+            },
+        )?;
+    }
+
+    let mut theorem_html = HtmlElement::new("div");
+    theorem_html.set_attr("id", &id);
+    theorem_html.set_attr("class", &format!("theorem theorem-{}", environment.name));
+
+    theorem_html.append({
+        let mut heading_html = HtmlElement::new("div");
+        heading_html.set_attr("class", "heading");
+        heading_html.append({
+            let mut label_html = HtmlElement::new("span");
+            label_html.set_attr("class", "label");
+            label_html.append_text(tag.as_ref().unwrap_or(&environment.title));
+            label_html
+        });
+        if let Some(title) = &title {
+            heading_html.append({
+                let mut title_html = HtmlElement::new("span");
+                title_html.set_attr("class", "title");
+                title_html.append_text(&format!(
+                    "{}{}{}",
+                    locale.title_open, title, locale.title_close
+                ));
+                title_html
+            });
+        }
+        heading_html
+    });
+
+    let mut content_html = HtmlElement::new("div");
+    content_html.set_attr("class", "content");
+    evaluate_with_ld2html_evaluator!(function to content_html with evaluator);
+    if environment.qed {
+        content_html.append({
+            let mut qed_html = HtmlElement::new("span");
+            qed_html.set_attr("class", "qed");
+            qed_html.append_text("∎");
+            qed_html
+        });
+    }
+    theorem_html.append(content_html);
+
+    Ok(Some(theorem_html))
+}
+
+// figure や equation などのカウンタと衝突しないように接頭辞を付ける
+pub fn get_theorem_counter_name(counter: &str) -> String {
+    format!("theorem-{}", counter)
+}
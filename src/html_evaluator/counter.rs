@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub struct CounterTable {
+    values: HashMap<String, usize>,
+    // 親カウンタが進むと子カウンタは 0 に戻る
+    parents: HashMap<String, String>,
+}
+
+impl CounterTable {
+    pub fn set_parent(&mut self, name: &str, parent: &str) {
+        self.parents.insert(name.to_string(), parent.to_string());
+    }
+
+    pub fn get(&self, name: &str) -> usize {
+        self.values.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn step(&mut self, name: &str) -> usize {
+        let value = self.get(name) + 1;
+        self.values.insert(name.to_string(), value);
+        self.reset_children(name);
+        value
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    fn reset_children(&mut self, name: &str) {
+        let children: Vec<String> = self
+            .parents
+            .iter()
+            .filter(|(_, parent)| parent.as_str() == name)
+            .map(|(child, _)| child.clone())
+            .collect();
+        for child in children {
+            self.values.remove(&child);
+            self.reset_children(&child);
+        }
+    }
+}
@@ -1,3 +1,6 @@
 pub(super) mod document;
+pub(super) mod footnote;
+pub(super) mod listing;
 pub(super) mod pagebreak;
+pub(super) mod section;
 mod title;
@@ -3,7 +3,9 @@ use anyhow::Result;
 use crate::{
     evaluate_with_ld2html_evaluator,
     html_evaluator::{
-        document::title::evaluate_title, litedown::Ld2HtmlEvaluator, preamble::preamble::Preamble,
+        document::{footnote::evaluate_endnotes, section::evaluate_section, title::evaluate_title},
+        litedown::{Ld2HtmlContent, Ld2HtmlEvaluator},
+        preamble::{preamble::Preamble, writing_mode::WritingMode},
     },
     tree::function::LitedownFunction,
     utility::html::HtmlElement,
@@ -13,7 +15,7 @@ pub fn evaluate_document(
     evaluator: &Ld2HtmlEvaluator,
     preamble: &Preamble,
     function: &LitedownFunction,
-) -> Result<(Vec<HtmlElement>, Vec<HtmlElement>)> {
+) -> Result<Ld2HtmlContent> {
     let mut head: Vec<HtmlElement> = Vec::new();
 
     // document.less
@@ -29,69 +31,101 @@ pub fn evaluate_document(
     head.push({
         let mut style = HtmlElement::new("style");
         style.set_attr("type", "text/less");
-        style.append_raw_text(&format!(
-            r#"
-            @page {{
-                size: {width} {height};
-                margin: {padding_vertical} 0;
-                padding: 0;
-                border-width: 0;
-            }}
-
-            #root {{
-                @media screen {{
-                    width: calc({width} - 2 * {padding_horizontal});
-                    min-height: calc({height} - 2 * {padding_vertical});
-                    padding: {padding_vertical} {padding_horizontal};
-                }}
-
-                @media print {{
-                    width: calc({width} - 2 * {padding_horizontal});
-                    margin: 0 {padding_horizontal};
-                }}
-            }}
-            "#,
-            width = preamble.page_size.width,
-            height = preamble.page_size.height,
-            padding_horizontal = preamble.page_padding.horizontal,
-            padding_vertical = preamble.page_padding.vertical,
-        ));
+        style.append_raw_text(&match preamble.writing_mode {
+            WritingMode::Horizontal => get_horizontal_page_style(preamble),
+            WritingMode::Vertical => get_vertical_page_style(preamble),
+        });
         style
     });
 
     let mut body = HtmlElement::new("div");
     body.set_attr("class", "document");
 
-    let mut section_index = 1;
-
     evaluate_with_ld2html_evaluator!(function to body with evaluator;
         function: {
             title: (child_function) => {
                 body.append(evaluate_title(evaluator, child_function)?);
             }
             section: (child_function) => {
-                let mut section_html = HtmlElement::new("section");
-                section_html.append({
-                    let mut header_html = HtmlElement::new("div");
-                    header_html.set_attr("class", "header");
-
-                    if let Some(raw_tag) = child_function.arguments.get_by_name("raw_tag") {
-                        let raw_tag = raw_tag.try_into_string()?;
-                        header_html.append_text(&raw_tag);
-                    } else {
-                        header_html.append_text(&format!("{}.", section_index));
-                        section_index += 1;
-                    }
-
-                    header_html
-                });
-                evaluate_with_ld2html_evaluator!(child_function to section_html with evaluator);
-                body.append(section_html);
+                body.append(evaluate_section(evaluator, child_function, 1)?);
             }
         }
     );
 
+    if let Some(endnotes_html) = evaluate_endnotes(evaluator) {
+        body.append(endnotes_html);
+    }
+
     let body = vec![body];
 
-    Ok((head, body))
+    Ok(Ld2HtmlContent {
+        head,
+        body,
+        attachments: Vec::new(),
+    })
+}
+
+fn get_horizontal_page_style(preamble: &Preamble) -> String {
+    format!(
+        r#"
+        @page {{
+            size: {width} {height};
+            margin: {padding_vertical} 0;
+            padding: 0;
+            border-width: 0;
+        }}
+
+        #root {{
+            @media screen {{
+                width: calc({width} - 2 * {padding_horizontal});
+                min-height: calc({height} - 2 * {padding_vertical});
+                padding: {padding_vertical} {padding_horizontal};
+            }}
+
+            @media print {{
+                width: calc({width} - 2 * {padding_horizontal});
+                margin: 0 {padding_horizontal};
+            }}
+        }}
+        "#,
+        width = preamble.page_size.width,
+        height = preamble.page_size.height,
+        padding_horizontal = preamble.page_padding.horizontal,
+        padding_vertical = preamble.page_padding.vertical,
+    )
+}
+
+// 縦書きではページが左右に並ぶため，縦横の余白の扱いを入れ替える
+fn get_vertical_page_style(preamble: &Preamble) -> String {
+    format!(
+        r#"
+        html {{
+            writing-mode: vertical-rl;
+        }}
+
+        @page {{
+            size: {width} {height};
+            margin: 0 {padding_horizontal};
+            padding: 0;
+            border-width: 0;
+        }}
+
+        #root {{
+            @media screen {{
+                height: calc({height} - 2 * {padding_vertical});
+                min-width: calc({width} - 2 * {padding_horizontal});
+                padding: {padding_vertical} {padding_horizontal};
+            }}
+
+            @media print {{
+                height: calc({height} - 2 * {padding_vertical});
+                margin: {padding_vertical} 0;
+            }}
+        }}
+        "#,
+        width = preamble.page_size.width,
+        height = preamble.page_size.height,
+        padding_horizontal = preamble.page_padding.horizontal,
+        padding_vertical = preamble.page_padding.vertical,
+    )
 }
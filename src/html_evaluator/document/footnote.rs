@@ -0,0 +1,79 @@
+use anyhow::{bail, Result};
+
+use crate::{
+    evaluate_with_ld2html_evaluator,
+    html_evaluator::litedown::Ld2HtmlEvaluator,
+    tree::function::{FunctionBodyForm, LitedownFunction},
+    utility::html::HtmlElement,
+};
+
+pub fn evaluate_footnote(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    if function.body.form != FunctionBodyForm::Inline {
+        bail!("function 'footnote' must be inline");
+    }
+    if function.body.is_empty() {
+        bail!("function 'footnote' must have body");
+    }
+
+    let id = evaluator.generate_id("footnote");
+    let marker_id = format!("{}-marker", id);
+    let number = evaluator.step_counter("footnote").to_string();
+
+    let mut content_html = HtmlElement::new("span");
+    content_html.set_attr("class", "content");
+    evaluate_with_ld2html_evaluator!(function to content_html with evaluator);
+
+    let mut marker_html = HtmlElement::new("sup");
+    marker_html.set_attr("class", "footnote-marker");
+    marker_html.set_attr("id", &marker_id);
+    marker_html.append({
+        let mut anchor_html = HtmlElement::new("a");
+        anchor_html.set_attr("href", &format!("#{}", id));
+        anchor_html.append_text(&number);
+        anchor_html
+    });
+
+    evaluator.push_endnote({
+        let mut endnote_html = HtmlElement::new("li");
+        endnote_html.set_attr("id", &id);
+        endnote_html.append({
+            let mut number_html = HtmlElement::new("span");
+            number_html.set_attr("class", "number");
+            number_html.append_text(&number);
+            number_html
+        });
+        endnote_html.append(content_html);
+        endnote_html.append({
+            let mut back_html = HtmlElement::new("a");
+            back_html.set_attr("class", "back-reference");
+            back_html.set_attr("href", &format!("#{}", marker_id));
+            back_html.append_text("↩");
+            back_html
+        });
+        endnote_html
+    });
+
+    Ok(Some(marker_html))
+}
+
+// これまでに書かれた脚注を後注の一覧として出力する
+pub fn evaluate_endnotes(evaluator: &Ld2HtmlEvaluator) -> Option<HtmlElement> {
+    let endnotes = evaluator.take_endnotes();
+    if endnotes.is_empty() {
+        return None;
+    }
+
+    let mut endnotes_html = HtmlElement::new("aside");
+    endnotes_html.set_attr("class", "endnotes");
+
+    let mut list_html = HtmlElement::new("ol");
+    for endnote in endnotes {
+        list_html.append(endnote);
+    }
+    endnotes_html.append(list_html);
+
+    Some(endnotes_html)
+}
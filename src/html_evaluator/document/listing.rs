@@ -0,0 +1,97 @@
+use anyhow::{bail, Result};
+
+use crate::{
+    html_evaluator::{
+        litedown::Ld2HtmlEvaluator,
+        reference::{ListingEntry, ListingKind},
+    },
+    tree::function::LitedownFunction,
+    utility::html::HtmlElement,
+};
+
+pub fn evaluate_toc(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let title = &evaluator.get_preamble().locale.toc;
+    evaluate_listing(evaluator, function, ListingKind::Section, title)
+}
+
+pub fn evaluate_list_of_figures(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let title = &evaluator.get_preamble().locale.list_of_figures;
+    evaluate_listing(evaluator, function, ListingKind::Figure, title)
+}
+
+pub fn evaluate_list_of_tables(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<Option<HtmlElement>> {
+    let title = &evaluator.get_preamble().locale.list_of_tables;
+    evaluate_listing(evaluator, function, ListingKind::Table, title)
+}
+
+fn evaluate_listing(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+    kind: ListingKind,
+    default_title: &str,
+) -> Result<Option<HtmlElement>> {
+    if !function.body.is_empty() {
+        bail!("cannot write body in '{}'", function.name);
+    }
+
+    let title = match function.arguments.get_by_name("title") {
+        Some(title) => title.try_into_string()?,
+        None => default_title.to_string(),
+    };
+
+    let mut listing_html = HtmlElement::new("nav");
+    listing_html.set_attr("class", "listing");
+
+    listing_html.append({
+        let mut heading_html = HtmlElement::new("div");
+        heading_html.set_attr("class", "heading");
+        heading_html.append_text(&title);
+        heading_html
+    });
+
+    let mut entries_html = HtmlElement::new("ol");
+    // 1 回目の評価では項目が未収集のため空になる
+    for entry in evaluator.get_listing_entries(kind).unwrap_or_default() {
+        entries_html.append(evaluate_listing_entry(&entry));
+    }
+    listing_html.append(entries_html);
+
+    Ok(Some(listing_html))
+}
+
+fn evaluate_listing_entry(entry: &ListingEntry) -> HtmlElement {
+    let mut anchor_html = HtmlElement::new("a");
+    anchor_html.set_attr("href", &format!("#{}", entry.id));
+
+    anchor_html.append({
+        let mut number_html = HtmlElement::new("span");
+        number_html.set_attr("class", "number");
+        if let Some(number) = &entry.number {
+            number_html.append_text(number);
+        }
+        number_html
+    });
+
+    if let Some(title) = &entry.title {
+        anchor_html.append({
+            let mut name_html = HtmlElement::new("span");
+            name_html.set_attr("class", "name");
+            name_html.append(title.clone());
+            name_html
+        });
+    }
+
+    let mut li_html = HtmlElement::new("li");
+    li_html.set_attr("data-level", &entry.level.to_string());
+    li_html.append(anchor_html);
+    li_html
+}
@@ -0,0 +1,175 @@
+use anyhow::{bail, Result};
+
+use crate::{
+    evaluate_with_ld2html_evaluator,
+    html_evaluator::{
+        document::footnote::evaluate_endnotes,
+        litedown::Ld2HtmlEvaluator,
+        preamble::{
+            footnote::FootnoteReset,
+            section_numbering::{SectionNumberStyle, MAX_SECTION_LEVEL},
+        },
+        reference::{ListingEntry, ListingKind, Reference},
+    },
+    tree::function::LitedownFunction,
+    utility::html::HtmlElement,
+};
+
+pub fn get_section_counter_name(level: usize) -> String {
+    format!("section-{}", level)
+}
+
+// 節ごとに振る番号 (図 2.1 など) の先頭に付ける最上位の節の番号
+// 最初の節より前では 0.1 のようになってしまうのでエラーにする
+pub fn format_section_prefix(evaluator: &Ld2HtmlEvaluator, name: &str) -> Result<String> {
+    let counter = evaluator.get_counter(&get_section_counter_name(1));
+    if counter == 0 {
+        bail!(
+            "'{}' numbered by section must be placed after the first section",
+            name
+        );
+    }
+    Ok(
+        match &evaluator.get_preamble().section_numbering.levels[0] {
+            SectionNumberStyle::Number(style) => style.format(counter),
+            _ => counter.to_string(),
+        },
+    )
+}
+
+// level は 1 始まり
+pub fn evaluate_section(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+    level: usize,
+) -> Result<HtmlElement> {
+    if MAX_SECTION_LEVEL < level {
+        bail!("section can be nested up to {} levels", MAX_SECTION_LEVEL);
+    }
+
+    let mut section_html = HtmlElement::new("section");
+    let id = evaluator.generate_id("section");
+    section_html.set_attr("id", &id);
+    section_html.set_attr("data-level", &level.to_string());
+
+    let mut header_html = HtmlElement::new(&format!("h{}", level + 1));
+    header_html.set_attr("class", "header");
+
+    let number = if let Some(raw_tag) = function.arguments.get_by_name("raw_tag") {
+        let raw_tag = raw_tag.try_into_string()?;
+        header_html.append_text(&raw_tag);
+        Some(raw_tag)
+    } else {
+        evaluator.step_counter(&get_section_counter_name(level));
+        match format_section_number(evaluator, level) {
+            Some((number, display)) => {
+                let mut number_html = HtmlElement::new("span");
+                number_html.set_attr("class", "number");
+                number_html.append_text(&display);
+                header_html.append(number_html);
+                Some(number)
+            }
+            None => None,
+        }
+    };
+
+    let title = match function.arguments.get_by_name("title") {
+        Some(title) => Some(title.try_into_string()?),
+        None => None,
+    };
+    if let Some(title) = &title {
+        let mut name_html = HtmlElement::new("span");
+        name_html.set_attr("class", "name");
+        name_html.append_text(title);
+        header_html.append(name_html);
+    }
+
+    if let Some(label) = function.arguments.get_by_name("label") {
+        let label = label.try_into_string()?;
+        match &number {
+            Some(number) => evaluator.define_label(
+                &label,
+                Reference {
+                    id: id.clone(),
+                    number: number.clone(),
+                },
+            )?,
+            None => bail!("cannot put label '{}' on unnumbered section", label),
+        }
+    }
+
+    evaluator.add_listing_entry(ListingEntry {
+        kind: ListingKind::Section,
+        id,
+        number,
+        title: title.map(|title| {
+            let mut title_html = HtmlElement::new("span");
+            title_html.append_text(&title);
+            title_html
+        }),
+        level,
+    });
+
+    section_html.append(header_html);
+
+    evaluate_with_ld2html_evaluator!(function to section_html with evaluator;
+        function: {
+            section: (child_function) => {
+                section_html.append(evaluate_section(evaluator, child_function, level + 1)?);
+            }
+        }
+    );
+
+    // 脚注番号を節ごとに振り直す場合，後注は節の末尾に置く
+    if level == 1 && evaluator.get_preamble().footnote.reset == FootnoteReset::Section {
+        if let Some(endnotes_html) = evaluate_endnotes(evaluator) {
+            section_html.append(endnotes_html);
+        }
+    }
+
+    Ok(section_html)
+}
+
+// (参照などに用いる番号, 見出しに表示する番号) を返す
+// 番号を付けない階層の場合は None
+fn format_section_number(evaluator: &Ld2HtmlEvaluator, level: usize) -> Option<(String, String)> {
+    let styles = &evaluator.get_preamble().section_numbering.levels;
+    let counter = evaluator.get_counter(&get_section_counter_name(level));
+    match &styles[level - 1] {
+        SectionNumberStyle::None => None,
+        SectionNumberStyle::JapaneseChapter => {
+            let number = evaluator.get_preamble().locale.format_chapter(counter);
+            Some((number.clone(), number))
+        }
+        SectionNumberStyle::JapaneseSection => {
+            let number = evaluator.get_preamble().locale.format_section(counter);
+            Some((number.clone(), number))
+        }
+        SectionNumberStyle::Number(_) => {
+            // 上位の階層の番号を含めて 2.1.3 のように表示する
+            let mut parts = Vec::new();
+            for (i, style) in styles.iter().enumerate().take(level) {
+                let counter = evaluator.get_counter(&get_section_counter_name(i + 1));
+                match style {
+                    SectionNumberStyle::None => {}
+                    SectionNumberStyle::Number(style) => parts.push(style.format(counter)),
+                    SectionNumberStyle::JapaneseChapter | SectionNumberStyle::JapaneseSection => {
+                        parts.push(counter.to_string())
+                    }
+                }
+            }
+            let number = parts.join(".");
+            // 階層を含まない番号は "1." のように表示する
+            let display = if parts.len() == 1 {
+                format!(
+                    "{}{}",
+                    number,
+                    evaluator.get_preamble().locale.section_suffix
+                )
+            } else {
+                number.clone()
+            };
+            Some((number, display))
+        }
+    }
+}
@@ -6,7 +6,10 @@ use crate::{
     utility::html::HtmlElement,
 };
 
-pub fn evaluate_title(_: &Ld2HtmlEvaluator, function: &LitedownFunction) -> Result<HtmlElement> {
+pub fn evaluate_title(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<HtmlElement> {
     let mut title_html: HtmlElement = HtmlElement::new("div");
     title_html.set_attr("class", "title");
 
@@ -15,8 +18,8 @@ pub fn evaluate_title(_: &Ld2HtmlEvaluator, function: &LitedownFunction) -> Resu
     for passage in &function.body.value {
         for passage_element in &passage.elements {
             match &passage_element {
-                PassageElement::String(string) => {
-                    title_html.append_text(&string);
+                PassageElement::String(string, _) => {
+                    evaluator.append_passage_text(&mut title_html, string);
                 }
                 PassageElement::Function(child_function) => match child_function.name.as_str() {
                     "author" => {
@@ -52,8 +52,13 @@ macro_rules! evaluate_with_ld2html_evaluator {
 
             for passage_element in &passage.elements {
                 match &passage_element {
-                    $crate::tree::function::PassageElement::String(string) => {
-                        passage_html.append_text(&string);
+                    $crate::tree::function::PassageElement::String(string, _) => {
+                        // $...$ の中身は手を加えずに出力する
+                        if $function.body.raw {
+                            passage_html.append_text(&string);
+                        } else {
+                            $evaluator.append_passage_text(&mut passage_html, &string);
+                        }
                     }
 
                     $crate::tree::function::PassageElement::Function(child_function) => {
@@ -111,7 +116,7 @@ macro_rules! evaluate_litedown_function {
         for passage in &$function.body.value {
             for passage_element in &passage.elements {
                 match &passage_element {
-                    $crate::tree::function::PassageElement::String(string) => {
+                    $crate::tree::function::PassageElement::String(string, _) => {
                         if !$crate::utility::whitespace::is_blank(string) {
                             anyhow::bail!("cannot write string in function '{}'", $function.name);
                         }
@@ -1,28 +1,55 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{cell::RefCell, collections::HashMap, fs, path::PathBuf};
 
 use anyhow::{bail, Context, Result};
 
 use crate::{
     html_evaluator::{
-        document::document::evaluate_document,
-        preamble::{math::Math, preamble::evaluate_preamble},
+        bibliography::{Bibliography, BibliographyEntry},
+        counter::CounterTable,
+        document::{document::evaluate_document, section::get_section_counter_name},
+        preamble::{
+            caption::CaptionNumbering, equation::EquationNumbering, footnote::FootnoteReset,
+            math::Math, preamble::evaluate_preamble, section_numbering::MAX_SECTION_LEVEL,
+            theorem::TheoremNumbering, writing_mode::WritingMode,
+        },
         presentation::presentation::evaluate_presentation,
+        reference::{ListingEntry, ListingKind, Reference, ReferenceTable},
     },
     tree::{function::LitedownFunction, litedown::LitedownAst},
-    utility::html::{Html, HtmlElement},
+    utility::{
+        html::{Html, HtmlElement},
+        tex_macro::parse_tex_macros_into,
+        text_normalization::{insert_spacing, normalize_punctuation, unify_alphanumerics},
+        whitespace::{collapse_whitespaces, WhitespaceMode},
+    },
 };
 
 use super::{
     common::{
+        callout::{evaluate_box, evaluate_danger, evaluate_note, evaluate_tip, evaluate_warning},
+        citation::{evaluate_bibliography, evaluate_cite},
         code::evaluate_code,
-        decorators::{evaluate_attention, evaluate_divider, evaluate_link, evaluate_strong},
+        decorators::{
+            evaluate_attention, evaluate_color, evaluate_divider, evaluate_emphasis, evaluate_link,
+            evaluate_marker, evaluate_nocheck, evaluate_small_caps, evaluate_strikethrough,
+            evaluate_strong, evaluate_subscript, evaluate_superscript, evaluate_underline,
+        },
         figure::evaluate_figure,
         grid::evaluate_grid,
         image::evaluate_image,
+        japanese::{evaluate_bouten, evaluate_ruby, evaluate_tcy},
+        language::{evaluate_lang, evaluate_quote},
         list::evaluate_list,
         math::evaluate_math,
+        reference::evaluate_reference,
+        table::evaluate_table,
+        theorem::{evaluate_theorem, get_theorem_counter_name},
+    },
+    document::{
+        footnote::evaluate_footnote,
+        listing::{evaluate_list_of_figures, evaluate_list_of_tables, evaluate_toc},
+        pagebreak::evaluate_pagebreak,
     },
-    document::pagebreak::evaluate_pagebreak,
     preamble::{font::FontFamily, preamble::Preamble, theme::Theme},
     presentation::absolute_block::evaluate_absolute_block,
 };
@@ -38,6 +65,20 @@ impl ContentMode {
         function_evaluators.insert("math".to_string(), evaluate_math);
         function_evaluators.insert("strong".to_string(), evaluate_strong);
         function_evaluators.insert("attention".to_string(), evaluate_attention);
+        function_evaluators.insert("emph".to_string(), evaluate_emphasis);
+        function_evaluators.insert("underline".to_string(), evaluate_underline);
+        function_evaluators.insert("strike".to_string(), evaluate_strikethrough);
+        function_evaluators.insert("sub".to_string(), evaluate_subscript);
+        function_evaluators.insert("sup".to_string(), evaluate_superscript);
+        function_evaluators.insert("small_caps".to_string(), evaluate_small_caps);
+        function_evaluators.insert("marker".to_string(), evaluate_marker);
+        function_evaluators.insert("color".to_string(), evaluate_color);
+        function_evaluators.insert("ruby".to_string(), evaluate_ruby);
+        function_evaluators.insert("bouten".to_string(), evaluate_bouten);
+        function_evaluators.insert("tcy".to_string(), evaluate_tcy);
+        function_evaluators.insert("lang".to_string(), evaluate_lang);
+        function_evaluators.insert("quote".to_string(), evaluate_quote);
+        function_evaluators.insert("nocheck".to_string(), evaluate_nocheck);
         function_evaluators.insert("list".to_string(), evaluate_list);
         function_evaluators.insert("figure".to_string(), evaluate_figure);
         function_evaluators.insert("image".to_string(), evaluate_image);
@@ -45,10 +86,23 @@ impl ContentMode {
         function_evaluators.insert("divider".to_string(), evaluate_divider);
         function_evaluators.insert("link".to_string(), evaluate_link);
         function_evaluators.insert("grid".to_string(), evaluate_grid);
+        function_evaluators.insert("ref".to_string(), evaluate_reference);
+        function_evaluators.insert("table".to_string(), evaluate_table);
+        function_evaluators.insert("cite".to_string(), evaluate_cite);
+        function_evaluators.insert("bibliography".to_string(), evaluate_bibliography);
+        function_evaluators.insert("note".to_string(), evaluate_note);
+        function_evaluators.insert("tip".to_string(), evaluate_tip);
+        function_evaluators.insert("warning".to_string(), evaluate_warning);
+        function_evaluators.insert("danger".to_string(), evaluate_danger);
+        function_evaluators.insert("box".to_string(), evaluate_box);
 
         match &self {
             ContentMode::Document => {
                 function_evaluators.insert("pagebreak".to_string(), evaluate_pagebreak);
+                function_evaluators.insert("footnote".to_string(), evaluate_footnote);
+                function_evaluators.insert("toc".to_string(), evaluate_toc);
+                function_evaluators.insert("list_of_figures".to_string(), evaluate_list_of_figures);
+                function_evaluators.insert("list_of_tables".to_string(), evaluate_list_of_tables);
             }
             ContentMode::Presentation => {
                 function_evaluators.insert("absolute".to_string(), evaluate_absolute_block);
@@ -64,7 +118,7 @@ impl ContentMode {
         evaluator: &Ld2HtmlEvaluator,
         preamble: &Preamble,
         function: &LitedownFunction,
-    ) -> Result<(Vec<HtmlElement>, Vec<HtmlElement>)> {
+    ) -> Result<Ld2HtmlContent> {
         match &self {
             ContentMode::Document => evaluate_document,
             ContentMode::Presentation => evaluate_presentation,
@@ -77,24 +131,68 @@ pub struct Ld2HtmlInput {
     pub source_path: Option<PathBuf>,
 }
 
+pub struct Ld2HtmlOutput {
+    pub html: Html,
+    pub attachments: Vec<Ld2HtmlAttachment>,
+    // 縦書きでは PDF のページを右から左へめくる
+    pub right_to_left: bool,
+}
+
+// 本体とは別のファイルとして出力される HTML (発表者ビューなど)
+pub struct Ld2HtmlAttachment {
+    pub name: String,
+    pub html: Html,
+    pub pdf: bool,
+}
+
+pub(super) struct Ld2HtmlContent {
+    pub head: Vec<HtmlElement>,
+    pub body: Vec<HtmlElement>,
+    pub attachments: Vec<Ld2HtmlContentAttachment>,
+}
+
+pub(super) struct Ld2HtmlContentAttachment {
+    pub name: String,
+    pub head: Vec<HtmlElement>,
+    pub body: Vec<HtmlElement>,
+    pub pdf: bool,
+}
+
 type Ld2HtmlFunctionEvaluator =
     fn(evaluator: &Ld2HtmlEvaluator, function: &LitedownFunction) -> Result<Option<HtmlElement>>;
 
-pub fn evaluate_litedown_to_html(input: Ld2HtmlInput) -> Result<Html> {
+pub fn evaluate_litedown_to_html(input: Ld2HtmlInput) -> Result<Ld2HtmlOutput> {
     let mut evaluator = Ld2HtmlEvaluator::new(input);
     evaluator.evaluate()
 }
 
 pub struct Ld2HtmlEvaluator {
     input: Ld2HtmlInput,
+    preamble: Option<Preamble>,
+    bibliography: Option<Bibliography>,
     function_evaluators: HashMap<String, Ld2HtmlFunctionEvaluator>,
+    // 評価中に定義されたラベル
+    labels: RefCell<ReferenceTable>,
+    // 前回の評価で定義されたラベル (1 回目の評価では None)
+    resolved_labels: Option<ReferenceTable>,
+    id_counters: RefCell<HashMap<String, usize>>,
+    counters: RefCell<CounterTable>,
+    // まだ出力されていない後注
+    endnotes: RefCell<Vec<HtmlElement>>,
 }
 
 impl Ld2HtmlEvaluator {
     pub fn new(input: Ld2HtmlInput) -> Ld2HtmlEvaluator {
         Ld2HtmlEvaluator {
             input,
+            preamble: None,
+            bibliography: None,
             function_evaluators: HashMap::new(),
+            labels: RefCell::new(ReferenceTable::default()),
+            resolved_labels: None,
+            id_counters: RefCell::new(HashMap::new()),
+            counters: RefCell::new(CounterTable::default()),
+            endnotes: RefCell::new(Vec::new()),
         }
     }
 
@@ -105,14 +203,30 @@ impl Ld2HtmlEvaluator {
         }
     }
 
-    pub fn evaluate(&mut self) -> Result<Html> {
+    // 相対パスはソースファイルからの相対パスとして扱う
+    pub(crate) fn resolve_path(&self, src: &str) -> Result<PathBuf> {
+        if src.starts_with('/') {
+            Ok(PathBuf::from(src))
+        } else {
+            let source_path = self.get_source_path().context("cannot use relative path")?;
+            Ok(source_path.with_file_name(src))
+        }
+    }
+
+    pub(crate) fn get_preamble(&self) -> &Preamble {
+        self.preamble
+            .as_ref()
+            .expect("preamble must be evaluated before content")
+    }
+
+    pub fn evaluate(&mut self) -> Result<Ld2HtmlOutput> {
         let preamble_function = self
             .input
             .ast
             .body
             .get(0)
             .context("preamble not found: first function must be 'preamble'")?;
-        let preamble = match preamble_function.name.as_str() {
+        let mut preamble = match preamble_function.name.as_str() {
             "preamble" => {
                 evaluate_preamble(preamble_function).context("failed to evaluate preamble")?
             }
@@ -120,6 +234,53 @@ impl Ld2HtmlEvaluator {
         };
         println!("preamble: {:?}", preamble);
 
+        {
+            let mut counters = self.counters.borrow_mut();
+            for level in 2..=MAX_SECTION_LEVEL {
+                counters.set_parent(
+                    &get_section_counter_name(level),
+                    &get_section_counter_name(level - 1),
+                );
+            }
+            if preamble.footnote.reset == FootnoteReset::Section {
+                counters.set_parent("footnote", &get_section_counter_name(1));
+            }
+            if let CaptionNumbering::Section = preamble.caption.numbering {
+                counters.set_parent("figure", &get_section_counter_name(1));
+                counters.set_parent("table", &get_section_counter_name(1));
+            }
+            if preamble.equation_numbering == EquationNumbering::Section {
+                counters.set_parent("equation", &get_section_counter_name(1));
+            }
+            if preamble.theorems.numbering == TheoremNumbering::Section {
+                for environment in &preamble.theorems.environments {
+                    if let Some(counter) = &environment.counter {
+                        counters.set_parent(
+                            &get_theorem_counter_name(counter),
+                            &get_section_counter_name(1),
+                        );
+                    }
+                }
+            }
+        }
+        for src in &preamble.math_macros.sources {
+            let path = self.resolve_path(src)?;
+            let source = match fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(e) => bail!("could not read math macros: {}", e),
+            };
+            // 前に書かれた定義と重なる \newcommand はエラーにする
+            parse_tex_macros_into(&source, &mut preamble.math_macros.macros)
+                .with_context(|| format!("failed to parse math macros: {}", src))?;
+        }
+        if let Some(src) = &preamble.citation.src {
+            let path = self.resolve_path(src)?;
+            let bibliography = Bibliography::load(&path)
+                .with_context(|| format!("failed to load bibliography: {}", src))?;
+            self.bibliography = Some(bibliography);
+        }
+        self.preamble = Some(preamble);
+
         let content_function =
             self.input.ast.body.get(1).context(
                 "content not found: second function must be 'document' or 'presentation'",
@@ -133,28 +294,73 @@ impl Ld2HtmlEvaluator {
         };
         self.function_evaluators
             .extend(content_mode.get_function_evaluators());
-        let (content_head, content_body) =
-            content_mode.get_evaluator()(&self, &preamble, content_function)?;
+        // 定理環境はプリアンブルで定義された名前で呼び出す
+        let theorem_names: Vec<String> = self
+            .get_preamble()
+            .theorems
+            .environments
+            .iter()
+            .map(|environment| environment.name.clone())
+            .collect();
+        for name in theorem_names {
+            if self.function_evaluators.contains_key(&name) {
+                bail!(
+                    "theorem environment '{}' conflicts with built-in function",
+                    name
+                );
+            }
+            self.function_evaluators.insert(name, evaluate_theorem);
+        }
+
+        // 前方参照を解決するため，1 回目の評価でラベルを収集してから 2 回目の評価を行う
+        content_mode.get_evaluator()(self, self.get_preamble(), content_function)?;
+        self.resolved_labels = Some(self.labels.take());
+        self.id_counters.take();
+        self.counters.borrow_mut().clear();
+        self.endnotes.take();
+
+        let preamble = self.get_preamble();
+        let content = content_mode.get_evaluator()(self, preamble, content_function)?;
+
+        let html = Self::build_html(preamble, content.head, content.body);
+        let attachments = content
+            .attachments
+            .into_iter()
+            .map(|attachment| Ld2HtmlAttachment {
+                name: attachment.name,
+                html: Self::build_html(preamble, attachment.head, attachment.body),
+                pdf: attachment.pdf,
+            })
+            .collect();
+
+        Ok(Ld2HtmlOutput {
+            html,
+            attachments,
+            right_to_left: preamble.writing_mode == WritingMode::Vertical,
+        })
+    }
 
+    fn build_html(preamble: &Preamble, head: Vec<HtmlElement>, body: Vec<HtmlElement>) -> Html {
         let mut html = Html::new();
+        html.set_lang(&preamble.language);
 
-        for element in Self::get_main_head(&preamble) {
+        for element in Self::get_main_head(preamble) {
             html.append_head(element);
         }
-        for element in content_head {
+        for element in head {
             html.append_head(element);
         }
 
         html.append_body({
             let mut root = HtmlElement::new("div");
             root.set_attr("id", "root");
-            for element in content_body {
+            for element in body {
                 root.append(element);
             }
             root
         });
 
-        Ok(html)
+        html
     }
 
     fn get_main_head(preamble: &Preamble) -> Vec<HtmlElement> {
@@ -176,11 +382,36 @@ impl Ld2HtmlEvaluator {
                 r#"
                 html {{
                     --strong-color: {strong_color};
+                    --theorem-background-color: {theorem_background_color};
+                    --note-color: {note_color};
+                    --tip-color: {tip_color};
+                    --warning-color: {warning_color};
+                    --danger-color: {danger_color};
                 }}
                 "#,
                 strong_color = match preamble.theme {
                     Theme::Default => "royalblue",
                     Theme::Paper => "black",
+                },
+                theorem_background_color = match preamble.theme {
+                    Theme::Default => "#f4f7ff",
+                    Theme::Paper => "transparent",
+                },
+                note_color = match preamble.theme {
+                    Theme::Default => "royalblue",
+                    Theme::Paper => "#333333",
+                },
+                tip_color = match preamble.theme {
+                    Theme::Default => "seagreen",
+                    Theme::Paper => "#555555",
+                },
+                warning_color = match preamble.theme {
+                    Theme::Default => "darkorange",
+                    Theme::Paper => "#333333",
+                },
+                danger_color = match preamble.theme {
+                    Theme::Default => "crimson",
+                    Theme::Paper => "black",
                 }
             ));
             element
@@ -192,17 +423,32 @@ impl Ld2HtmlEvaluator {
             element.append_raw_text(&format!(
                 r#"
                 html {{
-                    --main-font-family: {font_family};
                     --main-font-size: {font_size};
-    
-                    font-family: var(--main-font-family);
+
                     font-size: var(--main-font-size);
                 }}
+                [lang] {{
+                    --main-font-family: {latin_font_family}, {generic_font_family};
+
+                    font-family: var(--main-font-family);
+                }}
+                [lang|="ja"] {{
+                    --main-font-family: {latin_font_family}, {japanese_font_family}, {generic_font_family};
+                }}
                 "#,
                 font_size = preamble.font.size,
-                font_family = match preamble.font.family {
-                    FontFamily::Serif => "Georgia, 'Times New Roman', Times, serif",
-                    FontFamily::SansSerif => "Arial, Helvetica, sans-serif",
+                latin_font_family = match preamble.font.family {
+                    FontFamily::Serif => "Georgia, 'Times New Roman', Times",
+                    FontFamily::SansSerif => "Arial, Helvetica",
+                },
+                // 日本語の部分だけ和文フォントにフォールバックさせる
+                japanese_font_family = match preamble.font.family {
+                    FontFamily::Serif => "'Hiragino Mincho ProN', 'Yu Mincho', 'Noto Serif JP'",
+                    FontFamily::SansSerif => "'Hiragino Sans', 'Yu Gothic', 'Noto Sans JP'",
+                },
+                generic_font_family = match preamble.font.family {
+                    FontFamily::Serif => "serif",
+                    FontFamily::SansSerif => "sans-serif",
                 }
             ));
             element
@@ -236,8 +482,61 @@ impl Ld2HtmlEvaluator {
             highlight_script
         });
 
+        // 縦書きでもコードや数式，表は横書きのまま組む
+        if preamble.writing_mode == WritingMode::Vertical {
+            result.push({
+                let mut element = HtmlElement::new("style");
+                element.append_raw_text(
+                    r#"
+                    pre,
+                    table.table,
+                    .display-math,
+                    .inline-math {
+                        writing-mode: horizontal-tb;
+                    }
+                    .inline-math {
+                        display: inline-block;
+                    }
+                    "#,
+                );
+                element
+            });
+        }
+
         // math
         if let Some(math) = &preamble.math {
+            // KaTeX と MathJax で macros の形式が異なる (MathML は変換時に展開済み)
+            let macros: serde_json::Map<String, serde_json::Value> = preamble
+                .math_macros
+                .macros
+                .iter()
+                .map(|tex_macro| match (math, tex_macro.parameters) {
+                    (Math::Mathjax, 0) => {
+                        (tex_macro.name.clone(), serde_json::json!(tex_macro.body))
+                    }
+                    (Math::Mathjax, parameters) => (
+                        tex_macro.name.clone(),
+                        serde_json::json!([tex_macro.body, parameters]),
+                    ),
+                    _ => (
+                        format!("\\{}", tex_macro.name),
+                        serde_json::json!(tex_macro.body),
+                    ),
+                })
+                .collect();
+            if !matches!(math, Math::Mathml) {
+                result.push({
+                    let mut math_macros_script = HtmlElement::new("script");
+                    // マクロの中身に </script> や <!-- があってもスクリプトが途切れないようにする
+                    // (JSON では < は文字列の中にしか現れない)
+                    let macros = serde_json::Value::Object(macros)
+                        .to_string()
+                        .replace('<', "\\u003c");
+                    math_macros_script.append_raw_text(&format!("window.mathMacros = {};", macros));
+                    math_macros_script
+                });
+            }
+
             match math {
                 Math::Katex => {
                     result.push({
@@ -275,7 +574,7 @@ impl Ld2HtmlEvaluator {
                         math_load_script.append_raw_text(
                             r#"
                             window.addEventListener("DOMContentLoaded", () => {
-                                const macros = {};
+                                const macros = window.mathMacros;
                                 Array.from(document.getElementsByClassName("display-math")).forEach((el) => {
                                     console.log(el.innerText);
                                     katex.render(el.innerText, el, {
@@ -333,6 +632,7 @@ impl Ld2HtmlEvaluator {
                                 tex: {
                                     inlineMath: [window.mathJaxTriggers.inline],
                                     displayMath: [window.mathJaxTriggers.display],
+                                    macros: window.mathMacros,
                                 },
                             };
                             "#,
@@ -393,12 +693,176 @@ impl Ld2HtmlEvaluator {
                         math_load_style
                     });
                 }
+                Math::Mathml => {
+                    result.push({
+                        let mut math_style = HtmlElement::new("style");
+                        math_style.append_raw_text(
+                            r#"
+                            math {
+                                font-family: "Latin Modern Math", "STIX Two Math", "Cambria Math", math;
+                            }
+                            math mtext {
+                                font-family: var(--main-font-family);
+                            }
+                            .display-math > math {
+                                display: block math;
+                                overflow-x: auto;
+                            }
+                            "#,
+                        );
+                        math_style
+                    });
+                }
             }
         }
 
         result
     }
 
+    // 本文の文字列を追加する (縦書きでは 2 桁までの数字を縦中横にする)
+    pub(crate) fn append_passage_text(&self, html: &mut HtmlElement, text: &str) {
+        let text = &collapse_whitespaces(text, &self.get_preamble().whitespace);
+        let text = &self.normalize_text(text);
+        if self.get_preamble().writing_mode != WritingMode::Vertical {
+            self.append_plain_text(html, text);
+            return;
+        }
+        let chars: Vec<char> = text.chars().collect();
+        let mut plain = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let length = chars[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+            if length == 0 {
+                plain.push(chars[i]);
+                i += 1;
+                continue;
+            }
+            // 英字や小数点に続く数字は横倒しのままにする
+            let is_word = |c: Option<&char>| {
+                c.is_some_and(|c| c.is_ascii_alphanumeric() || *c == '.' || *c == ',')
+            };
+            let before = if i == 0 { None } else { chars.get(i - 1) };
+            let digits: String = chars[i..i + length].iter().collect();
+            if length <= 2 && !is_word(before) && !is_word(chars.get(i + length)) {
+                if !plain.is_empty() {
+                    self.append_plain_text(html, &std::mem::take(&mut plain));
+                }
+                html.append({
+                    let mut tcy_html = HtmlElement::new("span");
+                    tcy_html.set_attr("class", "tcy");
+                    tcy_html.append_text(&digits);
+                    tcy_html
+                });
+            } else {
+                plain.push_str(&digits);
+            }
+            i += length;
+        }
+        if !plain.is_empty() {
+            self.append_plain_text(html, &plain);
+        }
+    }
+
+    // Preserve 以外では空白と改行を折り返しに任せる
+    fn append_plain_text(&self, html: &mut HtmlElement, text: &str) {
+        match self.get_preamble().whitespace {
+            WhitespaceMode::Preserve => html.append_text(text),
+            WhitespaceMode::Collapse | WhitespaceMode::Cjk => html.append_collapsible_text(text),
+        };
+    }
+
+    fn normalize_text(&self, text: &str) -> String {
+        let normalization = &self.get_preamble().normalization;
+        let mut text = text.to_string();
+        if normalization.alphanumeric {
+            text = unify_alphanumerics(&text);
+        }
+        if let Some(style) = &normalization.punctuation {
+            text = normalize_punctuation(&text, style);
+        }
+        if normalization.spacing {
+            // U+2009 (thin space)
+            text = insert_spacing(&text, "\u{2009}");
+        }
+        text
+    }
+
+    pub(crate) fn generate_id(&self, prefix: &str) -> String {
+        let mut id_counters = self.id_counters.borrow_mut();
+        let counter = id_counters.entry(prefix.to_string()).or_insert(0);
+        *counter += 1;
+        format!("{}-{}", prefix, counter)
+    }
+
+    pub(crate) fn get_counter(&self, name: &str) -> usize {
+        self.counters.borrow().get(name)
+    }
+
+    pub(crate) fn step_counter(&self, name: &str) -> usize {
+        self.counters.borrow_mut().step(name)
+    }
+
+    pub(crate) fn push_endnote(&self, endnote: HtmlElement) {
+        self.endnotes.borrow_mut().push(endnote);
+    }
+
+    pub(crate) fn take_endnotes(&self) -> Vec<HtmlElement> {
+        self.endnotes.take()
+    }
+
+    pub(crate) fn define_label(&self, label: &str, reference: Reference) -> Result<()> {
+        self.labels.borrow_mut().insert(label, reference)
+    }
+
+    pub(crate) fn add_listing_entry(&self, entry: ListingEntry) {
+        self.labels.borrow_mut().push_entry(entry);
+    }
+
+    // 1 回目の評価では未収集のため None を返す
+    pub(crate) fn get_listing_entries(&self, kind: ListingKind) -> Option<Vec<ListingEntry>> {
+        self.resolved_labels
+            .as_ref()
+            .map(|resolved_labels| resolved_labels.get_entries(kind))
+    }
+
+    // 1 回目の評価ではラベルが未収集のため None を返す
+    pub(crate) fn resolve_label(&self, label: &str) -> Result<Option<Reference>> {
+        match &self.resolved_labels {
+            Some(resolved_labels) => match resolved_labels.get(label) {
+                Some(reference) => Ok(Some(reference.clone())),
+                None => bail!("undefined label: '{}'", label),
+            },
+            None => Ok(None),
+        }
+    }
+
+    // 引用番号 (初出順) と文献を返す
+    pub(crate) fn cite(&self, key: &str) -> Result<(usize, BibliographyEntry)> {
+        let bibliography = self
+            .bibliography
+            .as_ref()
+            .context("bibliography not found: set 'citation' in preamble")?;
+        let entry = match bibliography.get(key) {
+            Some(entry) => entry.clone(),
+            None => bail!("undefined citation key: '{}'", key),
+        };
+        let number = self.labels.borrow_mut().push_citation(key);
+        Ok((number, entry))
+    }
+
+    // 1 回目の評価では未収集のため None を返す
+    pub(crate) fn get_cited_entries(&self) -> Option<Vec<BibliographyEntry>> {
+        let resolved_labels = self.resolved_labels.as_ref()?;
+        let bibliography = self.bibliography.as_ref()?;
+        Some(
+            resolved_labels
+                .get_citations()
+                .iter()
+                .filter_map(|key| bibliography.get(key).cloned())
+                .collect(),
+        )
+    }
+
     pub(crate) fn evaluate_main_function(
         &self,
         function: &LitedownFunction,
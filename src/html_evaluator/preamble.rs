@@ -1,6 +1,18 @@
+pub(super) mod caption;
+pub(super) mod citation;
+pub(super) mod equation;
 pub(super) mod font;
+pub(super) mod footnote;
+pub(super) mod language;
+pub(super) mod locale;
 pub(super) mod math;
+pub(super) mod math_macros;
+pub(super) mod normalization;
 pub(super) mod page_padding;
 pub(super) mod page_size;
 pub(super) mod preamble;
+pub(super) mod section_numbering;
 pub(super) mod theme;
+pub(super) mod theorem;
+pub(super) mod whitespace;
+pub(super) mod writing_mode;
@@ -0,0 +1,28 @@
+use anyhow::{bail, Result};
+
+use crate::tree::function::LitedownFunction;
+
+#[derive(Debug, Clone)]
+pub struct Caption {
+    pub numbering: CaptionNumbering,
+}
+
+#[derive(Debug, Clone)]
+pub enum CaptionNumbering {
+    // 文書全体の通し番号 (図 3)
+    Document,
+    // 節ごとの番号 (図 2.3)
+    Section,
+}
+
+pub(super) fn evaluate_caption(function: &LitedownFunction, caption: &Caption) -> Result<Caption> {
+    let mut caption = caption.clone();
+    if let Some(numbering) = function.arguments.get_by_name("numbering") {
+        caption.numbering = match numbering.try_into_string()?.as_str() {
+            "document" => CaptionNumbering::Document,
+            "section" => CaptionNumbering::Section,
+            _ => bail!("unknown caption numbering"),
+        };
+    }
+    Ok(caption)
+}
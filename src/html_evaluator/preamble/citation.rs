@@ -0,0 +1,36 @@
+use anyhow::{bail, Result};
+
+use crate::tree::function::LitedownFunction;
+
+#[derive(Debug, Clone)]
+pub struct Citation {
+    // .bib または CSL-JSON (.json) のパス
+    pub src: Option<String>,
+    pub style: CitationStyle,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CitationStyle {
+    // [1, 2]
+    Numeric,
+    // (Knuth, 1984)
+    AuthorYear,
+}
+
+pub(super) fn evaluate_citation(
+    function: &LitedownFunction,
+    citation: &Citation,
+) -> Result<Citation> {
+    let mut citation = citation.clone();
+    if let Some(src) = function.arguments.get_by_name("src") {
+        citation.src = Some(src.try_into_string()?);
+    }
+    if let Some(style) = function.arguments.get_by_name("style") {
+        citation.style = match style.try_into_string()?.as_str() {
+            "numeric" => CitationStyle::Numeric,
+            "author-year" => CitationStyle::AuthorYear,
+            _ => bail!("unknown citation style"),
+        };
+    }
+    Ok(citation)
+}
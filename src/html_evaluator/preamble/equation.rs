@@ -0,0 +1,28 @@
+use anyhow::{bail, Result};
+
+use crate::tree::function::LitedownFunction;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EquationNumbering {
+    // label が付いた数式のみ番号を振る
+    None,
+    // 文書全体の通し番号 (3)
+    Document,
+    // 節ごとの番号 (2.3)
+    Section,
+}
+
+pub(super) fn evaluate_equation(
+    function: &LitedownFunction,
+    numbering: &EquationNumbering,
+) -> Result<EquationNumbering> {
+    match function.arguments.get_by_name("numbering") {
+        Some(numbering) => match numbering.try_into_string()?.as_str() {
+            "none" => Ok(EquationNumbering::None),
+            "document" => Ok(EquationNumbering::Document),
+            "section" => Ok(EquationNumbering::Section),
+            _ => bail!("unknown equation numbering"),
+        },
+        None => Ok(numbering.clone()),
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::{bail, Result};
+
+use crate::tree::function::LitedownFunction;
+
+// 脚注は常に後注 (文書または節の末尾の一覧) として出力する
+// Chrome は float: footnote に対応していないため，ページ下部への配置はできない
+#[derive(Debug, Clone)]
+pub struct Footnote {
+    pub reset: FootnoteReset,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FootnoteReset {
+    Document,
+    Section,
+}
+
+pub(super) fn evaluate_footnote(
+    function: &LitedownFunction,
+    footnote: &Footnote,
+) -> Result<Footnote> {
+    let mut footnote = footnote.clone();
+    if function.arguments.get_by_name("mode").is_some() {
+        bail!("footnote mode is not supported: footnotes are always printed as endnotes");
+    }
+    if let Some(reset) = function.arguments.get_by_name("reset") {
+        footnote.reset = match reset.try_into_string()?.as_str() {
+            "document" => FootnoteReset::Document,
+            "section" => FootnoteReset::Section,
+            _ => bail!("unknown footnote reset"),
+        };
+    }
+    Ok(footnote)
+}
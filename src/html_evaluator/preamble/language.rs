@@ -0,0 +1,27 @@
+use anyhow::{bail, Result};
+
+use crate::{deconstruct_required_arguments, tree::function::LitedownFunction};
+
+// "ja" や "en-US" のような言語タグ
+pub fn validate_language(language: &str) -> Result<&str> {
+    let is_valid = !language.is_empty()
+        && language.split('-').all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+        && language
+            .split('-')
+            .next()
+            .unwrap()
+            .chars()
+            .all(|c| c.is_ascii_alphabetic());
+    if !is_valid {
+        bail!("invalid language: '{}'", language);
+    }
+    Ok(language)
+}
+
+pub(super) fn evaluate_language(function: &LitedownFunction) -> Result<String> {
+    deconstruct_required_arguments!((language) from function);
+    let language = language.try_into_string()?;
+    Ok(validate_language(&language)?.to_string())
+}
@@ -0,0 +1,225 @@
+use anyhow::{bail, Result};
+
+use crate::{tree::function::LitedownFunction, utility::numbering::NumberStyle};
+
+// 自動で生成する見出しや番号の文字列
+#[derive(Debug, Clone)]
+pub struct Locale {
+    pub figure: String,
+    pub table: String,
+    // 図番号とキャプションの間 (図1：...)
+    pub separator: String,
+    // ラベルと番号の間 (図1, Figure 1)
+    pub label_spacing: String,
+    pub toc: String,
+    pub list_of_figures: String,
+    pub list_of_tables: String,
+    pub bibliography: String,
+    pub note: String,
+    pub tip: String,
+    pub warning: String,
+    pub danger: String,
+    pub definition: String,
+    pub theorem: String,
+    pub lemma: String,
+    pub example: String,
+    pub proof: String,
+    // 定理の題を囲む括弧
+    pub title_open: String,
+    pub title_close: String,
+    // {} を番号に置き換える (第{}章, Chapter {})
+    pub chapter: String,
+    pub section: String,
+    // 番号だけの節見出しの後ろに付ける (1.)
+    pub section_suffix: String,
+    pub chapter_number: NumberStyle,
+    pub section_number: NumberStyle,
+    pub caption_number: NumberStyle,
+    pub theorem_number: NumberStyle,
+    pub slide_number: NumberStyle,
+}
+
+impl Locale {
+    // 対応していない言語は英語にする
+    pub fn new(language: &str) -> Locale {
+        let primary = language.split('-').next().unwrap_or_default();
+        match primary.to_lowercase().as_str() {
+            "ja" => Locale::japanese(),
+            _ => Locale::english(),
+        }
+    }
+
+    fn japanese() -> Locale {
+        Locale {
+            figure: "図".to_string(),
+            table: "表".to_string(),
+            separator: "：".to_string(),
+            label_spacing: "".to_string(),
+            toc: "目次".to_string(),
+            list_of_figures: "図目次".to_string(),
+            list_of_tables: "表目次".to_string(),
+            bibliography: "参考文献".to_string(),
+            note: "注".to_string(),
+            tip: "ヒント".to_string(),
+            warning: "警告".to_string(),
+            danger: "危険".to_string(),
+            definition: "定義".to_string(),
+            theorem: "定理".to_string(),
+            lemma: "補題".to_string(),
+            example: "例".to_string(),
+            proof: "証明".to_string(),
+            title_open: "（".to_string(),
+            title_close: "）".to_string(),
+            chapter: "第{}章".to_string(),
+            section: "第{}節".to_string(),
+            section_suffix: ".".to_string(),
+            chapter_number: NumberStyle::Arabic,
+            section_number: NumberStyle::Arabic,
+            caption_number: NumberStyle::Arabic,
+            theorem_number: NumberStyle::Arabic,
+            slide_number: NumberStyle::Arabic,
+        }
+    }
+
+    fn english() -> Locale {
+        Locale {
+            figure: "Figure".to_string(),
+            table: "Table".to_string(),
+            separator: ": ".to_string(),
+            label_spacing: " ".to_string(),
+            toc: "Contents".to_string(),
+            list_of_figures: "List of Figures".to_string(),
+            list_of_tables: "List of Tables".to_string(),
+            bibliography: "References".to_string(),
+            note: "Note".to_string(),
+            tip: "Tip".to_string(),
+            warning: "Warning".to_string(),
+            danger: "Danger".to_string(),
+            definition: "Definition".to_string(),
+            theorem: "Theorem".to_string(),
+            lemma: "Lemma".to_string(),
+            example: "Example".to_string(),
+            proof: "Proof".to_string(),
+            title_open: " (".to_string(),
+            title_close: ")".to_string(),
+            chapter: "Chapter {}".to_string(),
+            section: "Section {}".to_string(),
+            section_suffix: ".".to_string(),
+            chapter_number: NumberStyle::Arabic,
+            section_number: NumberStyle::Arabic,
+            caption_number: NumberStyle::Arabic,
+            theorem_number: NumberStyle::Arabic,
+            slide_number: NumberStyle::Arabic,
+        }
+    }
+
+    // 図1, Figure 1
+    pub fn format_tag(&self, label: &str, number: &str) -> String {
+        if label.is_empty() {
+            return number.to_string();
+        }
+        format!("{}{}{}", label, self.label_spacing, number)
+    }
+
+    pub fn format_chapter(&self, number: usize) -> String {
+        self.chapter
+            .replace("{}", &self.chapter_number.format(number))
+    }
+
+    pub fn format_section(&self, number: usize) -> String {
+        self.section
+            .replace("{}", &self.section_number.format(number))
+    }
+}
+
+// @locale[figure="Fig.", separator=". ", caption_number="roman"] のように上書きする
+pub(super) fn evaluate_locale(function: &LitedownFunction, locale: &Locale) -> Result<Locale> {
+    let mut locale = locale.clone();
+    let labels = [
+        ("figure", &mut locale.figure),
+        ("table", &mut locale.table),
+        ("separator", &mut locale.separator),
+        ("label_spacing", &mut locale.label_spacing),
+        ("toc", &mut locale.toc),
+        ("list_of_figures", &mut locale.list_of_figures),
+        ("list_of_tables", &mut locale.list_of_tables),
+        ("bibliography", &mut locale.bibliography),
+        ("note", &mut locale.note),
+        ("tip", &mut locale.tip),
+        ("warning", &mut locale.warning),
+        ("danger", &mut locale.danger),
+        ("definition", &mut locale.definition),
+        ("theorem", &mut locale.theorem),
+        ("lemma", &mut locale.lemma),
+        ("example", &mut locale.example),
+        ("proof", &mut locale.proof),
+        ("title_open", &mut locale.title_open),
+        ("title_close", &mut locale.title_close),
+        ("chapter", &mut locale.chapter),
+        ("section", &mut locale.section),
+        ("section_suffix", &mut locale.section_suffix),
+    ];
+    for (name, label) in labels {
+        if let Some(value) = function.arguments.get_by_name(name) {
+            *label = value.try_into_string()?;
+        }
+    }
+    for name in ["chapter", "section"] {
+        if let Some(value) = function.arguments.get_by_name(name) {
+            if !value.try_into_string()?.contains("{}") {
+                bail!("locale '{}' must contain '{{}}'", name);
+            }
+        }
+    }
+
+    let number_styles = [
+        ("chapter_number", &mut locale.chapter_number),
+        ("section_number", &mut locale.section_number),
+        ("caption_number", &mut locale.caption_number),
+        ("theorem_number", &mut locale.theorem_number),
+        ("slide_number", &mut locale.slide_number),
+    ];
+    for (name, number_style) in number_styles {
+        if let Some(value) = function.arguments.get_by_name(name) {
+            let value = value.try_into_string()?;
+            *number_style = match NumberStyle::from_name(&value) {
+                Some(style) => style,
+                None => bail!("unknown number style: {}", value),
+            };
+        }
+    }
+    Ok(locale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn japanese_chapter_and_section() {
+        let mut locale = Locale::new("ja-JP");
+        assert_eq!(locale.format_chapter(1), "第1章");
+        assert_eq!(locale.format_section(12), "第12節");
+
+        locale.chapter_number = NumberStyle::Kanji;
+        assert_eq!(locale.format_chapter(0), "第〇章");
+        assert_eq!(locale.format_chapter(10), "第十章");
+        assert_eq!(locale.format_chapter(11), "第十一章");
+        assert_eq!(locale.format_chapter(20), "第二十章");
+        assert_eq!(locale.format_chapter(100), "第百章");
+        // 節の番号は章の番号と別に指定する
+        assert_eq!(locale.format_section(3), "第3節");
+        locale.section_number = NumberStyle::Kanji;
+        assert_eq!(locale.format_section(3), "第三節");
+    }
+
+    #[test]
+    fn english_chapter_and_section() {
+        let mut locale = Locale::new("en");
+        assert_eq!(locale.format_chapter(2), "Chapter 2");
+        locale.section_number = NumberStyle::UpperRoman;
+        assert_eq!(locale.format_section(4), "Section IV");
+        assert_eq!(locale.format_tag("Figure", "1.2"), "Figure 1.2");
+        assert_eq!(locale.format_tag("", "1.2"), "1.2");
+    }
+}
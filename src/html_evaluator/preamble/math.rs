@@ -6,6 +6,8 @@ use crate::{deconstruct_required_arguments, tree::function::LitedownFunction};
 pub enum Math {
     Katex,
     Mathjax,
+    // ビルド時に MathML に変換する
+    Mathml,
 }
 
 pub(super) fn evaluate_math(function: &LitedownFunction) -> Result<Option<Math>> {
@@ -14,6 +16,7 @@ pub(super) fn evaluate_math(function: &LitedownFunction) -> Result<Option<Math>>
     match mode.as_str() {
         "katex" => Ok(Some(Math::Katex)),
         "mathjax" => Ok(Some(Math::Mathjax)),
+        "mathml" => Ok(Some(Math::Mathml)),
         "none" => Ok(None),
         _ => bail!("unknown math mode"),
     }
@@ -0,0 +1,29 @@
+use anyhow::{Context, Result};
+
+use crate::{
+    tree::function::LitedownFunction,
+    utility::tex_macro::{parse_tex_macros_into, TexMacro},
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct MathMacros {
+    pub macros: Vec<TexMacro>,
+    // \newcommand を並べた .sty/.tex ファイル (ソースファイルからの相対パス)
+    pub sources: Vec<String>,
+}
+
+pub(super) fn evaluate_math_macros(
+    function: &LitedownFunction,
+    math_macros: &MathMacros,
+) -> Result<MathMacros> {
+    let mut math_macros = math_macros.clone();
+    if let Some(src) = function.arguments.get_by_name("src") {
+        math_macros.sources.push(src.try_into_string()?);
+    }
+    if !function.body.is_empty() {
+        let source = function.body.try_get_as_string()?;
+        parse_tex_macros_into(&source, &mut math_macros.macros)
+            .context("failed to parse math macros")?;
+    }
+    Ok(math_macros)
+}
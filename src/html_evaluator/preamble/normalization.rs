@@ -0,0 +1,38 @@
+use anyhow::{bail, Result};
+
+use crate::{tree::function::LitedownFunction, utility::text_normalization::PunctuationStyle};
+
+// 本文の文字列を出力前に整える (既定ではすべて無効)
+#[derive(Debug, Clone)]
+pub struct Normalization {
+    // 和欧間スペース
+    pub spacing: bool,
+    pub punctuation: Option<PunctuationStyle>,
+    // 全角英数字を半角にする
+    pub alphanumeric: bool,
+}
+
+// @normalization[spacing=true, punctuation="、。", alphanumeric=true]
+pub(super) fn evaluate_normalization(
+    function: &LitedownFunction,
+    normalization: &Normalization,
+) -> Result<Normalization> {
+    let mut normalization = normalization.clone();
+    if let Some(spacing) = function.arguments.get_by_name("spacing") {
+        normalization.spacing = spacing.try_into_boolean()?;
+    }
+    if let Some(punctuation) = function.arguments.get_by_name("punctuation") {
+        let punctuation = punctuation.try_into_string()?;
+        normalization.punctuation = match punctuation.as_str() {
+            "none" => None,
+            _ => match PunctuationStyle::from_name(&punctuation) {
+                Some(style) => Some(style),
+                None => bail!("unknown punctuation style: {}", punctuation),
+            },
+        };
+    }
+    if let Some(alphanumeric) = function.arguments.get_by_name("alphanumeric") {
+        normalization.alphanumeric = alphanumeric.try_into_boolean()?;
+    }
+    Ok(normalization)
+}
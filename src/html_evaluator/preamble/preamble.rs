@@ -1,13 +1,29 @@
 use anyhow::{bail, Context, Result};
 
-use crate::{evaluate_litedown_function, tree::function::LitedownFunction};
+use crate::{
+    evaluate_litedown_function,
+    tree::function::{LitedownFunction, PassageElement},
+    utility::{numbering::NumberStyle, whitespace::WhitespaceMode},
+};
 
 use super::{
+    caption::{evaluate_caption, Caption, CaptionNumbering},
+    citation::{evaluate_citation, Citation, CitationStyle},
+    equation::{evaluate_equation, EquationNumbering},
     font::{evaluate_font, Font, FontFamily},
+    footnote::{evaluate_footnote, Footnote, FootnoteReset},
+    language::evaluate_language,
+    locale::{evaluate_locale, Locale},
     math::{evaluate_math, Math},
+    math_macros::{evaluate_math_macros, MathMacros},
+    normalization::{evaluate_normalization, Normalization},
     page_padding::{evaluate_page_padding, PagePadding},
     page_size::{evaluate_page_size, PageSize},
+    section_numbering::{evaluate_section_numbering, SectionNumberStyle, SectionNumbering},
     theme::{evaluate_theme, Theme},
+    theorem::{evaluate_theorem, Theorems},
+    whitespace::evaluate_whitespace,
+    writing_mode::{evaluate_writing_mode, WritingMode},
 };
 
 #[derive(Debug)]
@@ -17,6 +33,18 @@ pub struct Preamble {
     pub theme: Theme,
     pub font: Font,
     pub math: Option<Math>,
+    pub math_macros: MathMacros,
+    pub caption: Caption,
+    pub section_numbering: SectionNumbering,
+    pub footnote: Footnote,
+    pub citation: Citation,
+    pub equation_numbering: EquationNumbering,
+    pub theorems: Theorems,
+    pub writing_mode: WritingMode,
+    pub language: String,
+    pub locale: Locale,
+    pub normalization: Normalization,
+    pub whitespace: WhitespaceMode,
 }
 
 pub fn evaluate_preamble(function: &LitedownFunction) -> Result<Preamble> {
@@ -31,6 +59,55 @@ pub fn evaluate_preamble(function: &LitedownFunction) -> Result<Preamble> {
         size: "10.5pt".to_string(),
     };
     let mut math = Some(Math::Katex);
+    let mut math_macros = MathMacros::default();
+    let mut caption = Caption {
+        numbering: CaptionNumbering::Document,
+    };
+    let mut section_numbering = SectionNumbering {
+        levels: vec![
+            SectionNumberStyle::Number(NumberStyle::Arabic),
+            SectionNumberStyle::Number(NumberStyle::Arabic),
+            SectionNumberStyle::Number(NumberStyle::Arabic),
+            SectionNumberStyle::None,
+            SectionNumberStyle::None,
+        ],
+    };
+
+    let mut footnote = Footnote {
+        reset: FootnoteReset::Document,
+    };
+    let mut citation = Citation {
+        src: None,
+        style: CitationStyle::Numeric,
+    };
+    let mut equation_numbering = EquationNumbering::None;
+    let mut writing_mode = WritingMode::Horizontal;
+    let mut normalization = Normalization {
+        spacing: false,
+        punctuation: None,
+        alphanumeric: false,
+    };
+    let mut whitespace = WhitespaceMode::Preserve;
+
+    // 定理の名前などの既定の文字列は言語と @locale で決まるため，先に読んでおく
+    let mut language = "ja".to_string();
+    let mut locale_functions = Vec::new();
+    for passage in &function.body.value {
+        for element in &passage.elements {
+            if let PassageElement::Function(child_function) = element {
+                match child_function.name.as_str() {
+                    "language" => language = evaluate_language(child_function)?,
+                    "locale" => locale_functions.push(child_function),
+                    _ => {}
+                }
+            }
+        }
+    }
+    let mut locale = Locale::new(&language);
+    for locale_function in locale_functions {
+        locale = evaluate_locale(locale_function, &locale)?;
+    }
+    let mut theorems = Theorems::new(&locale);
 
     if function.body.is_empty() {
         bail!("preamble must have body");
@@ -57,6 +134,44 @@ pub fn evaluate_preamble(function: &LitedownFunction) -> Result<Preamble> {
         math: (child_function) => {
             math = evaluate_math(child_function)?;
         }
+        math_macros: (child_function) => {
+            math_macros = evaluate_math_macros(child_function, &math_macros)?;
+        }
+        caption: (child_function) => {
+            caption = evaluate_caption(child_function, &caption)?;
+            // @caption[figure="Fig."] は @locale[figure="Fig."] と同じ
+            locale = evaluate_locale(child_function, &locale)?;
+        }
+        section_numbering: (child_function) => {
+            section_numbering = evaluate_section_numbering(child_function, &section_numbering)?;
+        }
+        footnote: (child_function) => {
+            footnote = evaluate_footnote(child_function, &footnote)?;
+        }
+        citation: (child_function) => {
+            citation = evaluate_citation(child_function, &citation)?;
+        }
+        equation: (child_function) => {
+            equation_numbering = evaluate_equation(child_function, &equation_numbering)?;
+        }
+        theorem: (child_function) => {
+            theorems = evaluate_theorem(child_function, &theorems)?;
+        }
+        writing_mode: (child_function) => {
+            writing_mode = evaluate_writing_mode(child_function)?;
+        }
+        language: (_child_function) => {}
+        locale: (_child_function) => {}
+        // 表記ゆれの検査 (--check) でのみ用いる
+        orthography: (_child_function) => {}
+        // 文字数の集計 (--stats) でのみ用いる
+        statistics: (_child_function) => {}
+        normalization: (child_function) => {
+            normalization = evaluate_normalization(child_function, &normalization)?;
+        }
+        whitespace: (child_function) => {
+            whitespace = evaluate_whitespace(child_function)?;
+        }
     );
 
     let page_size = page_size.context("page-size not found")?;
@@ -67,5 +182,17 @@ pub fn evaluate_preamble(function: &LitedownFunction) -> Result<Preamble> {
         theme,
         font,
         math,
+        math_macros,
+        caption,
+        section_numbering,
+        footnote,
+        citation,
+        equation_numbering,
+        theorems,
+        writing_mode,
+        language,
+        locale,
+        normalization,
+        whitespace,
     })
 }
@@ -0,0 +1,48 @@
+use anyhow::{bail, Result};
+
+use crate::{tree::function::LitedownFunction, utility::numbering::NumberStyle};
+
+// section は h2 から h6 まで
+pub const MAX_SECTION_LEVEL: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SectionNumberStyle {
+    None,
+    Number(NumberStyle),
+    // 第1章
+    JapaneseChapter,
+    // 第1節
+    JapaneseSection,
+}
+
+#[derive(Debug, Clone)]
+pub struct SectionNumbering {
+    // 0 番目が最上位の section
+    pub levels: Vec<SectionNumberStyle>,
+}
+
+pub(super) fn evaluate_section_numbering(
+    function: &LitedownFunction,
+    section_numbering: &SectionNumbering,
+) -> Result<SectionNumbering> {
+    let mut section_numbering = section_numbering.clone();
+    if MAX_SECTION_LEVEL < function.arguments.len() {
+        bail!("section can be nested up to {} levels", MAX_SECTION_LEVEL);
+    }
+    for i in 0..function.arguments.len() {
+        let style = match function.arguments.get_by_index(i) {
+            Some(style) => style.try_into_string()?,
+            None => bail!("section numbering must be positional arguments"),
+        };
+        section_numbering.levels[i] = match style.as_str() {
+            "none" => SectionNumberStyle::None,
+            "japanese-chapter" => SectionNumberStyle::JapaneseChapter,
+            "japanese-section" => SectionNumberStyle::JapaneseSection,
+            _ => match NumberStyle::from_name(&style) {
+                Some(style) => SectionNumberStyle::Number(style),
+                None => bail!("unknown section numbering style: {}", style),
+            },
+        };
+    }
+    Ok(section_numbering)
+}
@@ -0,0 +1,129 @@
+use anyhow::{bail, Result};
+
+use crate::tree::function::LitedownFunction;
+
+use super::locale::Locale;
+
+#[derive(Debug, Clone)]
+pub struct TheoremEnvironment {
+    // 本文で用いる関数名
+    pub name: String,
+    // 見出しに表示する名前 (定理など)
+    pub title: String,
+    // 同じカウンタを用いる環境は通し番号になる (None なら番号を振らない)
+    pub counter: Option<String>,
+    // 末尾に証明終わりの記号を置く
+    pub qed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TheoremNumbering {
+    // 文書全体の通し番号 (定理 3)
+    Document,
+    // 節ごとの番号 (定理 2.3)
+    Section,
+}
+
+#[derive(Debug, Clone)]
+pub struct Theorems {
+    pub environments: Vec<TheoremEnvironment>,
+    pub numbering: TheoremNumbering,
+}
+
+impl Theorems {
+    // 既定の環境の名前は言語によって変わる
+    pub fn new(locale: &Locale) -> Theorems {
+        let numbered = |name: &str, title: &str| TheoremEnvironment {
+            name: name.to_string(),
+            title: title.to_string(),
+            counter: Some("theorem".to_string()),
+            qed: false,
+        };
+        Theorems {
+            environments: vec![
+                numbered("definition", &locale.definition),
+                numbered("theorem", &locale.theorem),
+                numbered("lemma", &locale.lemma),
+                numbered("example", &locale.example),
+                TheoremEnvironment {
+                    name: "proof".to_string(),
+                    title: locale.proof.clone(),
+                    counter: None,
+                    qed: true,
+                },
+            ],
+            numbering: TheoremNumbering::Document,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TheoremEnvironment> {
+        self.environments
+            .iter()
+            .find(|environment| environment.name == name)
+    }
+}
+
+// @theorem["corollary", title="系", counter="theorem"] のように環境を追加・変更する
+pub(super) fn evaluate_theorem(
+    function: &LitedownFunction,
+    theorems: &Theorems,
+) -> Result<Theorems> {
+    let mut theorems = theorems.clone();
+    if let Some(numbering) = function.arguments.get_by_name("numbering") {
+        theorems.numbering = match numbering.try_into_string()?.as_str() {
+            "document" => TheoremNumbering::Document,
+            "section" => TheoremNumbering::Section,
+            _ => bail!("unknown theorem numbering"),
+        };
+    }
+
+    let name = match function.arguments.get_by_index(0) {
+        Some(name) => name.try_into_string()?,
+        None => {
+            for option in ["title", "counter", "numbered", "qed"] {
+                if function.arguments.get_by_name(option).is_some() {
+                    bail!("'{}' requires theorem environment name", option);
+                }
+            }
+            return Ok(theorems);
+        }
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        bail!("invalid theorem environment name: '{}'", name);
+    }
+
+    let mut environment = match theorems.get(&name) {
+        Some(environment) => environment.clone(),
+        None => TheoremEnvironment {
+            name: name.clone(),
+            title: match function.arguments.get_by_name("title") {
+                Some(_) => String::new(),
+                None => bail!("new theorem environment '{}' requires title", name),
+            },
+            counter: Some("theorem".to_string()),
+            qed: false,
+        },
+    };
+    if let Some(title) = function.arguments.get_by_name("title") {
+        environment.title = title.try_into_string()?;
+    }
+    if let Some(counter) = function.arguments.get_by_name("counter") {
+        environment.counter = Some(counter.try_into_string()?);
+    }
+    if let Some(numbered) = function.arguments.get_by_name("numbered") {
+        if !numbered.try_into_boolean()? {
+            environment.counter = None;
+        } else if environment.counter.is_none() {
+            environment.counter = Some("theorem".to_string());
+        }
+    }
+    if let Some(qed) = function.arguments.get_by_name("qed") {
+        environment.qed = qed.try_into_boolean()?;
+    }
+
+    match theorems.environments.iter().position(|e| e.name == name) {
+        Some(index) => theorems.environments[index] = environment,
+        None => theorems.environments.push(environment),
+    }
+    Ok(theorems)
+}
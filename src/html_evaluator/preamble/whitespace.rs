@@ -0,0 +1,16 @@
+use anyhow::{bail, Result};
+
+use crate::{
+    deconstruct_required_arguments, tree::function::LitedownFunction,
+    utility::whitespace::WhitespaceMode,
+};
+
+// @whitespace["preserve" | "collapse" | "cjk"]
+pub(super) fn evaluate_whitespace(function: &LitedownFunction) -> Result<WhitespaceMode> {
+    deconstruct_required_arguments!((mode) from function);
+    let mode = mode.try_into_string()?.to_lowercase();
+    match WhitespaceMode::from_name(&mode) {
+        Some(mode) => Ok(mode),
+        None => bail!("unknown whitespace mode: {}", mode),
+    }
+}
@@ -0,0 +1,20 @@
+use anyhow::{bail, Result};
+
+use crate::{deconstruct_required_arguments, tree::function::LitedownFunction};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WritingMode {
+    Horizontal,
+    // 縦書き (右から左へ行が進み，ページも右から左へめくる)
+    Vertical,
+}
+
+pub(super) fn evaluate_writing_mode(function: &LitedownFunction) -> Result<WritingMode> {
+    deconstruct_required_arguments!((mode) from function);
+    let mode = mode.try_into_string()?.to_lowercase();
+    match mode.as_str() {
+        "horizontal" | "horizontal-tb" => Ok(WritingMode::Horizontal),
+        "vertical" | "vertical-rl" => Ok(WritingMode::Vertical),
+        _ => bail!("unknown writing mode"),
+    }
+}
@@ -1,4 +1,7 @@
 pub(super) mod absolute_block;
 mod footer;
 mod header;
+mod notes;
+mod notes_pages;
 pub(super) mod presentation;
+mod presenter;
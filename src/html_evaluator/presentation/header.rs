@@ -5,7 +5,10 @@ use crate::{
     tree::function::LitedownFunction, utility::html::HtmlElement,
 };
 
-pub fn evaluate_header(_: &Ld2HtmlEvaluator, function: &LitedownFunction) -> Result<HtmlElement> {
+pub fn evaluate_header(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<HtmlElement> {
     let level = if function.arguments.is_empty() {
         "primary".to_string()
     } else {
@@ -24,6 +27,6 @@ pub fn evaluate_header(_: &Ld2HtmlEvaluator, function: &LitedownFunction) -> Res
         .body
         .try_get_as_string()
         .context("function 'header' must have body")?;
-    header_html.append_text(&body);
+    evaluator.append_passage_text(&mut header_html, &body);
     Ok(header_html)
 }
@@ -0,0 +1,19 @@
+use anyhow::{bail, Result};
+
+use crate::{
+    evaluate_with_ld2html_evaluator, html_evaluator::litedown::Ld2HtmlEvaluator,
+    tree::function::LitedownFunction, utility::html::HtmlElement,
+};
+
+pub fn evaluate_notes(
+    evaluator: &Ld2HtmlEvaluator,
+    function: &LitedownFunction,
+) -> Result<HtmlElement> {
+    let mut notes_html = HtmlElement::new("div");
+    notes_html.set_attr("class", "notes");
+    evaluate_with_ld2html_evaluator!(function to notes_html with evaluator);
+    if notes_html.is_child_empty() {
+        bail!("function 'notes' must have body");
+    }
+    Ok(notes_html)
+}
@@ -0,0 +1,104 @@
+use anyhow::{Context, Result};
+
+use crate::{
+    html_evaluator::{
+        litedown::Ld2HtmlContentAttachment,
+        preamble::preamble::Preamble,
+        presentation::presentation::{get_presentation_style, get_slide_size_style, Slide},
+    },
+    utility::html::HtmlElement,
+};
+
+// A4 縦
+const PAGE_WIDTH_MM: f64 = 210.0;
+const PAGE_HEIGHT_MM: f64 = 297.0;
+const PAGE_MARGIN_MM: f64 = 15.0;
+
+pub(super) fn build_notes_pages(
+    preamble: &Preamble,
+    slides: &[Slide],
+) -> Result<Ld2HtmlContentAttachment> {
+    let slide_width = length_to_mm(&preamble.page_size.width)
+        .context("notes pages require an absolute page width")?;
+    let zoom = (PAGE_WIDTH_MM - 2.0 * PAGE_MARGIN_MM) / slide_width;
+
+    let mut head = Vec::new();
+
+    head.push(get_presentation_style());
+    head.push(get_slide_size_style(preamble));
+
+    // notes_pages.less
+    head.push({
+        let mut style = HtmlElement::new("style");
+        style.set_attr("type", "text/less");
+        style.append_raw_text(include_str!("./notes_pages.less"));
+        style
+    });
+
+    // 大きさ設定
+    head.push({
+        let mut style = HtmlElement::new("style");
+        style.set_attr("type", "text/less");
+        style.append_raw_text(&format!(
+            r#"
+            @page {{
+                size: {page_width}mm {page_height}mm;
+                margin: {page_margin}mm;
+            }}
+
+            .notes-page {{
+                & > .presentation > .slide-wrapper {{
+                    zoom: {zoom};
+                }}
+            }}
+            "#,
+            page_width = PAGE_WIDTH_MM,
+            page_height = PAGE_HEIGHT_MM,
+            page_margin = PAGE_MARGIN_MM,
+            zoom = zoom,
+        ));
+        style
+    });
+
+    let mut body = HtmlElement::new("div");
+    body.set_attr("class", "notes-pages");
+    for slide in slides.iter().filter(|slide| slide.pdf) {
+        body.append({
+            let mut page_html = HtmlElement::new("div");
+            page_html.set_attr("class", "notes-page");
+            page_html.append({
+                let mut presentation_html = HtmlElement::new("div");
+                presentation_html.set_attr("class", "presentation");
+                presentation_html.append(slide.wrapper.clone());
+                presentation_html
+            });
+            if let Some(notes) = &slide.notes {
+                page_html.append(notes.clone());
+            }
+            page_html
+        });
+    }
+
+    Ok(Ld2HtmlContentAttachment {
+        name: "notes".to_string(),
+        head,
+        body: vec![body],
+        pdf: true,
+    })
+}
+
+fn length_to_mm(length: &str) -> Option<f64> {
+    let length = length.trim();
+    let unit_start = length.find(|c: char| c.is_ascii_alphabetic())?;
+    let number: f64 = length[..unit_start].parse().ok()?;
+    let mm_per_unit = match &length[unit_start..] {
+        "mm" => 1.0,
+        "cm" => 10.0,
+        "in" => 25.4,
+        "pt" => 25.4 / 72.0,
+        "pc" => 25.4 / 6.0,
+        "px" => 25.4 / 96.0,
+        _ => return None,
+    };
+    Some(number * mm_per_unit)
+}
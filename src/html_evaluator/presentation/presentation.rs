@@ -1,11 +1,15 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 
 use crate::{
     evaluate_litedown_function, evaluate_with_ld2html_evaluator,
     html_evaluator::{
-        litedown::Ld2HtmlEvaluator,
-        preamble::preamble::Preamble,
-        presentation::{footer::evaluate_footer, header::evaluate_header},
+        litedown::{Ld2HtmlContent, Ld2HtmlEvaluator},
+        preamble::{preamble::Preamble, writing_mode::WritingMode},
+        presentation::{
+            footer::evaluate_footer, header::evaluate_header, notes::evaluate_notes,
+            notes_pages::build_notes_pages, presenter::build_presenter_view,
+        },
+        reference::Reference,
     },
     tree::function::LitedownFunction,
     utility::html::HtmlElement,
@@ -15,16 +19,11 @@ pub fn evaluate_presentation(
     evaluator: &Ld2HtmlEvaluator,
     preamble: &Preamble,
     function: &LitedownFunction,
-) -> Result<(Vec<HtmlElement>, Vec<HtmlElement>)> {
+) -> Result<Ld2HtmlContent> {
     let mut head: Vec<HtmlElement> = Vec::new();
 
     // presentation.less
-    head.push({
-        let mut style = HtmlElement::new("style");
-        style.set_attr("type", "text/less");
-        style.append_raw_text(include_str!("./presentation.less"));
-        style
-    });
+    head.push(get_presentation_style());
 
     // 大きさ設定
     head.push({
@@ -46,83 +45,234 @@ pub fn evaluate_presentation(
                 box-sizing: border-box;
                 border-width: 0;
             }}
-
-            .presentation {{
-                & > .slide-wrapper {{
-                    position: relative;
-                    width: {width};
-                    height: {height};
-
-                    & > .slide {{
-                        position: absolute;
-                        width: calc({width} - 2 * {padding_horizontal});
-                        min-height: calc({height} - 1 * {padding_vertical});
-                        transform: translateX({padding_horizontal}) translateY({padding_vertical});
-                    }}
-                }}
-            }}
             "#,
             width = preamble.page_size.width,
             height = preamble.page_size.height,
-            padding_horizontal = preamble.page_padding.horizontal,
-            padding_vertical = preamble.page_padding.vertical,
         ));
         style
     });
+    head.push(get_slide_size_style(preamble));
 
-    let mut body = HtmlElement::new("div");
-    body.set_attr("class", "presentation");
+    let presenter = match function.arguments.get_by_name("presenter") {
+        Some(presenter) => presenter.try_into_boolean()?,
+        None => false,
+    };
+    // 発表者ビューは聴衆側のウィンドウと同期するため viewer を必要とする
+    let viewer = match function.arguments.get_by_name("viewer") {
+        Some(viewer) => viewer.try_into_boolean()?,
+        None => false,
+    } || presenter;
+    let notes_pages = match function.arguments.get_by_name("notes_pages") {
+        Some(notes_pages) => notes_pages.try_into_boolean()?,
+        None => false,
+    };
+    // 分単位
+    let duration = match function.arguments.get_by_name("duration") {
+        Some(duration) => Some(duration.try_into_bare_float()?),
+        None => None,
+    };
+
+    if viewer {
+        // viewer.less
+        head.push({
+            let mut style = HtmlElement::new("style");
+            style.set_attr("type", "text/less");
+            style.append_raw_text(include_str!("./viewer.less"));
+            style
+        });
+
+        // viewer.js
+        head.push({
+            let mut script = HtmlElement::new("script");
+            script.set_attr("defer", "true");
+            script.append_raw_text(include_str!("./viewer.js"));
+            script
+        });
+    }
+
+    let audience_file_name = if presenter {
+        let source_path = evaluator
+            .get_source_path()
+            .context("cannot use presenter view without source path")?;
+        let audience_file_name = source_path.with_extension("html");
+        let audience_file_name = audience_file_name
+            .file_name()
+            .and_then(|name| name.to_str())
+            .context("invalid source path")?;
+        Some(audience_file_name.to_string())
+    } else {
+        None
+    };
+
+    let mut slides = Vec::new();
 
     let mut slide_index = 0;
+    // slide-index を持たないスライドの，直前の slide-index からの通し番号
+    let mut unindexed_slide_count = 0;
 
     evaluate_litedown_function!(function;
         slide: (child_function) => {
-            body.append({
-                let mut slide_wrapper_html = HtmlElement::new("div");
-                slide_wrapper_html.set_attr("class", "slide-wrapper");
-
-                let pdf = match child_function.arguments.get_by_name("pdf") {
-                    Some(show) => show.try_into_boolean()?,
-                    None => true,
-                };
-                slide_wrapper_html.set_attr("data-pdf", &pdf.to_string());
-                if pdf {
-                    slide_wrapper_html.append({
-                        let mut slide_index_html = HtmlElement::new("span");
-                        slide_index_html.set_attr("class", "slide-index");
-                        slide_index_html.append_text(&(slide_index + 1).to_string());
-                        slide_index += 1;
-                        slide_index_html
-                    });
+            let mut slide_wrapper_html = HtmlElement::new("div");
+            slide_wrapper_html.set_attr("class", "slide-wrapper");
+
+            let pdf = match child_function.arguments.get_by_name("pdf") {
+                Some(show) => show.try_into_boolean()?,
+                None => true,
+            };
+            slide_wrapper_html.set_attr("data-pdf", &pdf.to_string());
+            let slide_number = &evaluator.get_preamble().locale.slide_number;
+            let id = if pdf {
+                slide_wrapper_html.append({
+                    let mut slide_index_html = HtmlElement::new("span");
+                    slide_index_html.set_attr("class", "slide-index");
+                    slide_index_html.append_text(&slide_number.format(slide_index + 1));
+                    slide_index += 1;
+                    unindexed_slide_count = 0;
+                    slide_index_html
+                });
+                format!("slide-{}", slide_index)
+            } else {
+                unindexed_slide_count += 1;
+                format!("slide-{}-{}", slide_index, unindexed_slide_count)
+            };
+            slide_wrapper_html.set_attr("id", &id);
+
+            if let Some(label) = child_function.arguments.get_by_name("label") {
+                let label = label.try_into_string()?;
+                if !pdf {
+                    bail!("cannot put label '{}' on slide without slide-index", label);
                 }
+                evaluator.define_label(&label, Reference {
+                    id: id.clone(),
+                    number: slide_number.format(slide_index),
+                })?;
+            }
 
-                slide_wrapper_html.append(evaluate_slide(evaluator, child_function)?);
+            let (slide_html, notes_html) = evaluate_slide(evaluator, child_function)?;
+            slide_wrapper_html.append(slide_html);
 
-                slide_wrapper_html
+            slides.push(Slide {
+                id,
+                pdf,
+                wrapper: slide_wrapper_html,
+                notes: notes_html,
             });
         }
         title: (child_function) => {
-            body.append({
-                let mut slide_wrapper_html = HtmlElement::new("div");
-                slide_wrapper_html.set_attr("class", "slide-wrapper");
-                slide_wrapper_html.append(evaluate_title(evaluator, child_function)?);
-                slide_wrapper_html
+            let mut slide_wrapper_html = HtmlElement::new("div");
+            slide_wrapper_html.set_attr("class", "slide-wrapper");
+            unindexed_slide_count += 1;
+            let id = format!("slide-{}-{}", slide_index, unindexed_slide_count);
+            slide_wrapper_html.set_attr("id", &id);
+            slide_wrapper_html.append(evaluate_title(evaluator, child_function)?);
+
+            slides.push(Slide {
+                id,
+                pdf: true,
+                wrapper: slide_wrapper_html,
+                notes: None,
             });
         }
     );
 
+    let mut body = HtmlElement::new("div");
+    body.set_attr("class", "presentation");
+    if viewer {
+        body.set_attr("data-viewer", "true");
+    }
+    if let Some(audience_file_name) = &audience_file_name {
+        body.set_attr("data-channel", &get_channel_name(audience_file_name));
+    }
+    for slide in &slides {
+        body.append(slide.wrapper.clone());
+    }
+
+    let mut attachments = Vec::new();
+    if let Some(audience_file_name) = &audience_file_name {
+        attachments.push(build_presenter_view(
+            &slides,
+            audience_file_name,
+            &get_channel_name(audience_file_name),
+            duration,
+        ));
+    }
+    if notes_pages {
+        attachments.push(build_notes_pages(preamble, &slides)?);
+    }
+
     let body = vec![body];
 
-    Ok((head, body))
+    Ok(Ld2HtmlContent {
+        head,
+        body,
+        attachments,
+    })
+}
+
+pub(super) struct Slide {
+    pub id: String,
+    pub pdf: bool,
+    pub wrapper: HtmlElement,
+    pub notes: Option<HtmlElement>,
+}
+
+pub(super) fn get_presentation_style() -> HtmlElement {
+    let mut style = HtmlElement::new("style");
+    style.set_attr("type", "text/less");
+    style.append_raw_text(include_str!("./presentation.less"));
+    style
+}
+
+pub(super) fn get_slide_size_style(preamble: &Preamble) -> HtmlElement {
+    let mut style = HtmlElement::new("style");
+    style.set_attr("type", "text/less");
+    style.append_raw_text(&format!(
+        r#"
+        .presentation {{
+            & > .slide-wrapper {{
+                position: relative;
+                width: {width};
+                height: {height};
+
+                & > .slide {{
+                    position: absolute;
+                    width: calc({width} - 2 * {padding_horizontal});
+                    min-height: calc({height} - 1 * {padding_vertical});
+                    transform: translateX({padding_horizontal}) translateY({padding_vertical});
+                    {vertical_style}
+                }}
+            }}
+        }}
+        "#,
+        width = preamble.page_size.width,
+        height = preamble.page_size.height,
+        padding_horizontal = preamble.page_padding.horizontal,
+        padding_vertical = preamble.page_padding.vertical,
+        // 縦書きでは右上から書き始めるため，高さも固定する
+        vertical_style = match preamble.writing_mode {
+            WritingMode::Horizontal => String::new(),
+            WritingMode::Vertical => format!(
+                "writing-mode: vertical-rl; height: calc({} - 2 * {});",
+                preamble.page_size.height, preamble.page_padding.vertical
+            ),
+        },
+    ));
+    style
+}
+
+fn get_channel_name(audience_file_name: &str) -> String {
+    format!("litedown:{}", audience_file_name)
 }
 
 fn evaluate_slide(
     evaluator: &Ld2HtmlEvaluator,
     function: &LitedownFunction,
-) -> Result<HtmlElement> {
+) -> Result<(HtmlElement, Option<HtmlElement>)> {
     let mut slide_html = HtmlElement::new("div");
     slide_html.set_attr("class", "slide");
 
+    let mut notes_html = None;
+
     evaluate_with_ld2html_evaluator!(function to slide_html with evaluator;
         function: {
             header: (child_function) => {
@@ -131,9 +281,15 @@ fn evaluate_slide(
             footer: (child_function) => {
                 slide_html.append(evaluate_footer(evaluator, child_function)?);
             }
+            notes: (child_function) => {
+                if notes_html.is_some() {
+                    bail!("'slide' got multiple 'notes'");
+                }
+                notes_html = Some(evaluate_notes(evaluator, child_function)?);
+            }
         }
     );
-    Ok(slide_html)
+    Ok((slide_html, notes_html))
 }
 
 fn evaluate_title(
@@ -0,0 +1,109 @@
+window.addEventListener("DOMContentLoaded", () => {
+  const presenter = document.querySelector(".presenter");
+  const audience = presenter.dataset.audience;
+  const duration = presenter.dataset.duration ? Number(presenter.dataset.duration) : null;
+  const channel = new BroadcastChannel(presenter.dataset.channel);
+
+  const notes = Array.from(presenter.querySelectorAll(".notes-list > .slide-notes"));
+  const ids = notes.map((el) => el.dataset.slide);
+
+  const currentFrame = presenter.querySelector(".current-slide > iframe");
+  const nextFrame = presenter.querySelector(".next-slide > iframe");
+  const position = presenter.querySelector(".status > .position");
+  const elapsed = presenter.querySelector(".status > .elapsed");
+  const remaining = presenter.querySelector(".status > .remaining");
+
+  let current = -1;
+  let startedAt = Date.now();
+
+  const frameSource = (id) => `${audience}?embed#${id}`;
+
+  const show = (index, broadcast) => {
+    index = Math.max(0, Math.min(ids.length - 1, index));
+    if (index === current) {
+      return;
+    }
+    current = index;
+
+    currentFrame.src = frameSource(ids[current]);
+    if (current + 1 < ids.length) {
+      nextFrame.src = frameSource(ids[current + 1]);
+      nextFrame.style.visibility = "visible";
+    } else {
+      nextFrame.style.visibility = "hidden";
+    }
+
+    notes.forEach((el, i) => el.classList.toggle("current", i === current));
+    position.textContent = `${current + 1} / ${ids.length}`;
+
+    if (broadcast) {
+      channel.postMessage({ type: "slide", id: ids[current] });
+    }
+  };
+
+  const formatTime = (seconds) => {
+    const sign = seconds < 0 ? "-" : "";
+    seconds = Math.abs(Math.floor(seconds));
+    const m = String(Math.floor(seconds / 60)).padStart(2, "0");
+    const s = String(seconds % 60).padStart(2, "0");
+    return `${sign}${m}:${s}`;
+  };
+
+  const tick = () => {
+    const seconds = (Date.now() - startedAt) / 1000;
+    elapsed.textContent = formatTime(seconds);
+    if (duration !== null) {
+      remaining.textContent = formatTime(duration - seconds);
+      remaining.classList.toggle("overtime", duration < seconds);
+    }
+  };
+
+  channel.addEventListener("message", (event) => {
+    const message = event.data;
+    if (message.type === "slide") {
+      const index = ids.indexOf(message.id);
+      if (0 <= index) {
+        show(index, false);
+      }
+    }
+  });
+
+  window.addEventListener("keydown", (event) => {
+    if (event.ctrlKey || event.metaKey || event.altKey) {
+      return;
+    }
+    switch (event.key) {
+      case "ArrowRight":
+      case "ArrowDown":
+      case "PageDown":
+      case "Enter":
+      case " ":
+        show(current + 1, true);
+        break;
+      case "ArrowLeft":
+      case "ArrowUp":
+      case "PageUp":
+      case "Backspace":
+        show(current - 1, true);
+        break;
+      case "Home":
+        show(0, true);
+        break;
+      case "End":
+        show(ids.length - 1, true);
+        break;
+      case "r":
+        startedAt = Date.now();
+        tick();
+        break;
+      default:
+        return;
+    }
+    event.preventDefault();
+  });
+
+  show(0, false);
+  channel.postMessage({ type: "sync-request" });
+  tick();
+  setInterval(tick, 500);
+});
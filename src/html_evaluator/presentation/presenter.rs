@@ -0,0 +1,80 @@
+use crate::{
+    html_evaluator::{litedown::Ld2HtmlContentAttachment, presentation::presentation::Slide},
+    utility::html::HtmlElement,
+};
+
+pub(super) fn build_presenter_view(
+    slides: &[Slide],
+    audience_file_name: &str,
+    channel_name: &str,
+    duration: Option<f64>,
+) -> Ld2HtmlContentAttachment {
+    let mut head = Vec::new();
+
+    // presenter.less
+    head.push({
+        let mut style = HtmlElement::new("style");
+        style.set_attr("type", "text/less");
+        style.append_raw_text(include_str!("./presenter.less"));
+        style
+    });
+
+    // presenter.js
+    head.push({
+        let mut script = HtmlElement::new("script");
+        script.set_attr("defer", "true");
+        script.append_raw_text(include_str!("./presenter.js"));
+        script
+    });
+
+    let mut presenter_html = HtmlElement::new("div");
+    presenter_html.set_attr("class", "presenter");
+    presenter_html.set_attr("data-audience", audience_file_name);
+    presenter_html.set_attr("data-channel", channel_name);
+    if let Some(duration) = duration {
+        // 秒単位
+        presenter_html.set_attr("data-duration", &((duration * 60.0).round()).to_string());
+    }
+
+    for class in ["current-slide", "next-slide"] {
+        presenter_html.append({
+            let mut frame_wrapper_html = HtmlElement::new("div");
+            frame_wrapper_html.set_attr("class", class);
+            frame_wrapper_html.append(HtmlElement::new("iframe"));
+            frame_wrapper_html
+        });
+    }
+
+    presenter_html.append({
+        let mut notes_list_html = HtmlElement::new("div");
+        notes_list_html.set_attr("class", "notes-list");
+        for slide in slides {
+            let mut slide_notes_html = HtmlElement::new("div");
+            slide_notes_html.set_attr("class", "slide-notes");
+            slide_notes_html.set_attr("data-slide", &slide.id);
+            if let Some(notes) = &slide.notes {
+                slide_notes_html.append(notes.clone());
+            }
+            notes_list_html.append(slide_notes_html);
+        }
+        notes_list_html
+    });
+
+    presenter_html.append({
+        let mut status_html = HtmlElement::new("div");
+        status_html.set_attr("class", "status");
+        for class in ["position", "elapsed", "remaining"] {
+            let mut item_html = HtmlElement::new("span");
+            item_html.set_attr("class", class);
+            status_html.append(item_html);
+        }
+        status_html
+    });
+
+    Ld2HtmlContentAttachment {
+        name: "presenter".to_string(),
+        head,
+        body: vec![presenter_html],
+        pdf: false,
+    }
+}
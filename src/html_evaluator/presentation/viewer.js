@@ -0,0 +1,178 @@
+window.addEventListener("DOMContentLoaded", () => {
+  const presentation = document.querySelector(".presentation[data-viewer]");
+  if (!presentation) {
+    return;
+  }
+
+  const wrappers = Array.from(presentation.querySelectorAll(":scope > .slide-wrapper"));
+  if (wrappers.length === 0) {
+    return;
+  }
+
+  // 発表者ビューに埋め込まれている場合は同期しない
+  const embedded = new URLSearchParams(location.search).has("embed");
+  const channel =
+    !embedded && presentation.dataset.channel
+      ? new BroadcastChannel(presentation.dataset.channel)
+      : null;
+
+  const overviewColumns = 4;
+  const overviewGap = 16;
+
+  let mode = "single";
+  let current = 0;
+
+  const slideSize = () => ({
+    width: wrappers[0].offsetWidth,
+    height: wrappers[0].offsetHeight,
+  });
+
+  const layout = () => {
+    const { width, height } = slideSize();
+    if (mode === "single") {
+      const scale = Math.min(window.innerWidth / width, window.innerHeight / height);
+      const left = (window.innerWidth - width * scale) / 2;
+      const top = (window.innerHeight - height * scale) / 2;
+      wrappers.forEach((wrapper) => {
+        wrapper.style.transform = `translate(${left}px, ${top}px) scale(${scale})`;
+      });
+    } else {
+      const cellWidth =
+        (presentation.clientWidth - overviewGap * (overviewColumns + 1)) / overviewColumns;
+      const scale = cellWidth / width;
+      const cellHeight = height * scale;
+      wrappers.forEach((wrapper, i) => {
+        const left = overviewGap + (i % overviewColumns) * (cellWidth + overviewGap);
+        const top = overviewGap + Math.floor(i / overviewColumns) * (cellHeight + overviewGap);
+        wrapper.style.transform = `translate(${left}px, ${top}px) scale(${scale})`;
+      });
+    }
+  };
+
+  const indexOfHash = (hash) => {
+    const id = decodeURIComponent(hash.replace(/^#/, ""));
+    return wrappers.findIndex((wrapper) => wrapper.id === id);
+  };
+
+  const goTo = (index, broadcast = true) => {
+    index = Math.max(0, Math.min(wrappers.length - 1, index));
+    wrappers[current].classList.remove("current");
+    current = index;
+    wrappers[current].classList.add("current");
+    if (location.hash !== `#${wrappers[current].id}`) {
+      history.replaceState(null, "", `#${wrappers[current].id}`);
+    }
+    presentation.dispatchEvent(
+      new CustomEvent("litedown-slide-change", { detail: { index: current, id: wrappers[current].id } })
+    );
+    if (channel && broadcast) {
+      channel.postMessage({ type: "slide", id: wrappers[current].id });
+    }
+  };
+
+  const setMode = (newMode) => {
+    mode = newMode;
+    presentation.setAttribute("data-viewer-mode", mode);
+    layout();
+    if (mode === "overview") {
+      wrappers[current].scrollIntoView({ block: "center" });
+    }
+  };
+
+  const toggleFullscreen = () => {
+    if (document.fullscreenElement) {
+      document.exitFullscreen();
+    } else {
+      document.documentElement.requestFullscreen();
+    }
+  };
+
+  if (channel) {
+    channel.addEventListener("message", (event) => {
+      const message = event.data;
+      if (message.type === "slide") {
+        const index = wrappers.findIndex((wrapper) => wrapper.id === message.id);
+        if (0 <= index && index !== current) {
+          goTo(index, false);
+        }
+      } else if (message.type === "sync-request") {
+        channel.postMessage({ type: "slide", id: wrappers[current].id });
+      }
+    });
+  }
+
+  window.addEventListener("keydown", (event) => {
+    if (event.ctrlKey || event.metaKey || event.altKey) {
+      return;
+    }
+    switch (event.key) {
+      case "ArrowRight":
+      case "ArrowDown":
+      case "PageDown":
+      case "Enter":
+      case " ":
+        goTo(current + 1);
+        break;
+      case "ArrowLeft":
+      case "ArrowUp":
+      case "PageUp":
+      case "Backspace":
+        goTo(current - 1);
+        break;
+      case "Home":
+        goTo(0);
+        break;
+      case "End":
+        goTo(wrappers.length - 1);
+        break;
+      case "f":
+        toggleFullscreen();
+        break;
+      case "o":
+        setMode(mode === "single" ? "overview" : "single");
+        break;
+      case "Escape":
+        if (mode === "overview") {
+          setMode("single");
+        }
+        break;
+      default:
+        return;
+    }
+    event.preventDefault();
+  });
+
+  wrappers.forEach((wrapper, i) => {
+    wrapper.addEventListener("click", (event) => {
+      if (event.target.closest("a")) {
+        return;
+      }
+      if (mode === "overview") {
+        goTo(i);
+        setMode("single");
+      } else if (event.clientX < window.innerWidth / 2) {
+        goTo(current - 1);
+      } else {
+        goTo(current + 1);
+      }
+    });
+  });
+
+  window.addEventListener("hashchange", () => {
+    const index = indexOfHash(location.hash);
+    if (0 <= index && index !== current) {
+      goTo(index, !embedded);
+    }
+  });
+  window.addEventListener("resize", layout);
+
+  window.litedownViewer = {
+    presentation,
+    wrappers,
+    goTo,
+    current: () => current,
+  };
+
+  goTo(Math.max(0, indexOfHash(location.hash)), false);
+  setMode("single");
+});
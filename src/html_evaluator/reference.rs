@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+
+use crate::utility::html::HtmlElement;
+
+#[derive(Clone, Debug)]
+pub struct Reference {
+    pub id: String,
+    pub number: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ListingKind {
+    Section,
+    Figure,
+    Table,
+}
+
+// 目次・図目次・表目次の項目
+#[derive(Clone)]
+pub struct ListingEntry {
+    pub kind: ListingKind,
+    pub id: String,
+    pub number: Option<String>,
+    pub title: Option<HtmlElement>,
+    // 目次の字下げに用いる (図・表は 1)
+    pub level: usize,
+}
+
+#[derive(Default)]
+pub struct ReferenceTable {
+    labels: HashMap<String, Reference>,
+    entries: Vec<ListingEntry>,
+    // 引用された文献のキー (初出順)
+    citations: Vec<String>,
+}
+
+impl ReferenceTable {
+    pub fn insert(&mut self, label: &str, reference: Reference) -> Result<()> {
+        if self.labels.contains_key(label) {
+            bail!("duplicate label: '{}'", label);
+        }
+        self.labels.insert(label.to_string(), reference);
+        Ok(())
+    }
+
+    pub fn get(&self, label: &str) -> Option<&Reference> {
+        self.labels.get(label)
+    }
+
+    pub fn push_entry(&mut self, entry: ListingEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn get_entries(&self, kind: ListingKind) -> Vec<ListingEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.kind == kind)
+            .cloned()
+            .collect()
+    }
+
+    // 初出順の番号 (1 始まり) を返す
+    pub fn push_citation(&mut self, key: &str) -> usize {
+        match self.citations.iter().position(|citation| citation == key) {
+            Some(index) => index + 1,
+            None => {
+                self.citations.push(key.to_string());
+                self.citations.len()
+            }
+        }
+    }
+
+    pub fn get_citations(&self) -> &Vec<String> {
+        &self.citations
+    }
+}
@@ -1,3 +1,4 @@
+pub mod analyzer;
 pub mod html_evaluator;
 pub mod parser;
 pub mod tree;
@@ -2,22 +2,32 @@ use std::{
     env,
     fs::{self, File},
     io::Write,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 use anyhow::{bail, Context, Result};
 
 use litedown_lang::{
+    analyzer::{orthography::check_orthography, statistics::count_statistics},
     html_evaluator::litedown::{evaluate_litedown_to_html, Ld2HtmlInput},
     parser::litedown::parse_litedown,
     utility::{
-        git::get_current_git_version, html::print_html_to_pdf, tree_string_builder::ToTreeString,
+        git::get_current_git_version,
+        html::{print_html_to_pdf, Html, HtmlElement},
+        pdf::{get_named_destination_pages, set_right_to_left_direction},
+        tree_string_builder::ToTreeString,
     },
 };
 
+// 目次などのページ番号を埋め込んで印刷し直す回数の上限
+const MAX_PAGE_NUMBER_PASSES: usize = 3;
+
 struct Argument<'a> {
     path: &'a str,
     pdf: bool,
+    check: bool,
+    stats: bool,
+    pretty: bool,
 }
 
 fn main() -> Result<()> {
@@ -25,6 +35,9 @@ fn main() -> Result<()> {
     let args = {
         let mut path = None;
         let mut pdf = None;
+        let mut check = None;
+        let mut stats = None;
+        let mut pretty = None;
         let mut i = 1;
         while i < args.len() {
             let arg = args[i].as_str();
@@ -37,6 +50,27 @@ fn main() -> Result<()> {
                             bail!("Duplicate argument: {}", arg);
                         }
                     }
+                    "-check" | "c" => {
+                        if check.is_none() {
+                            check = Some(true);
+                        } else {
+                            bail!("Duplicate argument: {}", arg);
+                        }
+                    }
+                    "-stats" | "s" => {
+                        if stats.is_none() {
+                            stats = Some(true);
+                        } else {
+                            bail!("Duplicate argument: {}", arg);
+                        }
+                    }
+                    "-pretty" => {
+                        if pretty.is_none() {
+                            pretty = Some(true);
+                        } else {
+                            bail!("Duplicate argument: {}", arg);
+                        }
+                    }
                     _ => {
                         bail!("Unknown argument: {}", arg);
                     }
@@ -53,6 +87,9 @@ fn main() -> Result<()> {
         Argument {
             path: path.context("No path provided")?,
             pdf: pdf.unwrap_or(false),
+            check: check.unwrap_or(false),
+            stats: stats.unwrap_or(false),
+            pretty: pretty.unwrap_or(false),
         }
     };
 
@@ -74,6 +111,34 @@ fn main() -> Result<()> {
     println!("Parsing {:?}", source_path);
     let ast = parse_litedown(&source_code).context("Could not parse ld")?;
 
+    // 表記ゆれの検査だけを行う
+    if args.check {
+        let reports =
+            check_orthography(&ast, Some(&source_path)).context("Could not check orthography")?;
+        for report in &reports {
+            println!("{}:{}", source_path.display(), report);
+        }
+        if !reports.is_empty() {
+            bail!("Found {} inconsistent spellings", reports.len());
+        }
+        println!("No inconsistent spellings found");
+        return Ok(());
+    }
+
+    // 文字数などの集計だけを行う
+    if args.stats {
+        let entries = count_statistics(&ast).context("Could not count statistics")?;
+        for entry in &entries {
+            println!(
+                "{}{}: {}",
+                "  ".repeat(entry.level),
+                entry.name,
+                entry.statistics
+            );
+        }
+        return Ok(());
+    }
+
     let output_ast_path = source_path.with_extension("ldast.txt");
     println!("Saving ast to {:?}", output_ast_path);
 
@@ -94,30 +159,100 @@ fn main() -> Result<()> {
     }
 
     // html
-    let html = evaluate_litedown_to_html(Ld2HtmlInput {
+    let mut output = evaluate_litedown_to_html(Ld2HtmlInput {
         ast,
         source_path: Some(source_path.clone()),
     })
-    .context("Could not evaluate ast to html")?
-    .to_string()
-    .merge();
+    .context("Could not evaluate ast to html")?;
 
+    save_html(
+        &source_path,
+        "html",
+        &mut output.html,
+        args.pdf,
+        args.pretty,
+        output.right_to_left,
+    );
+    for attachment in &mut output.attachments {
+        save_html(
+            &source_path,
+            &format!("{}.html", attachment.name),
+            &mut attachment.html,
+            args.pdf && attachment.pdf,
+            args.pretty,
+            false,
+        );
+    }
+
+    Ok(())
+}
+
+fn save_html(
+    source_path: &Path,
+    extension: &str,
+    html: &mut Html,
+    pdf: bool,
+    pretty: bool,
+    right_to_left: bool,
+) {
     // save html
-    let output_html_path = source_path.with_extension("html");
+    let output_html_path = source_path.with_extension(extension);
     println!("Saving html to {:?}", output_html_path);
-
-    let mut output_html_file = File::create(&output_html_path).unwrap();
-    writeln!(output_html_file, "{}", html).unwrap();
-    output_html_file.flush().unwrap();
+    let html_string = write_html(&output_html_path, html, pretty);
 
     // pdf
-    if args.pdf {
-        let output_pdf_path = source_path.with_extension("pdf");
+    if pdf {
+        let output_pdf_path = output_html_path.with_extension("pdf");
         println!("Saving pdf to {:?}", output_pdf_path);
 
-        let output_pdf_data = print_html_to_pdf(output_html_path.to_str().unwrap()).unwrap();
+        let mut output_pdf_data = print_html_to_pdf(output_html_path.to_str().unwrap()).unwrap();
+        // 目次などのページ番号は，一度印刷して各項目のページを調べてから埋め込み，印刷し直す
+        // 番号を埋め込んだことでページがずれることがあるので，変わらなくなるまで繰り返す
+        if html_string.contains("class=\"listing\"") {
+            let mut pages = Vec::new();
+            for _ in 0..MAX_PAGE_NUMBER_PASSES {
+                let new_pages = get_named_destination_pages(&output_pdf_data).unwrap();
+                if new_pages == pages {
+                    break;
+                }
+                pages = new_pages;
+                html.append_head(get_page_number_style(&pages));
+                write_html(&output_html_path, html, pretty);
+                output_pdf_data = print_html_to_pdf(output_html_path.to_str().unwrap()).unwrap();
+            }
+        }
+        if right_to_left {
+            output_pdf_data = set_right_to_left_direction(output_pdf_data).unwrap();
+        }
         fs::write(output_pdf_path, &output_pdf_data).unwrap();
     }
+}
 
-    Ok(())
+fn write_html(output_html_path: &Path, html: &Html, pretty: bool) -> String {
+    let html = if pretty {
+        html.to_pretty_string().merge()
+    } else {
+        html.to_string().merge()
+    };
+
+    let mut output_html_file = File::create(output_html_path).unwrap();
+    writeln!(output_html_file, "{}", html).unwrap();
+    output_html_file.flush().unwrap();
+    html
+}
+
+// 目次などの項目 (リンク) に，リンク先のページ番号を CSS 変数として与える
+fn get_page_number_style(pages: &[(String, usize)]) -> HtmlElement {
+    let mut style_html = HtmlElement::new("style");
+    for (id, page) in pages {
+        let id = id
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('<', "\\3c ");
+        style_html.append_raw_text(&format!(
+            ".listing a[href=\"#{}\"] {{ --page-number: \"{}\"; }}\n",
+            id, page
+        ));
+    }
+    style_html
 }
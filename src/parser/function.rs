@@ -9,11 +9,12 @@ use crate::{
     utility::indented_string::IndentedStringIterator,
 };
 
-use super::function_body::parse_function_body;
+use super::function_body::{get_current_position, parse_function_body};
 
 pub(crate) fn parse_function(iter: &mut IndentedStringIterator) -> Result<LitedownFunction> {
     iter.parse(|iter| {
         let started_at_first_character = iter.current_indices().char_index == 0;
+        let position = get_current_position(iter);
 
         iter.next_char_as('@')
             .context("The start character '@' not found")?;
@@ -24,6 +25,7 @@ pub(crate) fn parse_function(iter: &mut IndentedStringIterator) -> Result<Litedo
             FunctionBody {
                 form: FunctionBodyForm::Inline,
                 value: Vec::new(),
+                raw: false,
             }
         });
 
@@ -31,6 +33,7 @@ pub(crate) fn parse_function(iter: &mut IndentedStringIterator) -> Result<Litedo
             name,
             arguments,
             body,
+            position,
         };
         Ok(function)
     })
@@ -1,7 +1,9 @@
 use anyhow::{bail, Result};
 
 use crate::{
-    tree::function::{FunctionBody, FunctionBodyForm, LitedownPassage, PassageElement},
+    tree::function::{
+        FunctionBody, FunctionBodyForm, LitedownPassage, PassageElement, SourcePosition,
+    },
     utility::indented_string::IndentedStringIterator,
 };
 
@@ -16,13 +18,15 @@ pub fn parse_function_body(
         if let Ok(_) = iter.next_char_as('{') {
             let mut elements = Vec::new();
             let mut string_body = String::new();
+            let mut string_position = get_current_position(iter);
             loop {
                 if let Ok(function) = parse_function(iter) {
                     if !string_body.is_empty() {
-                        elements.push(PassageElement::String(string_body));
+                        elements.push(PassageElement::String(string_body, string_position));
                         string_body = String::new();
                     }
                     elements.push(PassageElement::Function(function));
+                    string_position = get_current_position(iter);
                     continue;
                 }
 
@@ -30,11 +34,12 @@ pub fn parse_function_body(
                     Some(char) => {
                         if char == '}' {
                             if !string_body.is_empty() {
-                                elements.push(PassageElement::String(string_body));
+                                elements.push(PassageElement::String(string_body, string_position));
                             }
                             return Ok(Some(FunctionBody {
                                 form: FunctionBodyForm::Inline,
                                 value: vec![LitedownPassage { elements }],
+                                raw: false,
                             }));
                         } else {
                             string_body.push(char);
@@ -70,13 +75,15 @@ pub fn parse_function_body(
             // }
             // bail!("The finish character '$' not found");
             let mut body = String::new();
+            let position = get_current_position(iter);
             while let Some(char) = iter.next_char() {
                 if char == '$' {
                     return Ok(Some(FunctionBody {
                         form: FunctionBodyForm::Inline,
                         value: vec![LitedownPassage {
-                            elements: vec![PassageElement::String(body)],
+                            elements: vec![PassageElement::String(body, position)],
                         }],
+                        raw: true,
                     }));
                 }
                 body.push(char);
@@ -119,6 +126,7 @@ pub fn parse_function_body(
                         return Ok(Some(FunctionBody {
                             form: FunctionBodyForm::Block,
                             value: Vec::new(),
+                            raw: false,
                         }));
                     }
                 };
@@ -126,18 +134,26 @@ pub fn parse_function_body(
                 let mut passages = Vec::new();
                 let mut elements = Vec::new();
                 let mut string_body = String::new();
+                let mut string_position = get_current_position(iter);
                 loop {
+                    let position = get_current_position(iter);
                     match iter.next_char() {
                         Some(char) => {
                             if char == '@' {
                                 if !string_body.is_empty() {
-                                    elements.push(PassageElement::String(string_body));
+                                    elements.push(PassageElement::String(
+                                        string_body,
+                                        string_position.clone(),
+                                    ));
                                     string_body = String::new();
                                 }
                                 iter.back_char().unwrap();
                                 let function = parse_function(iter)?;
                                 elements.push(PassageElement::Function(function));
                             } else {
+                                if string_body.is_empty() {
+                                    string_position = position;
+                                }
                                 string_body.push(char);
                             }
                         }
@@ -148,10 +164,16 @@ pub fn parse_function_body(
 
                             let passed_blank_lines = iter.pass_blank_lines();
                             if passed_blank_lines == 0 {
+                                if string_body.is_empty() {
+                                    string_position = position;
+                                }
                                 string_body.push('\n');
                             } else {
                                 if !string_body.is_empty() {
-                                    elements.push(PassageElement::String(string_body));
+                                    elements.push(PassageElement::String(
+                                        string_body,
+                                        string_position.clone(),
+                                    ));
                                     string_body = String::new();
                                 }
                                 if !elements.is_empty() {
@@ -174,7 +196,7 @@ pub fn parse_function_body(
                     }
                 }
                 if !string_body.is_empty() {
-                    elements.push(PassageElement::String(string_body));
+                    elements.push(PassageElement::String(string_body, string_position));
                 }
                 if !elements.is_empty() {
                     passages.push(LitedownPassage { elements });
@@ -185,6 +207,7 @@ pub fn parse_function_body(
                 return Ok(Some(FunctionBody {
                     form: FunctionBodyForm::Block,
                     value: passages,
+                    raw: false,
                 }));
             }
 
@@ -204,6 +227,7 @@ pub fn parse_function_body(
                 }
 
                 let mut body = String::new();
+                let mut position = get_current_position(iter);
                 let mut is_body_indent_fitted = false;
                 loop {
                     if !is_body_indent_fitted && iter.has_next_char() {
@@ -213,6 +237,7 @@ pub fn parse_function_body(
                             bail!("block raw string body function must have body");
                         }
                         iter.set_line_indent(body_indent);
+                        position = get_current_position(iter);
                     }
                     while let Some(char) = iter.next_char() {
                         body.push(char);
@@ -231,11 +256,22 @@ pub fn parse_function_body(
                 return Ok(Some(FunctionBody {
                     form: FunctionBodyForm::Block,
                     value: vec![LitedownPassage {
-                        elements: vec![PassageElement::String(body)],
+                        elements: vec![PassageElement::String(body, position)],
                     }],
+                    raw: true,
                 }));
             }
         }
         Ok(None)
     })
 }
+
+// 次に読む文字の位置
+pub(super) fn get_current_position(iter: &IndentedStringIterator) -> SourcePosition {
+    let indices = iter.current_indices();
+    SourcePosition {
+        line: indices.line_index + 1,
+        column: indices.indent_level + indices.char_index + 1,
+        indent: indices.indent_level + 1,
+    }
+}
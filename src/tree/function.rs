@@ -10,6 +10,8 @@ pub struct LitedownFunction {
     pub name: String,
     pub arguments: FunctionArgumentContainer,
     pub body: FunctionBody,
+    // 先頭の @ の位置
+    pub position: SourcePosition,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -79,6 +81,8 @@ pub enum FunctionBodyForm {
 pub struct FunctionBody {
     pub form: FunctionBodyForm,
     pub value: Vec<LitedownPassage>,
+    // $...$ や : で書かれた，そのままの文字列
+    pub raw: bool,
 }
 
 impl FunctionBody {
@@ -91,7 +95,7 @@ impl FunctionBody {
         for passage in &self.value {
             for passage_element in &passage.elements {
                 match passage_element {
-                    PassageElement::String(string) => result.push_str(string),
+                    PassageElement::String(string, _) => result.push_str(string),
                     PassageElement::Function(_) => bail!("cannot write function"),
                 }
             }
@@ -109,10 +113,36 @@ pub struct LitedownPassage {
 #[serde(tag = "type", content = "value")]
 #[serde(rename_all = "snake_case")]
 pub enum PassageElement {
-    String(String),
+    String(String, SourcePosition),
     Function(LitedownFunction),
 }
 
+// ソースコード上の文字列の位置 (行・列は 1 始まり)
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct SourcePosition {
+    pub line: usize,
+    pub column: usize,
+    // 2 行目以降はこの列から始まる
+    pub indent: usize,
+}
+
+impl SourcePosition {
+    // 文字列の offset 文字目の (行, 列)
+    pub fn locate(&self, text: &str, offset: usize) -> (usize, usize) {
+        let mut line = self.line;
+        let mut column = self.column;
+        for c in text.chars().take(offset) {
+            if c == '\n' {
+                line += 1;
+                column = self.indent;
+            } else {
+                column += 1;
+            }
+        }
+        (line, column)
+    }
+}
+
 impl ToTreeString for LitedownFunction {
     fn write_tree_string(&self, builder: &mut TreeStringBuilder, level: usize) {
         builder.add_node(level, format!("Function({:?})", self.name));
@@ -136,7 +166,7 @@ impl ToTreeString for LitedownFunction {
             builder.add_node(level + 2, "Passage");
             for passage_element in &passage.elements {
                 match passage_element {
-                    PassageElement::String(string) => {
+                    PassageElement::String(string, _) => {
                         builder.add_node(level + 3, format!("{:?}", string));
                     }
                     PassageElement::Function(function) => {
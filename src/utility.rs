@@ -1,5 +1,13 @@
+pub mod bibtex;
+pub mod csv;
+pub mod flate;
 pub mod git;
 pub mod html;
 pub mod indented_string;
+pub mod mathml;
+pub mod numbering;
+pub mod pdf;
+pub mod tex_macro;
+pub mod text_normalization;
 pub mod tree_string_builder;
 pub mod whitespace;
@@ -0,0 +1,527 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+
+#[derive(Debug, Clone)]
+pub struct BibtexEntry {
+    pub entry_type: String,
+    pub key: String,
+    // フィールド名は小文字
+    pub fields: HashMap<String, String>,
+}
+
+pub fn parse_bibtex(source: &str) -> Result<Vec<BibtexEntry>> {
+    let mut parser = BibtexParser {
+        chars: source.chars().collect(),
+        index: 0,
+        strings: default_strings(),
+    };
+    let mut entries = Vec::new();
+    while parser.skip_to_entry() {
+        let line = parser.line();
+        if let Some(entry) = parser
+            .parse_entry()
+            .with_context(|| format!("failed to parse bibtex: line {}", line))?
+        {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+struct BibtexParser {
+    chars: Vec<char>,
+    index: usize,
+    // @string で定義された文字列
+    strings: HashMap<String, String>,
+}
+
+impl BibtexParser {
+    fn line(&self) -> usize {
+        self.chars[..self.index.min(self.chars.len())]
+            .iter()
+            .filter(|&&c| c == '\n')
+            .count()
+            + 1
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.index += 1;
+        Some(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespaces();
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => bail!("expected '{}', found '{}'", expected, c),
+            None => bail!("expected '{}', found EOF", expected),
+        }
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.index += 1;
+            } else if c == '%' {
+                // 行コメント
+                while let Some(c) = self.next() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    // エントリの外側の文字列は読み飛ばす
+    fn skip_to_entry(&mut self) -> bool {
+        while let Some(c) = self.peek() {
+            if c == '@' {
+                return true;
+            }
+            self.index += 1;
+        }
+        false
+    }
+
+    fn parse_identifier(&mut self) -> Result<String> {
+        self.skip_whitespaces();
+        let mut identifier = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || "{}()=,#\"@%".contains(c) {
+                break;
+            }
+            identifier.push(c);
+            self.index += 1;
+        }
+        if identifier.is_empty() {
+            bail!("identifier not found");
+        }
+        Ok(identifier)
+    }
+
+    fn parse_entry(&mut self) -> Result<Option<BibtexEntry>> {
+        self.expect('@')?;
+        let entry_type = self.parse_identifier()?.to_lowercase();
+
+        self.skip_whitespaces();
+        let closing = match self.next() {
+            Some('{') => '}',
+            Some('(') => ')',
+            _ => bail!("expected '{{' or '(' after '@{}'", entry_type),
+        };
+
+        match entry_type.as_str() {
+            "comment" => {
+                self.skip_comment(closing)?;
+                Ok(None)
+            }
+            "preamble" => {
+                self.parse_value()?;
+                self.expect(closing)?;
+                Ok(None)
+            }
+            "string" => {
+                let name = self.parse_identifier()?.to_lowercase();
+                self.expect('=')?;
+                let value = self.parse_value()?;
+                self.strings.insert(name, value);
+                self.expect(closing)?;
+                Ok(None)
+            }
+            _ => {
+                self.skip_whitespaces();
+                let mut key = String::new();
+                while let Some(c) = self.peek() {
+                    if c == ',' || c == closing || c.is_whitespace() {
+                        break;
+                    }
+                    key.push(c);
+                    self.index += 1;
+                }
+                if key.is_empty() {
+                    bail!("entry key not found");
+                }
+
+                let mut fields = HashMap::new();
+                loop {
+                    self.skip_whitespaces();
+                    match self.next() {
+                        Some(',') => {
+                            self.skip_whitespaces();
+                            // 末尾のカンマ
+                            if self.peek() == Some(closing) {
+                                self.index += 1;
+                                break;
+                            }
+                            let name = self.parse_identifier()?.to_lowercase();
+                            self.expect('=')?;
+                            let value = self.parse_value()?;
+                            if fields.contains_key(&name) {
+                                bail!("duplicate field '{}' in entry '{}'", name, key);
+                            }
+                            fields.insert(name, value);
+                        }
+                        Some(c) if c == closing => break,
+                        Some(c) => bail!("unexpected character '{}' in entry '{}'", c, key),
+                        None => bail!("EOF while scanning entry '{}'", key),
+                    }
+                }
+
+                Ok(Some(BibtexEntry {
+                    entry_type,
+                    key,
+                    fields,
+                }))
+            }
+        }
+    }
+
+    // "a" # {b} # macro のような連結を含む値
+    fn parse_value(&mut self) -> Result<String> {
+        let mut value = String::new();
+        loop {
+            self.skip_whitespaces();
+            match self.peek() {
+                Some('{') => value.push_str(&self.parse_braced_value()?),
+                Some('"') => value.push_str(&self.parse_quoted_value()?),
+                Some(c) if c.is_ascii_digit() => {
+                    while let Some(c) = self.peek() {
+                        if !c.is_ascii_digit() {
+                            break;
+                        }
+                        value.push(c);
+                        self.index += 1;
+                    }
+                }
+                Some(_) => {
+                    let name = self.parse_identifier()?.to_lowercase();
+                    match self.strings.get(&name) {
+                        Some(string) => value.push_str(string),
+                        None => bail!("undefined string '{}'", name),
+                    }
+                }
+                None => bail!("EOF while scanning value"),
+            }
+            self.skip_whitespaces();
+            if self.peek() == Some('#') {
+                self.index += 1;
+            } else {
+                break;
+            }
+        }
+        Ok(value)
+    }
+
+    // @comment{...} または @comment(...) の中身を読み飛ばす (開き括弧は読んだ後)
+    fn skip_comment(&mut self, closing: char) -> Result<()> {
+        let opening = if closing == '}' { '{' } else { '(' };
+        let mut depth = 0;
+        loop {
+            match self.next() {
+                Some(c) if c == opening => depth += 1,
+                Some(c) if c == closing => {
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                    depth -= 1;
+                }
+                Some(_) => {}
+                None => bail!("EOF while scanning comment"),
+            }
+        }
+    }
+
+    // 外側の括弧は含めず，内側の括弧はそのまま残す
+    fn parse_braced_value(&mut self) -> Result<String> {
+        self.expect('{')?;
+        let mut value = String::new();
+        let mut depth = 0;
+        loop {
+            match self.next() {
+                Some('{') => {
+                    depth += 1;
+                    value.push('{');
+                }
+                Some('}') => {
+                    if depth == 0 {
+                        return Ok(value);
+                    }
+                    depth -= 1;
+                    value.push('}');
+                }
+                Some('\\') => {
+                    value.push('\\');
+                    if let Some(c) = self.next() {
+                        value.push(c);
+                    }
+                }
+                Some(c) => value.push(c),
+                None => bail!("EOF while scanning braced value"),
+            }
+        }
+    }
+
+    fn parse_quoted_value(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut value = String::new();
+        let mut depth = 0;
+        loop {
+            match self.next() {
+                Some('"') if depth == 0 => return Ok(value),
+                Some('{') => {
+                    depth += 1;
+                    value.push('{');
+                }
+                Some('}') => {
+                    if depth == 0 {
+                        bail!("unbalanced braces in quoted value");
+                    }
+                    depth -= 1;
+                    value.push('}');
+                }
+                Some('\\') => {
+                    value.push('\\');
+                    if let Some(c) = self.next() {
+                        value.push(c);
+                    }
+                }
+                Some(c) => value.push(c),
+                None => bail!("EOF while scanning quoted value"),
+            }
+        }
+    }
+}
+
+fn default_strings() -> HashMap<String, String> {
+    let months = [
+        ("jan", "January"),
+        ("feb", "February"),
+        ("mar", "March"),
+        ("apr", "April"),
+        ("may", "May"),
+        ("jun", "June"),
+        ("jul", "July"),
+        ("aug", "August"),
+        ("sep", "September"),
+        ("oct", "October"),
+        ("nov", "November"),
+        ("dec", "December"),
+    ];
+    months
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+// 保護用の括弧や簡単な LaTeX 記法を取り除く
+pub fn clean_bibtex_text(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '{' | '}' => {}
+            '~' => result.push('\u{a0}'),
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                if chars.get(i + 2) == Some(&'-') {
+                    result.push('—');
+                    i += 2;
+                } else {
+                    result.push('–');
+                    i += 1;
+                }
+            }
+            '\\' => {
+                let command = chars.get(i + 1).copied();
+                match command {
+                    Some(accent @ ('\'' | '`' | '^' | '"' | '~' | '=' | '.')) => {
+                        // \'{e} や \'e
+                        let mut j = i + 2;
+                        while chars.get(j) == Some(&'{') {
+                            j += 1;
+                        }
+                        if let Some(&base) = chars.get(j) {
+                            result.push_str(&apply_accent(accent, base));
+                            j += 1;
+                        }
+                        while chars.get(j) == Some(&'}') {
+                            j += 1;
+                        }
+                        i = j;
+                        continue;
+                    }
+                    Some(symbol @ ('&' | '%' | '$' | '#' | '_' | '{' | '}')) => {
+                        result.push(symbol);
+                        i += 2;
+                        continue;
+                    }
+                    Some(c) if c.is_ascii_alphabetic() => {
+                        let mut j = i + 1;
+                        let mut name = String::new();
+                        while let Some(&c) = chars.get(j) {
+                            if !c.is_ascii_alphabetic() {
+                                break;
+                            }
+                            name.push(c);
+                            j += 1;
+                        }
+                        if chars.get(j) == Some(&' ') {
+                            j += 1;
+                        }
+                        match name.as_str() {
+                            "ss" => result.push('ß'),
+                            "ae" => result.push('æ'),
+                            "AE" => result.push('Æ'),
+                            "oe" => result.push('œ'),
+                            "OE" => result.push('Œ'),
+                            "o" => result.push('ø'),
+                            "O" => result.push('Ø'),
+                            "aa" => result.push('å'),
+                            "AA" => result.push('Å'),
+                            "l" => result.push('ł'),
+                            "L" => result.push('Ł'),
+                            "i" => result.push('ı'),
+                            "LaTeX" => result.push_str("LaTeX"),
+                            "TeX" => result.push_str("TeX"),
+                            // \textit{...} などは中身のみ残す
+                            _ => {}
+                        }
+                        i = j;
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            _ => result.push(c),
+        }
+        i += 1;
+    }
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn apply_accent(accent: char, base: char) -> String {
+    let combining = match accent {
+        '\'' => '\u{301}',
+        '`' => '\u{300}',
+        '^' => '\u{302}',
+        '"' => '\u{308}',
+        '~' => '\u{303}',
+        '=' => '\u{304}',
+        '.' => '\u{307}',
+        _ => return base.to_string(),
+    };
+    // 合成済み文字にはしないが，表示上は同等になる
+    format!("{}{}", base, combining)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_one(source: &str) -> BibtexEntry {
+        let mut entries = parse_bibtex(source).unwrap();
+        assert_eq!(entries.len(), 1);
+        entries.remove(0)
+    }
+
+    #[test]
+    fn entry() {
+        let entry = parse_one(
+            r#"@Article{knuth1984,
+  Author = "Donald E. Knuth",
+  title  = {Literate Programming},
+  year   = 1984,
+}"#,
+        );
+        assert_eq!(entry.entry_type, "article");
+        assert_eq!(entry.key, "knuth1984");
+        assert_eq!(entry.fields["author"], "Donald E. Knuth");
+        assert_eq!(entry.fields["title"], "Literate Programming");
+        assert_eq!(entry.fields["year"], "1984");
+    }
+
+    #[test]
+    fn parenthesized_entry() {
+        let entry = parse_one("@book(key, title = {A (B) C})");
+        assert_eq!(entry.key, "key");
+        assert_eq!(entry.fields["title"], "A (B) C");
+    }
+
+    #[test]
+    fn nested_braces() {
+        let entry =
+            parse_one(r#"@misc{key, title = {The {\TeX}book {and {more}}}, note = "a {"} b"}"#);
+        assert_eq!(entry.fields["title"], r"The {\TeX}book {and {more}}");
+        assert_eq!(entry.fields["note"], r#"a {"} b"#);
+        assert!(parse_bibtex("@misc{key, title = {a {b}}").is_err());
+    }
+
+    #[test]
+    fn concatenation_and_strings() {
+        let entry = parse_one(
+            r#"@String{acm = "ACM"}
+@string(press = {Press})
+@misc{key, publisher = acm # " " # press, note = "a" # {b} # 1}"#,
+        );
+        assert_eq!(entry.fields["publisher"], "ACM Press");
+        assert_eq!(entry.fields["note"], "ab1");
+        assert!(parse_bibtex("@misc{key, publisher = unknown}").is_err());
+    }
+
+    #[test]
+    fn month_macros() {
+        let entry = parse_one("@misc{key, month = jan, note = dec # { 24}}");
+        assert_eq!(entry.fields["month"], "January");
+        assert_eq!(entry.fields["note"], "December 24");
+    }
+
+    #[test]
+    fn comments_and_preamble() {
+        let entries = parse_bibtex(
+            r#"Text outside entries is ignored.
+@comment{ignored, {nested} braces}
+@Comment(ignored (nested) parentheses)
+@preamble{"\newcommand{\noop}[1]{}"}
+@misc{key, title = {Title}}"#,
+        )
+        .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "key");
+        assert!(parse_bibtex("@comment(unterminated").is_err());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            format!(
+                "{:#}",
+                parse_bibtex("@misc{a, title = {A}}\n\n@misc{b, title = {B}, title = {C}}")
+                    .unwrap_err()
+            ),
+            "failed to parse bibtex: line 3: duplicate field 'title' in entry 'b'"
+        );
+        assert!(parse_bibtex("@misc{, title = {A}}").is_err());
+        assert!(parse_bibtex("@misc{key, title = {A}").is_err());
+    }
+
+    #[test]
+    fn clean_text() {
+        assert_eq!(clean_bibtex_text(r"{The} \TeX book"), "The TeXbook");
+        assert_eq!(
+            clean_bibtex_text(r#"G\"{o}del and Erd\'os"#),
+            "Go\u{308}del and Erdo\u{301}s"
+        );
+        assert_eq!(clean_bibtex_text("pages 1--10---"), "pages 1–10—");
+        assert_eq!(clean_bibtex_text(r"A \& B"), "A & B");
+    }
+}
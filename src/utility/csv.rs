@@ -0,0 +1,147 @@
+use anyhow::{bail, Result};
+
+// RFC 4180 に沿って解析する (引用符内の区切り文字・改行・"" に対応)
+pub fn parse_csv(source: &str, delimiter: char) -> Result<Vec<Vec<String>>> {
+    let source = source.strip_prefix('\u{feff}').unwrap_or(source);
+
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    // 引用符で始まったフィールドの終わりの引用符の後
+    let mut after_quote = false;
+    let mut line = 1;
+
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => {
+                    quoted = false;
+                    after_quote = true;
+                }
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() && !after_quote => quoted = true,
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+                after_quote = false;
+                line += 1;
+            }
+            c if c == delimiter => {
+                record.push(std::mem::take(&mut field));
+                after_quote = false;
+            }
+            _ => {
+                if after_quote {
+                    bail!("unexpected character after closing quote: line {}", line);
+                }
+                field.push(c);
+            }
+        }
+    }
+    if quoted {
+        bail!("EOF while scanning quoted field: line {}", line);
+    }
+    if !field.is_empty() || after_quote || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    // 空行は無視する
+    records.retain(|record| !(record.len() == 1 && record[0].is_empty()));
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|field| field.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn simple() {
+        assert_eq!(
+            parse_csv("a,b,c\n1,2,3\n", ',').unwrap(),
+            records(&[&["a", "b", "c"], &["1", "2", "3"]])
+        );
+        // 末尾の改行がなくてもよい・空のフィールド
+        assert_eq!(
+            parse_csv("a,,c\n,2,", ',').unwrap(),
+            records(&[&["a", "", "c"], &["", "2", ""]])
+        );
+    }
+
+    #[test]
+    fn quoted_fields() {
+        assert_eq!(
+            parse_csv("\"a,b\",\"say \"\"hi\"\"\",\"\"\n", ',').unwrap(),
+            records(&[&["a,b", "say \"hi\"", ""]])
+        );
+        // 引用符で始まらないフィールドの引用符はそのまま
+        assert_eq!(parse_csv("a\"b\n", ',').unwrap(), records(&[&["a\"b"]]));
+    }
+
+    #[test]
+    fn newlines_in_quotes() {
+        assert_eq!(
+            parse_csv("\"line 1\nline 2\",x\r\n\"a\r\nb\",y", ',').unwrap(),
+            records(&[&["line 1\nline 2", "x"], &["a\r\nb", "y"]])
+        );
+    }
+
+    #[test]
+    fn line_endings_and_blank_lines() {
+        assert_eq!(
+            parse_csv("a,b\r\n\r\nc,d\re,f\n\n", ',').unwrap(),
+            records(&[&["a", "b"], &["c", "d"], &["e", "f"]])
+        );
+    }
+
+    #[test]
+    fn bom() {
+        assert_eq!(
+            parse_csv("\u{feff}\"a\",b\n", ',').unwrap(),
+            records(&[&["a", "b"]])
+        );
+    }
+
+    #[test]
+    fn tsv() {
+        assert_eq!(
+            parse_csv("a,b\tc\n\"d\te\"\tf\n", '\t').unwrap(),
+            records(&[&["a,b", "c"], &["d\te", "f"]])
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse_csv("a\n\"b\nc", ',').unwrap_err().to_string(),
+            "EOF while scanning quoted field: line 3"
+        );
+        assert_eq!(
+            parse_csv("a\n\"b\"c\n", ',').unwrap_err().to_string(),
+            "unexpected character after closing quote: line 2"
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use anyhow::{bail, Result};
+
+// 長さ符号 257..285 の基準値と追加ビット数
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA_BITS: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+// 距離符号 0..29 の基準値と追加ビット数
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+// 符号長の符号長が並ぶ順
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+// zlib 形式 (RFC 1950) のデータを展開する (PDF の FlateDecode)
+// PDF には末尾のチェックサムが欠けたものもあるため，チェックサムは確かめない
+pub fn inflate_zlib(data: &[u8]) -> Result<Vec<u8>> {
+    let (cmf, flg) = match data {
+        [cmf, flg, ..] => (*cmf, *flg),
+        _ => bail!("zlib header not found"),
+    };
+    if cmf & 0x0f != 8 || (u16::from(cmf) << 8 | u16::from(flg)) % 31 != 0 {
+        bail!("invalid zlib header");
+    }
+    if flg & 0x20 != 0 {
+        bail!("zlib preset dictionary is not supported");
+    }
+    inflate(&data[2..])
+}
+
+// deflate 形式 (RFC 1951) のデータを展開する
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>> {
+    let mut reader = BitReader { data, position: 0 };
+    let mut output = Vec::new();
+    loop {
+        let last = reader.read_bits(1)? == 1;
+        match reader.read_bits(2)? {
+            0 => inflate_stored_block(&mut reader, &mut output)?,
+            1 => {
+                let (literals, distances) = get_fixed_huffman()?;
+                inflate_huffman_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_huffman(&mut reader)?;
+                inflate_huffman_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            _ => bail!("invalid deflate block type"),
+        }
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    // ビット単位の位置 (各バイトの下位ビットから読む)
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn read_bits(&mut self, count: u8) -> Result<u32> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = match self.data.get(self.position / 8) {
+                Some(byte) => byte,
+                None => bail!("unexpected end of deflate data"),
+            };
+            value |= u32::from(byte >> (self.position % 8) & 1) << i;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+}
+
+// 正規ハフマン符号 (符号長ごとの個数と，符号順に並べた記号)
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Huffman> {
+        let mut counts = [0; 16];
+        for &length in lengths {
+            counts[usize::from(length)] += 1;
+        }
+        counts[0] = 0;
+        // 符号の数が多すぎる場合は不正 (少ない場合は許す)
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = left * 2 - i32::from(count);
+            if left < 0 {
+                bail!("invalid huffman code lengths");
+            }
+        }
+
+        let mut offsets = [0; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; usize::from(offsets[15] + counts[15])];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                let offset = &mut offsets[usize::from(length)];
+                symbols[usize::from(*offset)] = symbol as u16;
+                *offset += 1;
+            }
+        }
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16> {
+        // 符号は上位ビットから 1 ビットずつ読む
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for &count in &self.counts[1..] {
+            code |= reader.read_bits(1)? as i32;
+            let count = i32::from(count);
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        bail!("invalid huffman code")
+    }
+}
+
+fn inflate_stored_block(reader: &mut BitReader, output: &mut Vec<u8>) -> Result<()> {
+    reader.align_to_byte();
+    let length = reader.read_bits(16)?;
+    let complement = reader.read_bits(16)?;
+    if length != !complement & 0xffff {
+        bail!("invalid stored block length");
+    }
+    let start = reader.position / 8;
+    let end = start + length as usize;
+    match reader.data.get(start..end) {
+        Some(bytes) => output.extend_from_slice(bytes),
+        None => bail!("unexpected end of deflate data"),
+    }
+    reader.position = end * 8;
+    Ok(())
+}
+
+fn get_fixed_huffman() -> Result<(Huffman, Huffman)> {
+    let mut lengths = [0; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+fn read_dynamic_huffman(reader: &mut BitReader) -> Result<(Huffman, Huffman)> {
+    let literal_count = reader.read_bits(5)? as usize + 257;
+    let distance_count = reader.read_bits(5)? as usize + 1;
+    let code_length_count = reader.read_bits(4)? as usize + 4;
+    if 286 < literal_count || 30 < distance_count {
+        bail!("invalid dynamic huffman header");
+    }
+
+    let mut code_length_lengths = [0; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_length_lengths[index] = reader.read_bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_length_lengths)?;
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let symbol = code_lengths.decode(reader)?;
+        let (length, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => match lengths.last() {
+                Some(&previous) => (previous, 3 + reader.read_bits(2)?),
+                None => bail!("repeated code length without previous length"),
+            },
+            17 => (0, 3 + reader.read_bits(3)?),
+            _ => (0, 11 + reader.read_bits(7)?),
+        };
+        for _ in 0..repeat {
+            lengths.push(length);
+        }
+    }
+    if literal_count + distance_count < lengths.len() {
+        bail!("too many code lengths");
+    }
+    if lengths[256] == 0 {
+        bail!("missing end-of-block code");
+    }
+    Ok((
+        Huffman::new(&lengths[..literal_count])?,
+        Huffman::new(&lengths[literal_count..])?,
+    ))
+}
+
+fn inflate_huffman_block(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<()> {
+    loop {
+        let symbol = literals.decode(reader)?;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let index = usize::from(symbol - 257);
+                if LENGTH_BASES.len() <= index {
+                    bail!("invalid deflate length code");
+                }
+                let length = usize::from(LENGTH_BASES[index])
+                    + reader.read_bits(LENGTH_EXTRA_BITS[index])? as usize;
+
+                let index = usize::from(distances.decode(reader)?);
+                if DISTANCE_BASES.len() <= index {
+                    bail!("invalid deflate distance code");
+                }
+                let distance = usize::from(DISTANCE_BASES[index])
+                    + reader.read_bits(DISTANCE_EXTRA_BITS[index])? as usize;
+                if output.len() < distance {
+                    bail!("deflate distance too far back");
+                }
+                // 重なりうるので 1 バイトずつ写す
+                let start = output.len() - distance;
+                for i in 0..length {
+                    output.push(output[start + i]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn stored_block() {
+        // 圧縮なし (zlib.compress(b"abc", 0))
+        assert_eq!(
+            inflate_zlib(&from_hex("7801010300fcff616263024d0127")).unwrap(),
+            b"abc"
+        );
+    }
+
+    #[test]
+    fn fixed_huffman() {
+        assert_eq!(
+            inflate_zlib(&from_hex("78da4b4c4a0600024d0127")).unwrap(),
+            b"abc"
+        );
+        // 長さと距離による繰り返しを含む
+        let expected = [b"litedown ".repeat(20), b"PDF xref stream".to_vec()].concat();
+        assert_eq!(
+            inflate_zlib(&from_hex(
+                "78dacbc92c494dc92fcf53c8193a8c001737858aa2d43485e292a2d4c45c004a094bd4"
+            ))
+            .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn dynamic_huffman() {
+        let expected = [
+            b"In Chrome output the catalog, the page tree and the named destinations may live in compressed object streams. "
+                .repeat(3),
+            b"a".repeat(53),
+            b"e".repeat(36),
+        ]
+        .concat();
+        assert_eq!(
+            inflate_zlib(&from_hex(concat!(
+                "78dadd8dc10dc3300c0357e1004197c82b63a83691b88824c3520274fb0699a0e8b3f723710417c3",
+                "bc0d57c28fec472237a248caeeeb74872e2b91838458bd1b136545656433c9e6165079636f27d10c",
+                "c5b50f465c8e3f5f2c89b8e6a2f1c0f2c76ff20bfc820f4f629a7d"
+            )))
+            .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(inflate_zlib(&[]).is_err());
+        assert!(inflate_zlib(&from_hex("7800")).is_err());
+        // 途中で切れたデータ
+        assert!(inflate_zlib(&from_hex("78da4b4c4a")).is_err());
+        // 長さの補数が合わない
+        assert!(inflate_zlib(&from_hex("7801010300fcfe616263")).is_err());
+        // 予約されたブロック形式
+        assert!(inflate(&[0x07]).is_err());
+    }
+}
@@ -1,21 +1,25 @@
-use std::collections::HashMap;
-
 use headless_chrome::{types::PrintToPdfOptions, Browser, LaunchOptions};
 
 use anyhow::Result;
 
 pub struct Html {
+    lang: String,
     head: Vec<HtmlElement>,
     body: Vec<HtmlElement>,
 }
 impl Html {
     pub fn new() -> Self {
         Html {
+            lang: "ja".to_string(),
             head: Vec::new(),
             body: Vec::new(),
         }
     }
 
+    pub fn set_lang(&mut self, lang: &str) {
+        self.lang = lang.to_string();
+    }
+
     pub fn append_head(&mut self, element: HtmlElement) {
         self.head.push(element);
     }
@@ -25,6 +29,16 @@ impl Html {
     }
 
     pub fn to_string(&self) -> HtmlString {
+        self.to_html_string(false)
+    }
+
+    // ブロック要素ごとに改行して字下げする (表示は変わらない)
+    pub fn to_pretty_string(&self) -> HtmlString {
+        self.to_html_string(true)
+    }
+
+    fn to_html_string(&self, pretty: bool) -> HtmlString {
+        let indent = if pretty { Some(0) } else { None };
         let mut head = HtmlElement::new("head");
         for el in &self.head {
             head.append(el.clone());
@@ -36,8 +50,10 @@ impl Html {
         }
 
         HtmlString {
-            head: head.to_string(),
-            body: body.to_string(),
+            lang: self.lang.clone(),
+            head: head.to_string_with_indent(indent),
+            body: body.to_string_with_indent(indent),
+            pretty,
         }
     }
 }
@@ -45,7 +61,8 @@ impl Html {
 #[derive(Clone)]
 pub struct HtmlElement {
     tag: String,
-    attr: HashMap<String, String>,
+    // 出力が実行ごとに変わらないよう，追加した順に保持する
+    attr: Vec<(String, String)>,
     children: Option<Vec<HtmlElementChild>>,
 }
 
@@ -53,7 +70,7 @@ impl HtmlElement {
     pub fn new(tag: &str) -> Self {
         HtmlElement {
             tag: tag.to_string(),
-            attr: HashMap::new(),
+            attr: Vec::new(),
             children: Some(Vec::new()),
         }
     }
@@ -61,7 +78,7 @@ impl HtmlElement {
     pub fn new_void(tag: &str) -> Self {
         HtmlElement {
             tag: tag.to_string(),
-            attr: HashMap::new(),
+            attr: Vec::new(),
             children: None,
         }
     }
@@ -76,7 +93,10 @@ impl HtmlElement {
     }
 
     pub fn set_attr(&mut self, key: &str, value: &str) {
-        self.attr.insert(key.to_string(), value.to_string());
+        match self.attr.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.attr.push((key.to_string(), value.to_string())),
+        }
     }
 
     pub fn append(&mut self, element: HtmlElement) -> &mut Self {
@@ -100,53 +120,149 @@ impl HtmlElement {
     }
 
     pub fn append_text(&mut self, element: &str) -> &mut Self {
-        self.append_raw_text(&escape_html_text(element))
+        self.append_raw_text(&escape_html_text(element, true))
     }
 
-    fn write_to_string(&self, buffer: &mut String) {
+    // 空白と改行を HTML の既定どおり折り返しに任せる
+    pub fn append_collapsible_text(&mut self, element: &str) -> &mut Self {
+        self.append_raw_text(&escape_html_text(element, false))
+    }
+
+    fn to_string_with_indent(&self, indent: Option<usize>) -> String {
+        let mut buffer = String::new();
+        self.write_to_string(&mut buffer, indent);
+        buffer
+    }
+
+    // indent が Some のときは，自身と子がすべてブロック要素の場合に限り改行して字下げする
+    // (文字列やインライン要素の前後に空白を入れると表示が変わるため)
+    fn write_to_string(&self, buffer: &mut String, indent: Option<usize>) {
         buffer.push('<');
         buffer.push_str(&self.tag);
         for (k, v) in &self.attr {
-            buffer.push_str(&format!(" {k}={v:?}"));
+            buffer.push_str(&format!(" {}=\"{}\"", k, escape_html_attribute(v)));
         }
         buffer.push('>');
 
         if let Some(children) = &self.children {
+            let child_indent = indent.filter(|_| self.has_only_block_children());
             for child in children {
                 match child {
-                    HtmlElementChild::String(string) => buffer.push_str(&string),
-                    HtmlElementChild::HtmlElement(el) => el.write_to_string(buffer),
+                    HtmlElementChild::String(string) => buffer.push_str(string),
+                    HtmlElementChild::HtmlElement(el) => {
+                        if let Some(child_indent) = child_indent {
+                            push_newline(buffer, child_indent + 1);
+                        }
+                        // 自身を字下げしない場合も，子の中は同じ深さで字下げしてよい
+                        el.write_to_string(buffer, child_indent.map(|i| i + 1).or(indent));
+                    }
                 }
             }
+            if let Some(child_indent) = child_indent {
+                push_newline(buffer, child_indent);
+            }
             buffer.push_str(&format!("</{}>", self.tag));
         }
     }
+
+    fn has_only_block_children(&self) -> bool {
+        match &self.children {
+            Some(children) => {
+                BLOCK_TAGS.contains(&self.tag.as_str())
+                    && !children.is_empty()
+                    && children.iter().all(|child| match child {
+                        HtmlElementChild::String(_) => false,
+                        HtmlElementChild::HtmlElement(el) => BLOCK_TAGS.contains(&el.tag.as_str()),
+                    })
+            }
+            None => false,
+        }
+    }
 }
 
 impl ToString for HtmlElement {
     fn to_string(&self) -> String {
-        let mut buffer = String::new();
-        self.write_to_string(&mut buffer);
-        buffer
+        self.to_string_with_indent(None)
     }
 }
 
+// 前後に改行を入れても表示が変わらない要素
+// div と p は目次や表の caption で display: inline にしているため含めない
+// pre のように中の空白が表示に影響しうる要素も含めない
+const BLOCK_TAGS: [&str; 37] = [
+    "head",
+    "body",
+    "meta",
+    "link",
+    "title",
+    "style",
+    "script",
+    "section",
+    "article",
+    "header",
+    "footer",
+    "nav",
+    "aside",
+    "main",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ul",
+    "ol",
+    "li",
+    "dl",
+    "dt",
+    "dd",
+    "table",
+    "caption",
+    "thead",
+    "tbody",
+    "tfoot",
+    "tr",
+    "th",
+    "td",
+    "figure",
+    "figcaption",
+    "blockquote",
+];
+
+fn push_newline(buffer: &mut String, indent: usize) {
+    buffer.push('\n');
+    buffer.push_str(&"  ".repeat(indent));
+}
+
 #[derive(Clone)]
 enum HtmlElementChild {
     String(String),
     HtmlElement(HtmlElement),
 }
 
-fn escape_html_text(str: &str) -> String {
+fn escape_html_text(str: &str, preserve_whitespace: bool) -> String {
     let mut buffer = String::new();
     for c in str.chars() {
         match c {
-            ' ' => buffer.push_str("&nbsp;"),
+            ' ' if preserve_whitespace => buffer.push_str("&nbsp;"),
             '<' => buffer.push_str("&lt;"),
             '>' => buffer.push_str("&gt;"),
             '&' => buffer.push_str("&amp;"),
             '"' => buffer.push_str("&quot;"),
-            '\n' => buffer.push_str("<br>"),
+            '\n' if preserve_whitespace => buffer.push_str("<br>"),
+            _ => buffer.push(c),
+        }
+    }
+    buffer
+}
+
+// 属性値は二重引用符で囲むため，& と " だけを文字参照にする
+fn escape_html_attribute(str: &str) -> String {
+    let mut buffer = String::new();
+    for c in str.chars() {
+        match c {
+            '&' => buffer.push_str("&amp;"),
+            '"' => buffer.push_str("&quot;"),
             _ => buffer.push(c),
         }
     }
@@ -177,8 +293,10 @@ pub fn print_html_to_pdf(html_path: &str) -> Result<Vec<u8>> {
 }
 
 pub struct HtmlString {
+    lang: String,
     head: String,
     body: String,
+    pretty: bool,
 }
 
 impl HtmlString {
@@ -192,11 +310,171 @@ impl HtmlString {
 
     pub fn merge(&self) -> String {
         let mut buffer = String::new();
+        let separator = if self.pretty { "\n" } else { "" };
         buffer.push_str("<!DOCTYPE html>");
-        buffer.push_str("<html lang=\"ja\">");
+        buffer.push_str(separator);
+        buffer.push_str(&format!(
+            "<html lang=\"{}\">",
+            escape_html_attribute(&self.lang)
+        ));
+        buffer.push_str(separator);
         buffer.push_str(&self.head.clone());
+        buffer.push_str(separator);
         buffer.push_str(&self.body.clone());
+        buffer.push_str(separator);
         buffer.push_str("</html>");
         buffer
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(tag: &str, class: &str, children: Vec<HtmlElement>) -> HtmlElement {
+        let mut html = HtmlElement::new(tag);
+        if !class.is_empty() {
+            html.set_attr("class", class);
+        }
+        for child in children {
+            html.append(child);
+        }
+        html
+    }
+
+    fn text(tag: &str, class: &str, text: &str) -> HtmlElement {
+        let mut html = element(tag, class, Vec::new());
+        html.append_text(text);
+        html
+    }
+
+    // 字下げのために入れた改行と空白を取り除く
+    fn strip_indentation(html: &str) -> String {
+        let mut result = String::new();
+        let mut chars = html.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\n' {
+                while chars.peek() == Some(&' ') {
+                    chars.next();
+                }
+            } else {
+                result.push(c);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn pretty_and_compact_are_equivalent() {
+        let grid = element(
+            "div",
+            "grid",
+            vec![
+                element("div", "item", vec![text("p", "", "A")]),
+                text("div", "item", "B"),
+            ],
+        );
+        let callout = element(
+            "div",
+            "callout",
+            vec![
+                text("div", "heading", "Note"),
+                element("div", "content", vec![text("p", "", "text")]),
+            ],
+        );
+        let toc_name = element(
+            "span",
+            "name",
+            vec![element("div", "content", vec![text("p", "", "Intro")])],
+        );
+        let toc = element(
+            "nav",
+            "toc",
+            vec![element(
+                "ol",
+                "",
+                vec![element(
+                    "li",
+                    "",
+                    vec![element(
+                        "a",
+                        "",
+                        vec![text("span", "number", "1"), toc_name.clone()],
+                    )],
+                )],
+            )],
+        );
+        let section = element(
+            "section",
+            "",
+            vec![
+                text("h2", "", "Section"),
+                element("ul", "", vec![text("li", "", "x"), text("li", "", "y")]),
+            ],
+        );
+        let caption = element(
+            "caption",
+            "",
+            vec![
+                text("div", "", "Table 1"),
+                element("div", "content", vec![text("p", "", "Caption")]),
+            ],
+        );
+        let table = element(
+            "table",
+            "table",
+            vec![
+                caption.clone(),
+                element(
+                    "tbody",
+                    "",
+                    vec![element(
+                        "tr",
+                        "",
+                        vec![text("td", "", "1"), text("td", "", "2")],
+                    )],
+                ),
+            ],
+        );
+        let pre = text("pre", "", "a  b");
+
+        let mut html = Html::new();
+        html.append_head(HtmlElement::new_void("meta"));
+        html.append_head(text("title", "", "Sample"));
+        for el in [&grid, &callout, &toc, &section, &table, &pre] {
+            html.append_body(el.clone());
+        }
+        let compact = html.to_string().merge();
+        let pretty = html.to_pretty_string().merge();
+
+        // 改行と字下げを入れる以外は変えない
+        assert!(!compact.contains('\n'));
+        assert_eq!(strip_indentation(&pretty), compact);
+
+        // 空白で表示が変わりうる要素の中は字下げしない
+        for el in [&grid, &callout, &toc_name, &caption, &pre] {
+            assert!(pretty.contains(&el.to_string()), "{}", el.to_string());
+        }
+
+        // ブロック要素だけを並べた箇所は字下げする
+        assert!(pretty.contains(
+            "<section>\n  <h2>Section</h2>\n  <ul>\n    <li>x</li>\n    <li>y</li>\n  </ul>\n</section>"
+        ));
+        assert!(pretty.contains(
+            "</caption>\n  <tbody>\n    <tr>\n      <td>1</td>\n      <td>2</td>\n    </tr>\n  </tbody>\n</table>"
+        ));
+        assert!(pretty.contains("<head>\n  <meta>\n  <title>Sample</title>\n</head>"));
+    }
+
+    #[test]
+    fn escape() {
+        let mut html = HtmlElement::new("a");
+        html.set_attr("title", "a \"b\" & <c>");
+        html.set_attr("title", "\"x\" & <y>");
+        html.append_text("<a> & \"b\"  c\nd");
+        assert_eq!(
+            html.to_string(),
+            "<a title=\"&quot;x&quot; &amp; <y>\">&lt;a&gt;&nbsp;&amp;&nbsp;&quot;b&quot;&nbsp;&nbsp;c<br>d</a>"
+        );
+    }
+}
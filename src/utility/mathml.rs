@@ -0,0 +1,1709 @@
+use anyhow::{bail, Context, Result};
+
+use super::tex_macro::TexMacro;
+
+// TeX の数式を MathML (MathML Core) に変換する
+pub fn tex_to_mathml(tex: &str, display: bool, macros: &[TexMacro]) -> Result<String> {
+    let mut parser = Parser {
+        tokens: tokenize(tex)?,
+        index: 0,
+        macros,
+        expansions: 0,
+        variant: None,
+    };
+    let rows = parser.parse_rows(None)?;
+
+    let mut content = String::new();
+    match rows.as_slice() {
+        [row] if row.cells.len() == 1 => {
+            let items = &row.cells[0];
+            match (items.as_slice(), &row.tag) {
+                // 全体が 1 つの環境 (align など) であれば，その行ごとに番号を振る
+                ([Node::Table(table)], None) if display => render_table(table, true, &mut content),
+                (_, Some(_)) if display => {
+                    let table = Table {
+                        rows: rows.clone(),
+                        columns: Columns::Gather,
+                        open: None,
+                        close: None,
+                    };
+                    render_table(&table, true, &mut content);
+                }
+                _ => render_row(items, &mut content),
+            }
+        }
+        _ => {
+            let table = Table {
+                rows,
+                columns: Columns::Gather,
+                open: None,
+                close: None,
+            };
+            render_table(&table, display, &mut content);
+        }
+    }
+
+    Ok(format!(
+        r#"<math display="{}">{}</math>"#,
+        if display { "block" } else { "inline" },
+        content
+    ))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Command(String),
+    Char(char),
+    Open,
+    Close,
+    Superscript,
+    Subscript,
+    Align,
+    Parameter(usize),
+    Space,
+}
+
+fn tokenize(tex: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = tex.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        match c {
+            '\\' => {
+                let mut name = String::new();
+                while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                    name.push(chars[i]);
+                    i += 1;
+                }
+                if name.is_empty() {
+                    match chars.get(i) {
+                        Some(&c) => {
+                            name.push(c);
+                            i += 1;
+                        }
+                        None => bail!("unexpected '\\' at end of math"),
+                    }
+                } else {
+                    // 英字からなるコマンドの後の空白は無視する
+                    while i < chars.len() && chars[i].is_whitespace() {
+                        i += 1;
+                    }
+                }
+                tokens.push(Token::Command(name));
+            }
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            '^' => tokens.push(Token::Superscript),
+            '_' => tokens.push(Token::Subscript),
+            '&' => tokens.push(Token::Align),
+            '#' => match chars.get(i).and_then(|c| c.to_digit(10)) {
+                Some(n) if 0 < n => {
+                    tokens.push(Token::Parameter(n as usize));
+                    i += 1;
+                }
+                _ => bail!("unexpected '#'"),
+            },
+            '%' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            c if c.is_whitespace() => {
+                if tokens.last() != Some(&Token::Space) {
+                    tokens.push(Token::Space);
+                }
+            }
+            c => tokens.push(Token::Char(c)),
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Variant {
+    Normal,
+    Bold,
+    Italic,
+    BoldItalic,
+    DoubleStruck,
+    Script,
+    Fraktur,
+    SansSerif,
+    Monospace,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Row(Vec<Node>),
+    Identifier(String, Option<Variant>),
+    Number(String),
+    Operator(Operator),
+    Text(String),
+    Space(String),
+    // sin や lim など (limits が true なら上下に添字を置く)
+    Function(String, bool),
+    Fraction(Box<Node>, Box<Node>, FractionStyle),
+    Sqrt(Box<Node>, Option<Box<Node>>),
+    Scripts {
+        base: Box<Node>,
+        subscript: Option<Box<Node>>,
+        superscript: Option<Box<Node>>,
+        limits: Option<bool>,
+    },
+    Over(Box<Node>, Box<Node>, bool),
+    Under(Box<Node>, Box<Node>, bool),
+    Fenced(Option<String>, Vec<Node>, Option<String>),
+    Styled(String, Vec<Node>),
+    DisplayStyle(bool, Vec<Node>),
+    Phantom(Box<Node>),
+    Table(Table),
+    // 行番号 (行の処理時に取り除かれる)
+    Tag(String),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum FractionStyle {
+    Auto,
+    Display,
+    Text,
+    // \binom
+    NoLine,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Operator {
+    text: String,
+    stretchy: Option<bool>,
+    large: bool,
+    size: Option<&'static str>,
+}
+
+impl Operator {
+    fn new(text: &str) -> Operator {
+        Operator {
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn fence(text: &str) -> Operator {
+        Operator {
+            text: text.to_string(),
+            stretchy: Some(false),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Table {
+    rows: Vec<TableRow>,
+    columns: Columns,
+    open: Option<&'static str>,
+    close: Option<&'static str>,
+}
+
+#[derive(Clone, Debug)]
+struct TableRow {
+    cells: Vec<Vec<Node>>,
+    tag: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+enum Columns {
+    // matrix
+    Center,
+    // align (右揃えと左揃えの繰り返し)
+    Aligned,
+    // gather
+    Gather,
+    Cases,
+    // array の列指定 (l, c, r)
+    Array(Vec<char>),
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    index: usize,
+    macros: &'a [TexMacro],
+    expansions: usize,
+    // \mathbb などの内側で用いる字形
+    variant: Option<Variant>,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_spaces(&mut self) {
+        while self.tokens.get(self.index) == Some(&Token::Space) {
+            self.index += 1;
+        }
+    }
+
+    fn expand_macro(&mut self) -> Result<()> {
+        loop {
+            let name = match self.tokens.get(self.index) {
+                Some(Token::Command(name)) => name.clone(),
+                _ => return Ok(()),
+            };
+            let tex_macro = match self.macros.iter().find(|m| m.name == name) {
+                Some(tex_macro) => tex_macro,
+                None => return Ok(()),
+            };
+            self.expansions += 1;
+            if 10000 < self.expansions {
+                bail!("too many macro expansions: '\\{}'", name);
+            }
+
+            let mut index = self.index + 1;
+            let mut arguments = Vec::new();
+            for _ in 0..tex_macro.parameters {
+                while self.tokens.get(index) == Some(&Token::Space) {
+                    index += 1;
+                }
+                match self.tokens.get(index) {
+                    Some(Token::Open) => {
+                        let close = find_close(&self.tokens, index)
+                            .with_context(|| format!("unbalanced braces in '\\{}'", name))?;
+                        arguments.push(self.tokens[index + 1..close].to_vec());
+                        index = close + 1;
+                    }
+                    Some(Token::Close) | None => bail!("missing argument for '\\{}'", name),
+                    Some(token) => {
+                        arguments.push(vec![token.clone()]);
+                        index += 1;
+                    }
+                }
+            }
+
+            let mut expanded = Vec::new();
+            for token in tokenize(&tex_macro.body)? {
+                match token {
+                    Token::Parameter(n) => expanded.extend(arguments[n - 1].iter().cloned()),
+                    token => expanded.push(token),
+                }
+            }
+            self.tokens.splice(self.index..index, expanded);
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<Token>> {
+        self.skip_spaces();
+        self.expand_macro()?;
+        self.skip_spaces();
+        Ok(self.tokens.get(self.index).cloned())
+    }
+
+    fn next(&mut self) -> Result<Option<Token>> {
+        let token = self.peek()?;
+        if token.is_some() {
+            self.index += 1;
+        }
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token, message: &str) -> Result<()> {
+        match self.next()? {
+            Some(token) if token == expected => Ok(()),
+            _ => bail!("{}", message),
+        }
+    }
+
+    // 環境または数式全体を \\ と & で区切って解析する
+    fn parse_rows(&mut self, environment: Option<&str>) -> Result<Vec<TableRow>> {
+        let mut rows = Vec::new();
+        let mut cells = Vec::new();
+        loop {
+            let mut cell = self.parse_row(None)?;
+            match self.next()? {
+                Some(Token::Align) => {
+                    cells.push(cell);
+                }
+                Some(Token::Command(name)) if name == "\\" || name == "cr" => {
+                    // \\[2pt] の間隔指定は無視する
+                    if self.peek()? == Some(Token::Char('[')) {
+                        while !matches!(self.next()?, Some(Token::Char(']')) | None) {}
+                    }
+                    cells.push(cell);
+                    rows.push(take_row_tag(std::mem::take(&mut cells))?);
+                }
+                Some(Token::Command(name)) if name == "end" => {
+                    let name = self.read_text_group()?;
+                    match environment {
+                        Some(environment) if environment == name => {}
+                        _ => bail!("unexpected '\\end{{{}}}'", name),
+                    }
+                    cells.push(std::mem::take(&mut cell));
+                    break;
+                }
+                None => {
+                    if let Some(environment) = environment {
+                        bail!("missing '\\end{{{}}}'", environment);
+                    }
+                    cells.push(cell);
+                    break;
+                }
+                Some(Token::Close) => bail!("unbalanced braces"),
+                Some(Token::Command(name)) if name == "right" || name == "middle" => {
+                    bail!("missing '\\left'")
+                }
+                Some(Token::Command(name)) => bail!("unexpected '\\{}'", name),
+                Some(token) => bail!("unexpected token: {:?}", token),
+            }
+        }
+        let row = take_row_tag(cells)?;
+        // 末尾の \\ による空行は除く
+        if !(row.cells.len() == 1 && row.cells[0].is_empty() && row.tag.is_none())
+            || rows.is_empty()
+        {
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+
+    // 終端 (}, &, \\, \end, \right など) の直前まで解析する
+    fn parse_row(&mut self, terminator: Option<char>) -> Result<Vec<Node>> {
+        let mut items = Vec::new();
+        loop {
+            match self.peek()? {
+                None | Some(Token::Close) | Some(Token::Align) => break,
+                Some(Token::Char(c)) if Some(c) == terminator => break,
+                Some(Token::Command(name)) => match name.as_str() {
+                    "\\" | "cr" | "end" | "right" | "middle" => break,
+                    "displaystyle" | "textstyle" | "scriptstyle" | "scriptscriptstyle" => {
+                        self.index += 1;
+                        let rest = self.parse_row(terminator)?;
+                        items.push(Node::DisplayStyle(name == "displaystyle", rest));
+                        break;
+                    }
+                    "color" => {
+                        self.index += 1;
+                        let color = self.read_color()?;
+                        let rest = self.parse_row(terminator)?;
+                        items.push(Node::Styled(format!("color: {}", color), rest));
+                        break;
+                    }
+                    "over" | "choose" => {
+                        self.index += 1;
+                        let numerator = Node::Row(std::mem::take(&mut items));
+                        let denominator = Node::Row(self.parse_row(terminator)?);
+                        let fraction = if name == "over" {
+                            Node::Fraction(
+                                Box::new(numerator),
+                                Box::new(denominator),
+                                FractionStyle::Auto,
+                            )
+                        } else {
+                            Node::Fenced(
+                                Some("(".to_string()),
+                                vec![Node::Fraction(
+                                    Box::new(numerator),
+                                    Box::new(denominator),
+                                    FractionStyle::NoLine,
+                                )],
+                                Some(")".to_string()),
+                            )
+                        };
+                        items.push(fraction);
+                        break;
+                    }
+                    _ => items.push(self.parse_scripted()?),
+                },
+                Some(_) => items.push(self.parse_scripted()?),
+            }
+        }
+        Ok(items)
+    }
+
+    fn parse_group(&mut self) -> Result<Node> {
+        self.expect(Token::Open, "expected '{'")?;
+        let items = self.parse_row(None)?;
+        self.expect(Token::Close, "unbalanced braces")?;
+        Ok(Node::Row(items))
+    }
+
+    fn parse_argument(&mut self) -> Result<Node> {
+        match self.peek()? {
+            Some(Token::Open) => self.parse_group(),
+            None | Some(Token::Close) | Some(Token::Align) => bail!("missing argument"),
+            Some(_) => self.parse_atom(),
+        }
+    }
+
+    fn parse_argument_with_variant(&mut self, variant: Variant) -> Result<Node> {
+        let previous = self.variant.replace(variant);
+        let argument = self.parse_argument();
+        self.variant = previous;
+        argument
+    }
+
+    fn parse_optional_argument(&mut self) -> Result<Option<Node>> {
+        if self.peek()? != Some(Token::Char('[')) {
+            return Ok(None);
+        }
+        self.index += 1;
+        let items = self.parse_row(Some(']'))?;
+        self.expect(Token::Char(']'), "expected ']'")?;
+        Ok(Some(Node::Row(items)))
+    }
+
+    // 中身を解釈せずに文字列として読む (\text, \begin など)
+    fn read_text_group(&mut self) -> Result<String> {
+        self.skip_spaces();
+        if self.tokens.get(self.index) != Some(&Token::Open) {
+            bail!("expected '{{'");
+        }
+        let close = find_close(&self.tokens, self.index).context("unbalanced braces")?;
+        let mut text = String::new();
+        for token in &self.tokens[self.index + 1..close] {
+            match token {
+                Token::Char(c) => text.push(*c),
+                Token::Space => text.push(' '),
+                Token::Open | Token::Close => {}
+                Token::Command(name)
+                    if ["%", "&", "_", "{", "}", "#", "$", " "].contains(&name.as_str()) =>
+                {
+                    text.push_str(name)
+                }
+                // 負の空白は無視する
+                Token::Command(name) if name == "!" => {}
+                Token::Command(name) if get_space(name).is_some() => text.push('\u{2009}'),
+                Token::Command(name) => bail!("unsupported command in text: '\\{}'", name),
+                Token::Superscript => text.push('^'),
+                Token::Subscript => text.push('_'),
+                Token::Align => text.push('&'),
+                Token::Parameter(n) => bail!("unexpected '#{}'", n),
+            }
+        }
+        self.index = close + 1;
+        Ok(text)
+    }
+
+    fn read_color(&mut self) -> Result<String> {
+        let color = self.read_text_group()?;
+        let color = color.trim().to_string();
+        if color.is_empty() || !color.chars().all(|c| c.is_ascii_alphanumeric() || c == '#') {
+            bail!("invalid color: '{}'", color);
+        }
+        Ok(color)
+    }
+
+    fn parse_scripted(&mut self) -> Result<Node> {
+        let base = self.parse_atom()?;
+        let mut subscript = None;
+        let mut superscript: Option<Node> = None;
+        let mut limits = None;
+        let mut primes = 0;
+        loop {
+            match self.peek()? {
+                Some(Token::Superscript) => {
+                    self.index += 1;
+                    if superscript.is_some() {
+                        bail!("double superscript");
+                    }
+                    superscript = Some(self.parse_argument()?);
+                }
+                Some(Token::Subscript) => {
+                    self.index += 1;
+                    if subscript.is_some() {
+                        bail!("double subscript");
+                    }
+                    subscript = Some(Box::new(self.parse_argument()?));
+                }
+                Some(Token::Char('\'')) => {
+                    self.index += 1;
+                    primes += 1;
+                }
+                Some(Token::Command(name)) if name == "limits" || name == "nolimits" => {
+                    self.index += 1;
+                    limits = Some(name == "limits");
+                }
+                _ => break,
+            }
+        }
+        if 0 < primes {
+            let prime = Node::Operator(Operator::new(&"′".repeat(primes)));
+            superscript = Some(match superscript {
+                Some(superscript) => Node::Row(vec![prime, superscript]),
+                None => prime,
+            });
+        }
+        if subscript.is_none() && superscript.is_none() && limits.is_none() {
+            return Ok(base);
+        }
+        Ok(Node::Scripts {
+            base: Box::new(base),
+            subscript,
+            superscript: superscript.map(Box::new),
+            limits,
+        })
+    }
+
+    fn parse_atom(&mut self) -> Result<Node> {
+        let token = match self.peek()? {
+            Some(token) => token,
+            None => bail!("unexpected end of math"),
+        };
+        match token {
+            Token::Open => self.parse_group(),
+            // ^2 のように底がない場合
+            Token::Superscript | Token::Subscript => Ok(Node::Row(Vec::new())),
+            Token::Char(c) => {
+                self.index += 1;
+                self.parse_char(c)
+            }
+            Token::Command(name) => {
+                self.index += 1;
+                self.parse_command(&name)
+            }
+            Token::Close => bail!("unbalanced braces"),
+            Token::Align => bail!("unexpected '&'"),
+            Token::Parameter(n) => bail!("unexpected '#{}'", n),
+            Token::Space => unreachable!(),
+        }
+    }
+
+    fn parse_char(&mut self, c: char) -> Result<Node> {
+        if c.is_ascii_digit() || (c == '.' && self.next_is_digit()) {
+            let mut number = c.to_string();
+            while let Some(Token::Char(c)) = self.tokens.get(self.index) {
+                if c.is_ascii_digit() || (*c == '.' && self.peek_digit_at(self.index + 1)) {
+                    number.push(*c);
+                    self.index += 1;
+                } else {
+                    break;
+                }
+            }
+            return Ok(match self.variant {
+                Some(variant) => {
+                    Node::Number(number.chars().map(|c| apply_variant(c, variant)).collect())
+                }
+                None => Node::Number(number),
+            });
+        }
+        if c.is_ascii_alphabetic() {
+            return Ok(match self.variant {
+                Some(Variant::Normal) => Node::Identifier(c.to_string(), Some(Variant::Normal)),
+                Some(variant) => {
+                    Node::Identifier(apply_variant(c, variant).to_string(), Some(variant))
+                }
+                None => Node::Identifier(c.to_string(), None),
+            });
+        }
+        // 日本語などは文字列として扱う
+        if '\u{3000}' <= c {
+            let mut text = c.to_string();
+            while let Some(Token::Char(c)) = self.tokens.get(self.index) {
+                if *c < '\u{3000}' {
+                    break;
+                }
+                text.push(*c);
+                self.index += 1;
+            }
+            return Ok(Node::Text(text));
+        }
+        if c.is_alphabetic() {
+            return Ok(Node::Identifier(c.to_string(), self.variant));
+        }
+        Ok(match c {
+            '~' => Node::Space("0.3333em".to_string()),
+            '-' => Node::Operator(Operator::new("−")),
+            '*' => Node::Operator(Operator::new("∗")),
+            '(' | ')' | '[' | ']' | '|' | '/' => Node::Operator(Operator::fence(&c.to_string())),
+            c => Node::Operator(Operator::new(&c.to_string())),
+        })
+    }
+
+    fn next_is_digit(&self) -> bool {
+        self.peek_digit_at(self.index)
+    }
+
+    fn peek_digit_at(&self, index: usize) -> bool {
+        matches!(self.tokens.get(index), Some(Token::Char(c)) if c.is_ascii_digit())
+    }
+
+    fn parse_command(&mut self, name: &str) -> Result<Node> {
+        if let Some(identifier) = get_greek_letter(name) {
+            // 大文字のギリシャ文字は立体
+            return Ok(if identifier.chars().all(|c| c.is_uppercase()) {
+                Node::Identifier(identifier.to_string(), Some(Variant::Normal))
+            } else {
+                Node::Identifier(identifier.to_string(), None)
+            });
+        }
+        if let Some(identifier) = get_symbol_identifier(name) {
+            return Ok(Node::Identifier(
+                identifier.to_string(),
+                Some(Variant::Normal),
+            ));
+        }
+        if let Some(operator) = get_symbol_operator(name) {
+            return Ok(Node::Operator(operator));
+        }
+        if let Some(operator) = get_large_operator(name) {
+            return Ok(Node::Operator(Operator {
+                text: operator.to_string(),
+                large: true,
+                ..Default::default()
+            }));
+        }
+        if let Some((function, limits)) = get_function(name) {
+            return Ok(Node::Function(function.to_string(), limits));
+        }
+        if let Some(width) = get_space(name) {
+            return Ok(Node::Space(width.to_string()));
+        }
+        if let Some(size) = get_delimiter_size(name) {
+            let delimiter = self.read_delimiter()?.unwrap_or_default();
+            return Ok(Node::Operator(Operator {
+                text: delimiter,
+                stretchy: Some(true),
+                large: false,
+                size: Some(size),
+            }));
+        }
+        if let Some(variant) = get_variant(name) {
+            return self.parse_argument_with_variant(variant);
+        }
+        if let Some((accent, stretchy)) = get_accent(name) {
+            let base = self.parse_argument()?;
+            return Ok(Node::Over(
+                Box::new(base),
+                Box::new(Node::Operator(Operator {
+                    text: accent.to_string(),
+                    stretchy: Some(stretchy),
+                    ..Default::default()
+                })),
+                true,
+            ));
+        }
+
+        match name {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let numerator = self.parse_argument()?;
+                let denominator = self.parse_argument()?;
+                let style = match name {
+                    "dfrac" | "cfrac" => FractionStyle::Display,
+                    "tfrac" => FractionStyle::Text,
+                    _ => FractionStyle::Auto,
+                };
+                Ok(Node::Fraction(
+                    Box::new(numerator),
+                    Box::new(denominator),
+                    style,
+                ))
+            }
+            "binom" | "dbinom" | "tbinom" => {
+                let top = self.parse_argument()?;
+                let bottom = self.parse_argument()?;
+                let fraction =
+                    Node::Fraction(Box::new(top), Box::new(bottom), FractionStyle::NoLine);
+                let fraction = match name {
+                    "dbinom" => Node::DisplayStyle(true, vec![fraction]),
+                    "tbinom" => Node::DisplayStyle(false, vec![fraction]),
+                    _ => fraction,
+                };
+                Ok(Node::Fenced(
+                    Some("(".to_string()),
+                    vec![fraction],
+                    Some(")".to_string()),
+                ))
+            }
+            "sqrt" => {
+                let index = self.parse_optional_argument()?;
+                let radicand = self.parse_argument()?;
+                Ok(Node::Sqrt(Box::new(radicand), index.map(Box::new)))
+            }
+            "left" => {
+                let open = self.read_delimiter()?;
+                let mut items = self.parse_row(None)?;
+                loop {
+                    match self.next()? {
+                        Some(Token::Command(name)) if name == "middle" => {
+                            let middle = self.read_delimiter()?.unwrap_or_default();
+                            items.push(Node::Operator(Operator {
+                                text: middle,
+                                stretchy: Some(true),
+                                ..Default::default()
+                            }));
+                            items.extend(self.parse_row(None)?);
+                        }
+                        Some(Token::Command(name)) if name == "right" => break,
+                        _ => bail!("missing '\\right'"),
+                    }
+                }
+                let close = self.read_delimiter()?;
+                Ok(Node::Fenced(open, items, close))
+            }
+            "right" | "middle" => bail!("missing '\\left'"),
+            "operatorname" => {
+                self.skip_spaces();
+                let limits = self.tokens.get(self.index) == Some(&Token::Char('*'));
+                if limits {
+                    self.index += 1;
+                }
+                Ok(Node::Function(self.read_text_group()?, limits))
+            }
+            "text" | "textrm" | "textnormal" | "mbox" | "hbox" => {
+                Ok(Node::Text(self.read_text_group()?))
+            }
+            "textbf" => Ok(Node::Styled(
+                "font-weight: bold".to_string(),
+                vec![Node::Text(self.read_text_group()?)],
+            )),
+            "textit" => Ok(Node::Styled(
+                "font-style: italic".to_string(),
+                vec![Node::Text(self.read_text_group()?)],
+            )),
+            "overline" => {
+                let base = self.parse_argument()?;
+                Ok(Node::Over(
+                    Box::new(base),
+                    Box::new(Node::Operator(Operator {
+                        text: "‾".to_string(),
+                        stretchy: Some(true),
+                        ..Default::default()
+                    })),
+                    true,
+                ))
+            }
+            "underline" => {
+                let base = self.parse_argument()?;
+                Ok(Node::Under(
+                    Box::new(base),
+                    Box::new(Node::Operator(Operator {
+                        text: "_".to_string(),
+                        stretchy: Some(true),
+                        ..Default::default()
+                    })),
+                    true,
+                ))
+            }
+            "overbrace" | "underbrace" => {
+                let base = self.parse_argument()?;
+                let brace = Box::new(Node::Operator(Operator {
+                    text: if name == "overbrace" { "⏞" } else { "⏟" }.to_string(),
+                    stretchy: Some(true),
+                    ..Default::default()
+                }));
+                Ok(if name == "overbrace" {
+                    Node::Over(Box::new(base), brace, false)
+                } else {
+                    Node::Under(Box::new(base), brace, false)
+                })
+            }
+            "overset" | "stackrel" => {
+                let over = self.parse_argument()?;
+                let base = self.parse_argument()?;
+                Ok(Node::Over(Box::new(base), Box::new(over), false))
+            }
+            "underset" => {
+                let under = self.parse_argument()?;
+                let base = self.parse_argument()?;
+                Ok(Node::Under(Box::new(base), Box::new(under), false))
+            }
+            "not" => match self.parse_atom()? {
+                Node::Operator(operator) => Ok(Node::Operator(Operator {
+                    text: format!("{}\u{338}", operator.text),
+                    ..operator
+                })),
+                _ => bail!("'\\not' must be followed by an operator"),
+            },
+            "textcolor" => {
+                let color = self.read_color()?;
+                let body = self.parse_argument()?;
+                Ok(Node::Styled(format!("color: {}", color), vec![body]))
+            }
+            "boxed" => {
+                let body = self.parse_argument()?;
+                Ok(Node::Styled(
+                    "border: 1px solid; padding: 0.2em".to_string(),
+                    vec![body],
+                ))
+            }
+            "phantom" => Ok(Node::Phantom(Box::new(self.parse_argument()?))),
+            "mod" | "bmod" => Ok(Node::Row(vec![
+                Node::Space("0.2222em".to_string()),
+                Node::Function("mod".to_string(), false),
+            ])),
+            "pmod" => {
+                let modulus = self.parse_argument()?;
+                Ok(Node::Row(vec![
+                    Node::Space("0.4444em".to_string()),
+                    Node::Operator(Operator::fence("(")),
+                    Node::Function("mod".to_string(), false),
+                    modulus,
+                    Node::Operator(Operator::fence(")")),
+                ]))
+            }
+            "tag" => {
+                self.skip_spaces();
+                let starred = self.tokens.get(self.index) == Some(&Token::Char('*'));
+                if starred {
+                    self.index += 1;
+                }
+                let tag = self.read_text_group()?;
+                Ok(Node::Tag(if starred { tag } else { format!("({})", tag) }))
+            }
+            "notag" | "nonumber" => Ok(Node::Row(Vec::new())),
+            "label" => {
+                self.read_text_group()?;
+                Ok(Node::Row(Vec::new()))
+            }
+            "begin" => {
+                let environment = self.read_text_group()?;
+                self.parse_environment(&environment)
+                    .with_context(|| format!("in environment '{}'", environment))
+            }
+            _ => bail!("unknown command: '\\{}'", name),
+        }
+    }
+
+    fn read_delimiter(&mut self) -> Result<Option<String>> {
+        let delimiter = match self.next()? {
+            Some(Token::Char('.')) => return Ok(None),
+            Some(Token::Char('<')) => "⟨",
+            Some(Token::Char('>')) => "⟩",
+            Some(Token::Char(c)) if "()[]|/".contains(c) => return Ok(Some(c.to_string())),
+            Some(Token::Command(name)) => match name.as_str() {
+                "{" | "lbrace" => "{",
+                "}" | "rbrace" => "}",
+                "langle" => "⟨",
+                "rangle" => "⟩",
+                "lfloor" => "⌊",
+                "rfloor" => "⌋",
+                "lceil" => "⌈",
+                "rceil" => "⌉",
+                "|" | "Vert" | "lVert" | "rVert" => "‖",
+                "vert" | "lvert" | "rvert" => "|",
+                "uparrow" => "↑",
+                "downarrow" => "↓",
+                "updownarrow" => "↕",
+                "backslash" => "∖",
+                _ => bail!("invalid delimiter: '\\{}'", name),
+            },
+            _ => bail!("missing delimiter"),
+        };
+        Ok(Some(delimiter.to_string()))
+    }
+
+    fn parse_environment(&mut self, environment: &str) -> Result<Node> {
+        let (columns, open, close) = match environment {
+            "matrix" | "smallmatrix" => (Columns::Center, None, None),
+            "pmatrix" => (Columns::Center, Some("("), Some(")")),
+            "bmatrix" => (Columns::Center, Some("["), Some("]")),
+            "Bmatrix" => (Columns::Center, Some("{"), Some("}")),
+            "vmatrix" => (Columns::Center, Some("|"), Some("|")),
+            "Vmatrix" => (Columns::Center, Some("‖"), Some("‖")),
+            "cases" | "dcases" => (Columns::Cases, Some("{"), None),
+            "rcases" => (Columns::Cases, None, Some("}")),
+            "array" => {
+                let spec: Vec<char> = self
+                    .read_text_group()?
+                    .chars()
+                    .filter(|c| !c.is_whitespace() && *c != '|')
+                    .collect();
+                if let Some(c) = spec.iter().find(|c| !"lcr".contains(**c)) {
+                    bail!("unsupported column specifier: '{}'", c);
+                }
+                (Columns::Array(spec), None, None)
+            }
+            "align" | "align*" | "aligned" | "split" | "flalign" | "flalign*" | "eqnarray"
+            | "eqnarray*" => (Columns::Aligned, None, None),
+            "alignat" | "alignat*" | "alignedat" => {
+                // 列数の指定は不要
+                self.read_text_group()?;
+                (Columns::Aligned, None, None)
+            }
+            "gather" | "gather*" | "gathered" | "multline" | "multline*" | "equation"
+            | "equation*" => (Columns::Gather, None, None),
+            _ => bail!("unknown environment"),
+        };
+        let rows = self.parse_rows(Some(environment))?;
+        Ok(Node::Table(Table {
+            rows,
+            columns,
+            open,
+            close,
+        }))
+    }
+}
+
+fn find_close(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        match token {
+            Token::Open => depth += 1,
+            Token::Close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// 行内の \tag を取り出す
+fn take_row_tag(cells: Vec<Vec<Node>>) -> Result<TableRow> {
+    let mut tag = None;
+    let mut result = Vec::new();
+    for cell in cells {
+        let mut items = Vec::new();
+        for node in cell {
+            match node {
+                Node::Tag(text) => {
+                    if tag.is_some() {
+                        bail!("multiple '\\tag' in one line");
+                    }
+                    tag = Some(text);
+                }
+                node => items.push(node),
+            }
+        }
+        result.push(items);
+    }
+    Ok(TableRow { cells: result, tag })
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_row(items: &[Node], out: &mut String) {
+    for (i, item) in items.iter().enumerate() {
+        render(item, out);
+        // 関数名と引数の間の空白
+        if is_function(item) {
+            match items.get(i + 1) {
+                Some(Node::Operator(operator)) if operator.stretchy.is_some() => {}
+                Some(Node::Fenced(..)) | None => {}
+                Some(_) => out.push_str(r#"<mspace width="0.1667em"/>"#),
+            }
+        }
+    }
+}
+
+fn is_function(node: &Node) -> bool {
+    match node {
+        Node::Function(..) => true,
+        Node::Scripts { base, .. } => is_function(base),
+        _ => false,
+    }
+}
+
+fn render_mrow(items: &[Node], out: &mut String) {
+    out.push_str("<mrow>");
+    render_row(items, out);
+    out.push_str("</mrow>");
+}
+
+fn render(node: &Node, out: &mut String) {
+    match node {
+        Node::Row(items) => render_mrow(items, out),
+        Node::Identifier(text, variant) => {
+            if *variant == Some(Variant::Normal) && text.chars().count() == 1 {
+                out.push_str(&format!(
+                    r#"<mi mathvariant="normal">{}</mi>"#,
+                    escape(text)
+                ));
+            } else {
+                out.push_str(&format!("<mi>{}</mi>", escape(text)));
+            }
+        }
+        Node::Number(text) => out.push_str(&format!("<mn>{}</mn>", escape(text))),
+        Node::Operator(operator) => render_operator(operator, false, out),
+        Node::Text(text) => {
+            // mtext の前後の空白は詰められるため，改行しない空白にする
+            let text = text.replace(' ', "\u{a0}");
+            out.push_str(&format!("<mtext>{}</mtext>", escape(&text)));
+        }
+        Node::Space(width) => out.push_str(&format!(r#"<mspace width="{}"/>"#, width)),
+        Node::Function(name, limits) => {
+            if *limits {
+                out.push_str(&format!(
+                    r#"<mo movablelimits="true" form="prefix" lspace="0" rspace="0">{}</mo>"#,
+                    escape(name)
+                ));
+            } else {
+                out.push_str(&format!("<mi>{}</mi>", escape(name)));
+            }
+        }
+        Node::Fraction(numerator, denominator, style) => {
+            let attributes = match style {
+                FractionStyle::Auto => "",
+                FractionStyle::Display => r#" displaystyle="true""#,
+                FractionStyle::Text => r#" displaystyle="false""#,
+                FractionStyle::NoLine => r#" linethickness="0""#,
+            };
+            out.push_str(&format!("<mfrac{}>", attributes));
+            render_as_single(numerator, out);
+            render_as_single(denominator, out);
+            out.push_str("</mfrac>");
+        }
+        Node::Sqrt(radicand, index) => match index {
+            Some(index) => {
+                out.push_str("<mroot>");
+                render_as_single(radicand, out);
+                render_as_single(index, out);
+                out.push_str("</mroot>");
+            }
+            None => {
+                out.push_str("<msqrt>");
+                render(radicand, out);
+                out.push_str("</msqrt>");
+            }
+        },
+        Node::Scripts {
+            base,
+            subscript,
+            superscript,
+            limits,
+        } => {
+            let over_under = match limits {
+                Some(limits) => *limits,
+                None => prefers_limits(base),
+            };
+            let tag = match (over_under, subscript.is_some(), superscript.is_some()) {
+                (true, true, true) => "munderover",
+                (true, true, false) => "munder",
+                (true, false, true) => "mover",
+                (false, true, true) => "msubsup",
+                (false, true, false) => "msub",
+                (false, false, true) => "msup",
+                (_, false, false) => {
+                    render(base, out);
+                    return;
+                }
+            };
+            out.push_str(&format!("<{}>", tag));
+            match base.as_ref() {
+                // \limits の指定があれば常に上下に置く
+                Node::Operator(operator) if over_under => {
+                    render_operator(operator, limits.is_none(), out)
+                }
+                base => render_as_single(base, out),
+            }
+            if let Some(subscript) = subscript {
+                render_as_single(subscript, out);
+            }
+            if let Some(superscript) = superscript {
+                render_as_single(superscript, out);
+            }
+            out.push_str(&format!("</{}>", tag));
+        }
+        Node::Over(base, over, accent) => {
+            out.push_str(if *accent {
+                r#"<mover accent="true">"#
+            } else {
+                "<mover>"
+            });
+            render_as_single(base, out);
+            render_as_single(over, out);
+            out.push_str("</mover>");
+        }
+        Node::Under(base, under, accent) => {
+            out.push_str(if *accent {
+                r#"<munder accentunder="true">"#
+            } else {
+                "<munder>"
+            });
+            render_as_single(base, out);
+            render_as_single(under, out);
+            out.push_str("</munder>");
+        }
+        Node::Fenced(open, items, close) => {
+            out.push_str("<mrow>");
+            if let Some(open) = open {
+                out.push_str(&format!(
+                    r#"<mo fence="true" stretchy="true" form="prefix">{}</mo>"#,
+                    escape(open)
+                ));
+            }
+            render_mrow(items, out);
+            if let Some(close) = close {
+                out.push_str(&format!(
+                    r#"<mo fence="true" stretchy="true" form="postfix">{}</mo>"#,
+                    escape(close)
+                ));
+            }
+            out.push_str("</mrow>");
+        }
+        Node::Styled(style, items) => {
+            out.push_str(&format!(r#"<mrow style="{}">"#, escape(style)));
+            render_row(items, out);
+            out.push_str("</mrow>");
+        }
+        Node::DisplayStyle(display, items) => {
+            out.push_str(&format!(r#"<mrow displaystyle="{}">"#, display));
+            render_row(items, out);
+            out.push_str("</mrow>");
+        }
+        Node::Phantom(body) => {
+            out.push_str("<mphantom>");
+            render(body, out);
+            out.push_str("</mphantom>");
+        }
+        Node::Table(table) => render_table(table, false, out),
+        Node::Tag(_) => {}
+    }
+}
+
+// 添字などの子要素は 1 つの要素にまとめる
+fn render_as_single(node: &Node, out: &mut String) {
+    match node {
+        Node::Row(items) if items.len() != 1 => render_mrow(items, out),
+        Node::Row(items) => render_as_single(&items[0], out),
+        node => render(node, out),
+    }
+}
+
+fn render_operator(operator: &Operator, movable_limits: bool, out: &mut String) {
+    let mut attributes = String::new();
+    if let Some(stretchy) = operator.stretchy {
+        attributes.push_str(&format!(r#" stretchy="{}""#, stretchy));
+    }
+    if operator.large {
+        attributes.push_str(r#" largeop="true""#);
+        if movable_limits {
+            attributes.push_str(r#" movablelimits="true""#);
+        }
+    }
+    if let Some(size) = operator.size {
+        attributes.push_str(&format!(
+            r#" symmetric="true" minsize="{size}" maxsize="{size}""#,
+            size = size
+        ));
+    }
+    out.push_str(&format!(
+        "<mo{}>{}</mo>",
+        attributes,
+        escape(&operator.text)
+    ));
+}
+
+fn prefers_limits(node: &Node) -> bool {
+    match node {
+        Node::Operator(operator) => operator.large && !operator.text.contains(['∫', '∬', '∭', '∮']),
+        Node::Function(_, limits) => *limits,
+        Node::Over(_, _, accent) | Node::Under(_, _, accent) => !accent,
+        _ => false,
+    }
+}
+
+// numbered であれば，行番号の列を右端に置く
+fn render_table(table: &Table, numbered: bool, out: &mut String) {
+    let numbered = numbered && table.rows.iter().any(|row| row.tag.is_some());
+    if table.open.is_some() || table.close.is_some() {
+        out.push_str("<mrow>");
+        if let Some(open) = table.open {
+            out.push_str(&format!(r#"<mo fence="true" form="prefix">{}</mo>"#, open));
+        }
+    }
+
+    out.push_str(if numbered {
+        r#"<mtable style="width: 100%">"#
+    } else {
+        "<mtable>"
+    });
+    for row in &table.rows {
+        out.push_str("<mtr>");
+        if numbered {
+            out.push_str(r#"<mtd style="width: 50%"></mtd>"#);
+        }
+        for (i, cell) in row.cells.iter().enumerate() {
+            let (style, displaystyle) = match &table.columns {
+                Columns::Center => ("padding: 0.2em 0.5em", false),
+                Columns::Gather => ("padding: 0.25em 0", true),
+                Columns::Cases => ("text-align: left; padding: 0.1em 1em 0.1em 0", false),
+                Columns::Aligned if i % 2 == 0 => ("text-align: right; padding: 0.25em 0", true),
+                Columns::Aligned => ("text-align: left; padding: 0.25em 1em 0.25em 0", true),
+                Columns::Array(spec) => match spec.get(i) {
+                    Some('l') => ("text-align: left; padding: 0.1em 0.5em", false),
+                    Some('r') => ("text-align: right; padding: 0.1em 0.5em", false),
+                    _ => ("padding: 0.1em 0.5em", false),
+                },
+            };
+            out.push_str(&format!(r#"<mtd style="{}">"#, style));
+            out.push_str(&format!(r#"<mrow displaystyle="{}">"#, displaystyle));
+            // 右側の列は二項演算子として間隔を空けるため，空の要素から始める
+            if matches!(table.columns, Columns::Aligned) && i % 2 == 1 {
+                out.push_str("<mi></mi>");
+            }
+            render_row(cell, out);
+            out.push_str("</mrow></mtd>");
+        }
+        if numbered {
+            out.push_str(r#"<mtd style="width: 50%; text-align: right">"#);
+            if let Some(tag) = &row.tag {
+                out.push_str(&format!("<mtext>{}</mtext>", escape(tag)));
+            }
+            out.push_str("</mtd>");
+        }
+        out.push_str("</mtr>");
+    }
+    out.push_str("</mtable>");
+
+    if table.open.is_some() || table.close.is_some() {
+        if let Some(close) = table.close {
+            out.push_str(&format!(
+                r#"<mo fence="true" form="postfix">{}</mo>"#,
+                close
+            ));
+        }
+        out.push_str("</mrow>");
+    }
+}
+
+// 数学用英数字記号 (U+1D400 以降) への変換
+fn apply_variant(c: char, variant: Variant) -> char {
+    let exception = match (variant, c) {
+        (Variant::Italic, 'h') => Some('ℎ'),
+        (Variant::Script, 'B') => Some('ℬ'),
+        (Variant::Script, 'E') => Some('ℰ'),
+        (Variant::Script, 'F') => Some('ℱ'),
+        (Variant::Script, 'H') => Some('ℋ'),
+        (Variant::Script, 'I') => Some('ℐ'),
+        (Variant::Script, 'L') => Some('ℒ'),
+        (Variant::Script, 'M') => Some('ℳ'),
+        (Variant::Script, 'R') => Some('ℛ'),
+        (Variant::Script, 'e') => Some('ℯ'),
+        (Variant::Script, 'g') => Some('ℊ'),
+        (Variant::Script, 'o') => Some('ℴ'),
+        (Variant::Fraktur, 'C') => Some('ℭ'),
+        (Variant::Fraktur, 'H') => Some('ℌ'),
+        (Variant::Fraktur, 'I') => Some('ℑ'),
+        (Variant::Fraktur, 'R') => Some('ℜ'),
+        (Variant::Fraktur, 'Z') => Some('ℨ'),
+        (Variant::DoubleStruck, 'C') => Some('ℂ'),
+        (Variant::DoubleStruck, 'H') => Some('ℍ'),
+        (Variant::DoubleStruck, 'N') => Some('ℕ'),
+        (Variant::DoubleStruck, 'P') => Some('ℙ'),
+        (Variant::DoubleStruck, 'Q') => Some('ℚ'),
+        (Variant::DoubleStruck, 'R') => Some('ℝ'),
+        (Variant::DoubleStruck, 'Z') => Some('ℤ'),
+        _ => None,
+    };
+    if let Some(exception) = exception {
+        return exception;
+    }
+
+    let (letter_base, digit_base) = match variant {
+        Variant::Normal => return c,
+        Variant::Bold => (0x1D400, Some(0x1D7CE)),
+        Variant::Italic => (0x1D434, None),
+        Variant::BoldItalic => (0x1D468, Some(0x1D7CE)),
+        Variant::Script => (0x1D49C, None),
+        Variant::Fraktur => (0x1D504, None),
+        Variant::DoubleStruck => (0x1D538, Some(0x1D7D8)),
+        Variant::SansSerif => (0x1D5A0, Some(0x1D7E2)),
+        Variant::Monospace => (0x1D670, Some(0x1D7F6)),
+    };
+    let code = match c {
+        'A'..='Z' => letter_base + (c as u32 - 'A' as u32),
+        'a'..='z' => letter_base + 26 + (c as u32 - 'a' as u32),
+        '0'..='9' => match digit_base {
+            Some(digit_base) => digit_base + (c as u32 - '0' as u32),
+            None => return c,
+        },
+        _ => return c,
+    };
+    char::from_u32(code).unwrap_or(c)
+}
+
+fn get_variant(name: &str) -> Option<Variant> {
+    Some(match name {
+        "mathrm" | "rm" | "mathup" => Variant::Normal,
+        "mathbf" | "bf" => Variant::Bold,
+        "mathit" | "it" => Variant::Italic,
+        "boldsymbol" | "bm" => Variant::BoldItalic,
+        "mathbb" => Variant::DoubleStruck,
+        "mathcal" | "mathscr" => Variant::Script,
+        "mathfrak" => Variant::Fraktur,
+        "mathsf" => Variant::SansSerif,
+        "mathtt" => Variant::Monospace,
+        _ => return None,
+    })
+}
+
+fn get_greek_letter(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "omicron" => "ο",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        _ => return None,
+    })
+}
+
+fn get_symbol_identifier(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "hbar" => "ℏ",
+        "ell" => "ℓ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        "wp" => "℘",
+        "emptyset" => "∅",
+        "varnothing" => "∅",
+        "imath" => "ı",
+        "jmath" => "ȷ",
+        "angle" => "∠",
+        "triangle" => "△",
+        "top" => "⊤",
+        "bot" => "⊥",
+        "prime" => "′",
+        "%" => "%",
+        "$" => "$",
+        "#" => "#",
+        "&" => "&",
+        "_" => "_",
+        _ => return None,
+    })
+}
+
+fn get_symbol_operator(name: &str) -> Option<Operator> {
+    let text = match name {
+        "pm" => "±",
+        "mp" => "∓",
+        "times" => "×",
+        "div" => "÷",
+        "cdot" => "⋅",
+        "cdots" => "⋯",
+        "ldots" | "dots" => "…",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "leqq" => "≦",
+        "geqq" => "≧",
+        "neq" | "ne" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "supset" => "⊃",
+        "subseteq" => "⊆",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "forall" => "∀",
+        "exists" => "∃",
+        "neg" | "lnot" => "¬",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "Rightarrow" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" => "⇔",
+        "leftrightarrow" => "↔",
+        "iff" => "⟺",
+        "implies" => "⟹",
+        "longrightarrow" => "⟶",
+        "longleftarrow" => "⟵",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        "mid" => "∣",
+        "parallel" => "∥",
+        "perp" => "⊥",
+        "circ" => "∘",
+        "bullet" => "∙",
+        "star" => "⋆",
+        "ast" => "∗",
+        "oplus" => "⊕",
+        "ominus" => "⊖",
+        "otimes" => "⊗",
+        "odot" => "⊙",
+        "ll" => "≪",
+        "gg" => "≫",
+        "models" => "⊨",
+        "vdash" => "⊢",
+        "therefore" => "∴",
+        "because" => "∵",
+        "dagger" => "†",
+        "colon" => ":",
+        "backslash" => "∖",
+        _ => {
+            let fence = match name {
+                "{" | "lbrace" => "{",
+                "}" | "rbrace" => "}",
+                "langle" => "⟨",
+                "rangle" => "⟩",
+                "lfloor" => "⌊",
+                "rfloor" => "⌋",
+                "lceil" => "⌈",
+                "rceil" => "⌉",
+                "|" | "Vert" | "lVert" | "rVert" => "‖",
+                "vert" | "lvert" | "rvert" => "|",
+                _ => return None,
+            };
+            return Some(Operator::fence(fence));
+        }
+    };
+    Some(Operator::new(text))
+}
+
+fn get_large_operator(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "iiint" => "∭",
+        "oint" => "∮",
+        "bigcup" => "⋃",
+        "bigcap" => "⋂",
+        "bigoplus" => "⨁",
+        "bigotimes" => "⨂",
+        "bigvee" => "⋁",
+        "bigwedge" => "⋀",
+        _ => return None,
+    })
+}
+
+fn get_function(name: &str) -> Option<(&'static str, bool)> {
+    Some(match name {
+        "sin" => ("sin", false),
+        "cos" => ("cos", false),
+        "tan" => ("tan", false),
+        "cot" => ("cot", false),
+        "sec" => ("sec", false),
+        "csc" => ("csc", false),
+        "arcsin" => ("arcsin", false),
+        "arccos" => ("arccos", false),
+        "arctan" => ("arctan", false),
+        "sinh" => ("sinh", false),
+        "cosh" => ("cosh", false),
+        "tanh" => ("tanh", false),
+        "log" => ("log", false),
+        "ln" => ("ln", false),
+        "lg" => ("lg", false),
+        "exp" => ("exp", false),
+        "det" => ("det", true),
+        "dim" => ("dim", false),
+        "ker" => ("ker", false),
+        "deg" => ("deg", false),
+        "arg" => ("arg", false),
+        "gcd" => ("gcd", true),
+        "hom" => ("hom", false),
+        "Pr" => ("Pr", true),
+        "lim" => ("lim", true),
+        "limsup" => ("lim sup", true),
+        "liminf" => ("lim inf", true),
+        "max" => ("max", true),
+        "min" => ("min", true),
+        "sup" => ("sup", true),
+        "inf" => ("inf", true),
+        _ => return None,
+    })
+}
+
+fn get_space(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "," | "thinspace" => "0.1667em",
+        ":" | ">" | "medspace" => "0.2222em",
+        ";" | "thickspace" => "0.2778em",
+        "!" | "negthinspace" => "-0.1667em",
+        " " => "0.3333em",
+        "quad" => "1em",
+        "qquad" => "2em",
+        _ => return None,
+    })
+}
+
+fn get_delimiter_size(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "big" | "bigl" | "bigr" | "bigm" => "1.2em",
+        "Big" | "Bigl" | "Bigr" | "Bigm" => "1.623em",
+        "bigg" | "biggl" | "biggr" | "biggm" => "2.047em",
+        "Bigg" | "Biggl" | "Biggr" | "Biggm" => "2.470em",
+        _ => return None,
+    })
+}
+
+// (アクセント記号, 伸縮するか)
+fn get_accent(name: &str) -> Option<(&'static str, bool)> {
+    Some(match name {
+        "hat" => ("^", false),
+        "widehat" => ("^", true),
+        "bar" => ("¯", false),
+        "vec" => ("→", false),
+        "overrightarrow" => ("→", true),
+        "overleftarrow" => ("←", true),
+        "dot" => ("˙", false),
+        "ddot" => ("¨", false),
+        "tilde" => ("~", false),
+        "widetilde" => ("~", true),
+        "check" => ("ˇ", false),
+        "breve" => ("˘", false),
+        "acute" => ("´", false),
+        "grave" => ("`", false),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(tex: &str) -> String {
+        tex_to_mathml(tex, false, &[]).unwrap()
+    }
+
+    fn convert_display(tex: &str) -> String {
+        tex_to_mathml(tex, true, &[]).unwrap()
+    }
+
+    fn convert_error(tex: &str) -> String {
+        format!("{:#}", tex_to_mathml(tex, false, &[]).unwrap_err())
+    }
+
+    #[test]
+    fn fraction() {
+        assert_eq!(
+            convert(r"\frac{a}{b}"),
+            r#"<math display="inline"><mfrac><mi>a</mi><mi>b</mi></mfrac></math>"#
+        );
+    }
+
+    #[test]
+    fn over() {
+        assert_eq!(convert(r"a \over b"), convert(r"\frac{a}{b}"));
+        assert_eq!(convert(r"{a+b \over c}"), convert(r"{\frac{a+b}{c}}"));
+    }
+
+    #[test]
+    fn scripts() {
+        assert_eq!(
+            convert("x^2"),
+            r#"<math display="inline"><msup><mi>x</mi><mn>2</mn></msup></math>"#
+        );
+        assert_eq!(
+            convert("x_i"),
+            r#"<math display="inline"><msub><mi>x</mi><mi>i</mi></msub></math>"#
+        );
+        // 上付きと下付きの順序によらず <msubsup> になる
+        let expected =
+            r#"<math display="inline"><msubsup><mi>x</mi><mi>i</mi><mn>2</mn></msubsup></math>"#;
+        assert_eq!(convert("x^2_i"), expected);
+        assert_eq!(convert("x_i^2"), expected);
+    }
+
+    #[test]
+    fn left_right() {
+        assert_eq!(
+            convert(r"\left( x \right)"),
+            concat!(
+                r#"<math display="inline"><mrow>"#,
+                r#"<mo fence="true" stretchy="true" form="prefix">(</mo>"#,
+                r#"<mrow><mi>x</mi></mrow>"#,
+                r#"<mo fence="true" stretchy="true" form="postfix">)</mo>"#,
+                r#"</mrow></math>"#
+            )
+        );
+    }
+
+    #[test]
+    fn align_with_tag() {
+        let mathml =
+            convert_display(r"\begin{align} a &= b \tag{A} \\ c &= d \notag \\ e &= f \end{align}");
+        assert!(mathml.starts_with(r#"<math display="block"><mtable"#));
+        assert_eq!(mathml.matches("<mtr>").count(), 3);
+        // \tag の行だけに番号が入り，\notag の行と最後の行は空になる
+        let tags: Vec<&str> = mathml
+            .split("<mtr>")
+            .skip(1)
+            .map(|row| {
+                let start = row.rfind("text-align: right\">").unwrap();
+                &row[start..]
+            })
+            .collect();
+        assert!(tags[0].contains("<mtext>(A)</mtext>"));
+        assert!(!tags[1].contains("<mtext>"));
+        assert!(!tags[2].contains("<mtext>"));
+    }
+
+    #[test]
+    fn multiple_tags_in_one_line() {
+        assert!(tex_to_mathml(r"\begin{align} a \tag{1} \tag{2} \end{align}", true, &[]).is_err());
+    }
+
+    #[test]
+    fn unbalanced_braces() {
+        assert_eq!(convert_error("{a"), "unbalanced braces");
+        assert_eq!(convert_error("a}"), "unbalanced braces");
+    }
+
+    #[test]
+    fn unknown_command() {
+        assert_eq!(convert_error(r"\foo"), r"unknown command: '\foo'");
+    }
+
+    #[test]
+    fn missing_end() {
+        assert_eq!(
+            format!(
+                "{:#}",
+                tex_to_mathml(r"\begin{align} a", true, &[]).unwrap_err()
+            ),
+            r"in environment 'align': missing '\end{align}'"
+        );
+        assert_eq!(convert_error(r"\left( x"), r"missing '\right'");
+        assert_eq!(convert_error(r"x \right)"), r"missing '\left'");
+    }
+}
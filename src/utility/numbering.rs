@@ -0,0 +1,232 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumberStyle {
+    Arabic,
+    LowerRoman,
+    UpperRoman,
+    LowerAlphabetic,
+    UpperAlphabetic,
+    Kanji,
+}
+
+impl NumberStyle {
+    pub fn from_name(name: &str) -> Option<NumberStyle> {
+        match name {
+            "arabic" => Some(NumberStyle::Arabic),
+            "roman" => Some(NumberStyle::LowerRoman),
+            "upper-roman" => Some(NumberStyle::UpperRoman),
+            "alphabetic" => Some(NumberStyle::LowerAlphabetic),
+            "upper-alphabetic" => Some(NumberStyle::UpperAlphabetic),
+            "kanji" => Some(NumberStyle::Kanji),
+            _ => None,
+        }
+    }
+
+    pub fn format(&self, number: usize) -> String {
+        match self {
+            NumberStyle::Arabic => number.to_string(),
+            NumberStyle::LowerRoman => to_roman(number).to_lowercase(),
+            NumberStyle::UpperRoman => to_roman(number),
+            NumberStyle::LowerAlphabetic => to_alphabetic(number).to_lowercase(),
+            NumberStyle::UpperAlphabetic => to_alphabetic(number),
+            NumberStyle::Kanji => to_kanji(number),
+        }
+    }
+}
+
+// 0 や 4000 以上はローマ数字で表せないため算用数字にする
+fn to_roman(number: usize) -> String {
+    if number == 0 || 4000 <= number {
+        return number.to_string();
+    }
+    const SYMBOLS: [(usize, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut number = number;
+    let mut result = String::new();
+    for (value, symbol) in SYMBOLS {
+        while value <= number {
+            result.push_str(symbol);
+            number -= value;
+        }
+    }
+    result
+}
+
+// 1 => A, 26 => Z, 27 => AA
+fn to_alphabetic(number: usize) -> String {
+    if number == 0 {
+        return number.to_string();
+    }
+    let mut number = number;
+    let mut result = Vec::new();
+    while 0 < number {
+        number -= 1;
+        result.push((b'A' + (number % 26) as u8) as char);
+        number /= 26;
+    }
+    result.iter().rev().collect()
+}
+
+// 位取りの漢数字 (12 => 十二, 105 => 百五)
+fn to_kanji(number: usize) -> String {
+    const DIGITS: [&str; 10] = ["〇", "一", "二", "三", "四", "五", "六", "七", "八", "九"];
+    const SMALL_UNITS: [&str; 4] = ["", "十", "百", "千"];
+    const LARGE_UNITS: [&str; 5] = ["", "万", "億", "兆", "京"];
+
+    if number == 0 {
+        return DIGITS[0].to_string();
+    }
+
+    let mut groups = Vec::new();
+    let mut rest = number;
+    while 0 < rest {
+        groups.push(rest % 10000);
+        rest /= 10000;
+    }
+
+    let mut result = String::new();
+    for (i, &group) in groups.iter().enumerate().rev() {
+        if group == 0 {
+            continue;
+        }
+        for (j, unit) in SMALL_UNITS.iter().enumerate().rev() {
+            let digit = group / 10usize.pow(j as u32) % 10;
+            if digit == 0 {
+                continue;
+            }
+            // 十・百・千の前の一は省略する
+            if !(digit == 1 && 0 < j) {
+                result.push_str(DIGITS[digit]);
+            }
+            result.push_str(unit);
+        }
+        result.push_str(LARGE_UNITS[i]);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format_all(style: NumberStyle, numbers: &[usize]) -> Vec<String> {
+        numbers.iter().map(|&number| style.format(number)).collect()
+    }
+
+    #[test]
+    fn arabic() {
+        assert_eq!(
+            format_all(NumberStyle::Arabic, &[0, 1, 10, 4000]),
+            ["0", "1", "10", "4000"]
+        );
+    }
+
+    #[test]
+    fn roman() {
+        assert_eq!(
+            format_all(
+                NumberStyle::UpperRoman,
+                &[1, 4, 9, 10, 11, 14, 20, 40, 90, 100, 400, 1994, 3999]
+            ),
+            [
+                "I",
+                "IV",
+                "IX",
+                "X",
+                "XI",
+                "XIV",
+                "XX",
+                "XL",
+                "XC",
+                "C",
+                "CD",
+                "MCMXCIV",
+                "MMMCMXCIX"
+            ]
+        );
+        assert_eq!(
+            format_all(NumberStyle::LowerRoman, &[3, 11, 100]),
+            ["iii", "xi", "c"]
+        );
+        // ローマ数字で表せない数
+        assert_eq!(
+            format_all(NumberStyle::UpperRoman, &[0, 4000, 12345]),
+            ["0", "4000", "12345"]
+        );
+    }
+
+    #[test]
+    fn alphabetic() {
+        assert_eq!(
+            format_all(
+                NumberStyle::LowerAlphabetic,
+                &[1, 2, 26, 27, 28, 52, 53, 702, 703]
+            ),
+            ["a", "b", "z", "aa", "ab", "az", "ba", "zz", "aaa"]
+        );
+        assert_eq!(
+            format_all(NumberStyle::UpperAlphabetic, &[0, 1, 26, 27]),
+            ["0", "A", "Z", "AA"]
+        );
+    }
+
+    #[test]
+    fn kanji() {
+        assert_eq!(
+            format_all(
+                NumberStyle::Kanji,
+                &[0, 1, 10, 11, 20, 21, 100, 105, 110, 1000, 1999, 4000]
+            ),
+            [
+                "〇",
+                "一",
+                "十",
+                "十一",
+                "二十",
+                "二十一",
+                "百",
+                "百五",
+                "百十",
+                "千",
+                "千九百九十九",
+                "四千"
+            ]
+        );
+        assert_eq!(
+            format_all(
+                NumberStyle::Kanji,
+                &[10000, 10001, 12345, 100000000, 100010000, 20000000000000000]
+            ),
+            [
+                "一万",
+                "一万一",
+                "一万二千三百四十五",
+                "一億",
+                "一億一万",
+                "二京"
+            ]
+        );
+    }
+
+    #[test]
+    fn style_names() {
+        assert_eq!(
+            NumberStyle::from_name("upper-roman"),
+            Some(NumberStyle::UpperRoman)
+        );
+        assert_eq!(NumberStyle::from_name("kanji"), Some(NumberStyle::Kanji));
+        assert_eq!(NumberStyle::from_name("Roman"), None);
+    }
+}
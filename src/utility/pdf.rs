@@ -0,0 +1,1553 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Context, Result};
+
+use super::flate::inflate_zlib;
+
+// 入れ子や参照をたどる深さの上限 (壊れた PDF で止まらないようにする)
+const MAX_DEPTH: usize = 64;
+
+// PDF のページ送りを右から左にする (縦書き用)
+// 増分更新でカタログに /ViewerPreferences を追記する
+pub fn set_right_to_left_direction(pdf: Vec<u8>) -> Result<Vec<u8>> {
+    let update = match get_right_to_left_update(&PdfDocument::load(&pdf)?)? {
+        Some(update) => update,
+        None => return Ok(pdf),
+    };
+    let mut result = pdf;
+    result.extend_from_slice(&update);
+    Ok(result)
+}
+
+// 名前付き出力先ごとに，それが何ページ目 (1 始まり) にあるかを返す
+// Chrome はリンク先になっている id を名前付き出力先として書き出す
+pub fn get_named_destination_pages(pdf: &[u8]) -> Result<Vec<(String, usize)>> {
+    let document = PdfDocument::load(pdf)?;
+    let catalog = document.get_catalog()?;
+    let pages = document.get_pages(&catalog)?;
+
+    let mut destinations = Vec::new();
+    // カタログの /Dests 辞書 (PDF 1.1 の形式)
+    if let Some(dests) = get(&catalog, "Dests") {
+        destinations.extend(document.resolve_dictionary(dests)?);
+    }
+    // /Names の /Dests 名前ツリー (PDF 1.2 以降の形式)
+    if let Some(names) = get(&catalog, "Names") {
+        if let Some(tree) = get(&document.resolve_dictionary(names)?, "Dests") {
+            document.collect_name_tree(tree, 0, &mut destinations)?;
+        }
+    }
+
+    let mut result = Vec::new();
+    for (name, destination) in destinations {
+        if let Some(page) = document.get_destination_page(&destination)? {
+            if let Some(index) = pages.iter().position(|&number| number == page) {
+                result.push((name, index + 1));
+            }
+        }
+    }
+    Ok(result)
+}
+
+// カタログを書き換えたオブジェクトと，それを指す相互参照 (すでに右から左なら None)
+fn get_right_to_left_update(document: &PdfDocument) -> Result<Option<Vec<u8>>> {
+    if get(&document.trailer, "Encrypt").is_some() {
+        bail!("encrypted pdf is not supported");
+    }
+    let (root_number, root_generation) = match get(&document.trailer, "Root") {
+        Some(PdfObject::Reference(number, generation)) => (*number, *generation),
+        _ => bail!("pdf root not found"),
+    };
+    let size = get_integer(&document.trailer, "Size").context("pdf size not found")?;
+    if size <= root_number || 0xffff < root_generation {
+        bail!("invalid pdf root");
+    }
+
+    let mut catalog = document.get_catalog()?;
+    let mut preferences = match get(&catalog, "ViewerPreferences") {
+        Some(preferences) => document.resolve_dictionary(preferences)?,
+        None => Vec::new(),
+    };
+    let right_to_left = PdfObject::Name("R2L".to_string());
+    if get(&preferences, "Direction") == Some(&right_to_left) {
+        return Ok(None);
+    }
+    set(&mut preferences, "Direction", right_to_left);
+    set(
+        &mut catalog,
+        "ViewerPreferences",
+        PdfObject::Dictionary(preferences),
+    );
+
+    let mut update = Vec::new();
+    if !document.data.ends_with(b"\n") {
+        update.push(b'\n');
+    }
+    let catalog_offset = document.data.len() + update.len();
+    update.extend_from_slice(format!("{} {} obj\n", root_number, root_generation).as_bytes());
+    write_object(&mut update, &PdfObject::Dictionary(catalog));
+    update.extend_from_slice(b"\nendobj\n");
+
+    let xref_offset = document.data.len() + update.len();
+    let mut trailer = Vec::new();
+    for key in ["Root", "Info", "ID"] {
+        if let Some(value) = get(&document.trailer, key) {
+            trailer.push((key.to_string(), value.clone()));
+        }
+    }
+    trailer.push((
+        "Prev".to_string(),
+        PdfObject::Integer(document.startxref as i64),
+    ));
+
+    if document.xref_stream {
+        // 元の相互参照がストリームなら，追記するものもストリームにする (番号は /Size)
+        let offset_width = (u64::BITS - (xref_offset as u64).leading_zeros()).div_ceil(8);
+        let mut rows = Vec::new();
+        for (offset, generation) in [(catalog_offset, root_generation), (xref_offset, 0)] {
+            rows.push(1);
+            rows.extend_from_slice(&(offset as u64).to_be_bytes()[8 - offset_width as usize..]);
+            rows.extend_from_slice(&(generation as u16).to_be_bytes());
+        }
+        let integers = |values: &[usize]| {
+            PdfObject::Array(
+                values
+                    .iter()
+                    .map(|&value| PdfObject::Integer(value as i64))
+                    .collect(),
+            )
+        };
+        let mut dictionary = vec![
+            ("Type".to_string(), PdfObject::Name("XRef".to_string())),
+            ("Size".to_string(), PdfObject::Integer(size as i64 + 1)),
+            ("Index".to_string(), integers(&[root_number, 1, size, 1])),
+            ("W".to_string(), integers(&[1, offset_width as usize, 2])),
+        ];
+        dictionary.extend(trailer);
+        dictionary.push(("Length".to_string(), PdfObject::Integer(rows.len() as i64)));
+
+        update.extend_from_slice(format!("{} 0 obj\n", size).as_bytes());
+        write_object(&mut update, &PdfObject::Dictionary(dictionary));
+        update.extend_from_slice(b"\nstream\n");
+        update.extend_from_slice(&rows);
+        update.extend_from_slice(b"\nendstream\nendobj\n");
+    } else {
+        update.extend_from_slice(
+            format!(
+                "xref\n{} 1\n{:010} {:05} n \ntrailer\n",
+                root_number, catalog_offset, root_generation
+            )
+            .as_bytes(),
+        );
+        let mut dictionary = vec![("Size".to_string(), PdfObject::Integer(size as i64))];
+        dictionary.extend(trailer);
+        write_object(&mut update, &PdfObject::Dictionary(dictionary));
+        update.push(b'\n');
+    }
+    update.extend_from_slice(format!("startxref\n{}\n%%EOF\n", xref_offset).as_bytes());
+    Ok(Some(update))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PdfObject {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Real(f64),
+    String(Vec<u8>),
+    // 先頭の / を除き，#xx を戻したもの
+    Name(String),
+    Array(Vec<PdfObject>),
+    Dictionary(PdfDictionary),
+    Reference(usize, usize),
+}
+
+type PdfDictionary = Vec<(String, PdfObject)>;
+
+fn get<'a>(dictionary: &'a PdfDictionary, key: &str) -> Option<&'a PdfObject> {
+    dictionary
+        .iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value)
+}
+
+fn set(dictionary: &mut PdfDictionary, key: &str, value: PdfObject) {
+    match dictionary.iter_mut().find(|(name, _)| name == key) {
+        Some((_, old_value)) => *old_value = value,
+        None => dictionary.push((key.to_string(), value)),
+    }
+}
+
+// 直接書かれた 0 以上の整数
+fn get_integer(dictionary: &PdfDictionary, key: &str) -> Option<usize> {
+    match get(dictionary, key) {
+        Some(PdfObject::Integer(value)) => usize::try_from(*value).ok(),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum XrefEntry {
+    Offset(usize),
+    // オブジェクトストリームの番号と，その中での順番
+    Compressed(usize, usize),
+}
+
+// startxref からたどった相互参照をもとに，オブジェクトを読み出す
+// (ファイルを後ろから探すと，圧縮されたストリームの中身に一致してしまうことがある)
+struct PdfDocument<'a> {
+    data: &'a [u8],
+    entries: HashMap<usize, XrefEntry>,
+    // 最新の trailer (相互参照ストリームならその辞書)
+    trailer: PdfDictionary,
+    startxref: usize,
+    // 最新の相互参照がストリームかどうか
+    xref_stream: bool,
+}
+
+impl<'a> PdfDocument<'a> {
+    fn load(data: &'a [u8]) -> Result<PdfDocument<'a>> {
+        let startxref = find_startxref(data)?;
+        let mut document = PdfDocument {
+            data,
+            entries: HashMap::new(),
+            trailer: Vec::new(),
+            startxref,
+            xref_stream: false,
+        };
+
+        // 新しい相互参照から順に /Prev をたどる (先に読んだものが優先)
+        let mut visited = HashSet::new();
+        let mut next = Some(startxref);
+        while let Some(offset) = next {
+            if !visited.insert(offset) {
+                bail!("pdf cross-reference sections form a loop");
+            }
+            let is_table = Parser::new(data, offset).read_token().ok() == Some(&b"xref"[..]);
+            let trailer = if is_table {
+                document.read_xref_table(offset)?
+            } else {
+                document.read_xref_stream(offset)?
+            };
+            // 相互参照表と相互参照ストリームを併用した PDF では /XRefStm にも項目がある
+            if let Some(xref_stream) = get_integer(&trailer, "XRefStm") {
+                if visited.insert(xref_stream) {
+                    document.read_xref_stream(xref_stream)?;
+                }
+            }
+            next = get_integer(&trailer, "Prev");
+            if offset == startxref {
+                document.trailer = trailer;
+                document.xref_stream = !is_table;
+            }
+        }
+        Ok(document)
+    }
+
+    fn read_xref_table(&mut self, offset: usize) -> Result<PdfDictionary> {
+        let mut parser = Parser::new(self.data, offset);
+        parser.expect_keyword("xref")?;
+        loop {
+            let token = parser.read_token()?;
+            if token == b"trailer" {
+                break;
+            }
+            let start = parse_usize(token)?;
+            let count = parse_usize(parser.read_token()?)?;
+            let end = start
+                .checked_add(count)
+                .context("invalid pdf cross-reference table")?;
+            for number in start..end {
+                let offset = parse_usize(parser.read_token()?)?;
+                parse_usize(parser.read_token()?)?;
+                match parser.read_token()? {
+                    b"n" => {
+                        self.entries
+                            .entry(number)
+                            .or_insert(XrefEntry::Offset(offset));
+                    }
+                    b"f" => {}
+                    _ => bail!("invalid pdf cross-reference entry at offset {}", offset),
+                }
+            }
+        }
+        match parser.parse_object(0)? {
+            PdfObject::Dictionary(trailer) => Ok(trailer),
+            _ => bail!("invalid pdf trailer"),
+        }
+    }
+
+    fn read_xref_stream(&mut self, offset: usize) -> Result<PdfDictionary> {
+        let object = parse_indirect_object(self.data, offset)
+            .context("pdf cross-reference not found at startxref")?;
+        let dictionary = match object.object {
+            PdfObject::Dictionary(dictionary) => dictionary,
+            _ => bail!("invalid pdf cross-reference stream"),
+        };
+        if get(&dictionary, "Type") != Some(&PdfObject::Name("XRef".to_string())) {
+            bail!("pdf cross-reference not found at startxref");
+        }
+        // 相互参照ストリームの /Length は直接書かれる
+        let length = get_integer(&dictionary, "Length")
+            .context("invalid pdf cross-reference stream length")?;
+        let start = object
+            .stream_start
+            .context("invalid pdf cross-reference stream")?;
+        let data = decode_stream(&dictionary, get_stream_data(self.data, start, length)?)?;
+
+        let widths = match get(&dictionary, "W") {
+            Some(PdfObject::Array(widths)) if widths.len() == 3 => widths
+                .iter()
+                .map(|width| match width {
+                    PdfObject::Integer(width @ 0..=8) => Ok(*width as usize),
+                    _ => bail!("invalid pdf cross-reference stream widths"),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            _ => bail!("invalid pdf cross-reference stream widths"),
+        };
+        let row_length: usize = widths.iter().sum();
+        if row_length == 0 {
+            bail!("invalid pdf cross-reference stream widths");
+        }
+        let index = match get(&dictionary, "Index") {
+            Some(PdfObject::Array(index)) => index
+                .iter()
+                .map(|value| match value {
+                    PdfObject::Integer(value) => {
+                        usize::try_from(*value).context("invalid pdf cross-reference index")
+                    }
+                    _ => bail!("invalid pdf cross-reference index"),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            _ => vec![
+                0,
+                get_integer(&dictionary, "Size").context("pdf size not found")?,
+            ],
+        };
+        if index.len() % 2 != 0 {
+            bail!("invalid pdf cross-reference index");
+        }
+
+        let mut rows = data.chunks_exact(row_length);
+        for section in index.chunks(2) {
+            let end = section[0]
+                .checked_add(section[1])
+                .context("invalid pdf cross-reference index")?;
+            for number in section[0]..end {
+                let row = rows
+                    .next()
+                    .context("pdf cross-reference stream is too short")?;
+                let mut fields = [0; 3];
+                let mut position = 0;
+                for (field, &width) in fields.iter_mut().zip(&widths) {
+                    *field = row[position..position + width]
+                        .iter()
+                        .fold(0, |value, &byte| value << 8 | byte as usize);
+                    position += width;
+                }
+                // 種類の幅が 0 なら種類 1 とみなす
+                let kind = if widths[0] == 0 { 1 } else { fields[0] };
+                let entry = match kind {
+                    1 => XrefEntry::Offset(fields[1]),
+                    2 => XrefEntry::Compressed(fields[1], fields[2]),
+                    _ => continue,
+                };
+                self.entries.entry(number).or_insert(entry);
+            }
+        }
+        Ok(dictionary)
+    }
+
+    // 相互参照にないオブジェクトは null とみなす
+    fn get_object(&self, number: usize) -> Result<PdfObject> {
+        match self.entries.get(&number) {
+            None => Ok(PdfObject::Null),
+            Some(XrefEntry::Offset(offset)) => {
+                let object = parse_indirect_object(self.data, *offset)?;
+                if object.number != number {
+                    bail!("pdf object {} not found at offset {}", number, offset);
+                }
+                Ok(object.object)
+            }
+            Some(XrefEntry::Compressed(stream_number, index)) => {
+                let (dictionary, data) = self.get_stream(*stream_number)?;
+                let first =
+                    get_integer(&dictionary, "First").context("invalid pdf object stream")?;
+                let count = get_integer(&dictionary, "N").context("invalid pdf object stream")?;
+                if count <= *index {
+                    bail!("pdf object {} not found in object stream", number);
+                }
+                // 先頭に (番号, 位置) の組が並ぶ
+                let mut parser = Parser::new(&data, 0);
+                let mut header = Vec::new();
+                for _ in 0..=*index {
+                    let object_number = parse_usize(parser.read_token()?)?;
+                    let object_offset = parse_usize(parser.read_token()?)?;
+                    header.push((object_number, object_offset));
+                }
+                let (object_number, object_offset) = header[*index];
+                if object_number != number {
+                    bail!("pdf object {} not found in object stream", number);
+                }
+                Parser::new(&data, first + object_offset).parse_object(0)
+            }
+        }
+    }
+
+    // ストリームの辞書と展開したデータ
+    fn get_stream(&self, number: usize) -> Result<(PdfDictionary, Vec<u8>)> {
+        let offset = match self.entries.get(&number) {
+            Some(XrefEntry::Offset(offset)) => *offset,
+            _ => bail!("pdf stream {} not found", number),
+        };
+        let object = parse_indirect_object(self.data, offset)?;
+        let (dictionary, start) = match (object.object, object.stream_start) {
+            (PdfObject::Dictionary(dictionary), Some(start)) if object.number == number => {
+                (dictionary, start)
+            }
+            _ => bail!("pdf stream {} not found at offset {}", number, offset),
+        };
+        let length = match get(&dictionary, "Length") {
+            Some(length) => match self.resolve(length)? {
+                PdfObject::Integer(length) => usize::try_from(length).ok(),
+                _ => None,
+            },
+            None => None,
+        }
+        .context("invalid pdf stream length")?;
+        let data = decode_stream(&dictionary, get_stream_data(self.data, start, length)?)?;
+        Ok((dictionary, data))
+    }
+
+    fn resolve(&self, object: &PdfObject) -> Result<PdfObject> {
+        let mut object = object.clone();
+        for _ in 0..MAX_DEPTH {
+            match object {
+                PdfObject::Reference(number, _) => object = self.get_object(number)?,
+                _ => return Ok(object),
+            }
+        }
+        bail!("pdf references are nested too deeply")
+    }
+
+    fn resolve_dictionary(&self, object: &PdfObject) -> Result<PdfDictionary> {
+        match self.resolve(object)? {
+            PdfObject::Dictionary(dictionary) => Ok(dictionary),
+            _ => bail!("pdf dictionary expected"),
+        }
+    }
+
+    fn get_catalog(&self) -> Result<PdfDictionary> {
+        let root = get(&self.trailer, "Root").context("pdf root not found")?;
+        self.resolve_dictionary(root).context("invalid pdf root")
+    }
+
+    // ページツリーをたどり，ページのオブジェクト番号を順に集める
+    fn get_pages(&self, catalog: &PdfDictionary) -> Result<Vec<usize>> {
+        let mut pages = Vec::new();
+        match get(catalog, "Pages") {
+            Some(PdfObject::Reference(number, _)) => {
+                self.collect_pages(*number, 0, &mut HashSet::new(), &mut pages)?
+            }
+            _ => bail!("pdf pages not found"),
+        }
+        Ok(pages)
+    }
+
+    fn collect_pages(
+        &self,
+        number: usize,
+        depth: usize,
+        visited: &mut HashSet<usize>,
+        pages: &mut Vec<usize>,
+    ) -> Result<()> {
+        if MAX_DEPTH < depth || !visited.insert(number) {
+            bail!("invalid pdf page tree");
+        }
+        let node = self
+            .resolve_dictionary(&PdfObject::Reference(number, 0))
+            .context("invalid pdf page tree")?;
+        match get(&node, "Kids") {
+            Some(kids) => match self.resolve(kids)? {
+                PdfObject::Array(kids) => {
+                    for kid in kids {
+                        match kid {
+                            PdfObject::Reference(kid, _) => {
+                                self.collect_pages(kid, depth + 1, visited, pages)?
+                            }
+                            _ => bail!("invalid pdf page tree"),
+                        }
+                    }
+                }
+                _ => bail!("invalid pdf page tree"),
+            },
+            None => pages.push(number),
+        }
+        Ok(())
+    }
+
+    // 名前ツリーの葉 (/Names [キー 値 ...]) を順に集める
+    fn collect_name_tree(
+        &self,
+        node: &PdfObject,
+        depth: usize,
+        entries: &mut PdfDictionary,
+    ) -> Result<()> {
+        if MAX_DEPTH < depth {
+            bail!("pdf name tree is too deep");
+        }
+        let node = self
+            .resolve_dictionary(node)
+            .context("invalid pdf name tree")?;
+        if let Some(names) = get(&node, "Names") {
+            match self.resolve(names)? {
+                PdfObject::Array(names) => {
+                    for pair in names.chunks_exact(2) {
+                        if let PdfObject::String(key) = self.resolve(&pair[0])? {
+                            entries.push((decode_text_string(&key), pair[1].clone()));
+                        }
+                    }
+                }
+                _ => bail!("invalid pdf name tree"),
+            }
+        }
+        if let Some(kids) = get(&node, "Kids") {
+            match self.resolve(kids)? {
+                PdfObject::Array(kids) => {
+                    for kid in &kids {
+                        self.collect_name_tree(kid, depth + 1, entries)?;
+                    }
+                }
+                _ => bail!("invalid pdf name tree"),
+            }
+        }
+        Ok(())
+    }
+
+    // 出力先は [ページ /XYZ x y z] の配列か，<< /D [...] >> の辞書
+    fn get_destination_page(&self, destination: &PdfObject) -> Result<Option<usize>> {
+        let destination = match self.resolve(destination)? {
+            PdfObject::Dictionary(dictionary) => match get(&dictionary, "D") {
+                Some(destination) => self.resolve(destination)?,
+                None => return Ok(None),
+            },
+            destination => destination,
+        };
+        match destination {
+            PdfObject::Array(items) => match items.first() {
+                Some(PdfObject::Reference(page, _)) => Ok(Some(*page)),
+                _ => Ok(None),
+            },
+            _ => Ok(None),
+        }
+    }
+}
+
+// ファイル末尾の startxref が指す位置
+fn find_startxref(data: &[u8]) -> Result<usize> {
+    let tail_start = data.len().saturating_sub(1024);
+    let position = data[tail_start..]
+        .windows(b"startxref".len())
+        .rposition(|window| window == b"startxref")
+        .context("pdf startxref not found")?;
+    let mut parser = Parser::new(data, tail_start + position + b"startxref".len());
+    let offset = parse_usize(parser.read_token()?)?;
+    if data.len() <= offset {
+        bail!("invalid pdf startxref");
+    }
+    Ok(offset)
+}
+
+// テキスト文字列は BOM 付きの UTF-16BE か，それ以外 (ASCII の範囲は PDFDocEncoding と共通)
+fn decode_text_string(bytes: &[u8]) -> String {
+    match bytes {
+        [0xfe, 0xff, rest @ ..] => String::from_utf16_lossy(
+            &rest
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect::<Vec<_>>(),
+        ),
+        _ => match std::str::from_utf8(bytes) {
+            Ok(text) => text.to_string(),
+            Err(_) => bytes.iter().map(|&byte| byte as char).collect(),
+        },
+    }
+}
+
+fn parse_usize(token: &[u8]) -> Result<usize> {
+    std::str::from_utf8(token)
+        .ok()
+        .and_then(|token| token.parse().ok())
+        .with_context(|| format!("invalid pdf number '{}'", String::from_utf8_lossy(token)))
+}
+
+fn get_stream_data(data: &[u8], start: usize, length: usize) -> Result<&[u8]> {
+    start
+        .checked_add(length)
+        .and_then(|end| data.get(start..end))
+        .context("pdf stream exceeds the end of file")
+}
+
+// /Filter に従ってストリームを展開する (Chrome が使う FlateDecode のみ)
+fn decode_stream(dictionary: &PdfDictionary, data: &[u8]) -> Result<Vec<u8>> {
+    let filters = match get(dictionary, "Filter") {
+        None => Vec::new(),
+        Some(PdfObject::Name(filter)) => vec![filter.clone()],
+        Some(PdfObject::Array(filters)) => filters
+            .iter()
+            .map(|filter| match filter {
+                PdfObject::Name(filter) => Ok(filter.clone()),
+                _ => bail!("invalid pdf filter"),
+            })
+            .collect::<Result<Vec<_>>>()?,
+        Some(_) => bail!("invalid pdf filter"),
+    };
+    let parameters = match get(dictionary, "DecodeParms") {
+        Some(PdfObject::Dictionary(parameters)) => vec![Some(parameters)],
+        Some(PdfObject::Array(parameters)) => parameters
+            .iter()
+            .map(|parameters| match parameters {
+                PdfObject::Dictionary(parameters) => Some(parameters),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut data = data.to_vec();
+    for (i, filter) in filters.iter().enumerate() {
+        data = match filter.as_str() {
+            "FlateDecode" => inflate_zlib(&data)?,
+            _ => bail!("unsupported pdf filter: {}", filter),
+        };
+        if let Some(Some(parameters)) = parameters.get(i) {
+            data = apply_predictor(data, parameters)?;
+        }
+    }
+    Ok(data)
+}
+
+// PNG の予測子 (/Predictor 10 以上) を戻す
+fn apply_predictor(data: Vec<u8>, parameters: &PdfDictionary) -> Result<Vec<u8>> {
+    let predictor = get_integer(parameters, "Predictor").unwrap_or(1);
+    if predictor == 1 {
+        return Ok(data);
+    }
+    if predictor < 10 {
+        bail!("unsupported pdf predictor: {}", predictor);
+    }
+    let colors = get_integer(parameters, "Colors").unwrap_or(1);
+    let bits = get_integer(parameters, "BitsPerComponent").unwrap_or(8);
+    let columns = get_integer(parameters, "Columns").unwrap_or(1);
+    let pixel_length = (colors * bits).div_ceil(8).max(1);
+    let row_length = (colors * bits * columns).div_ceil(8);
+    if row_length == 0 {
+        bail!("invalid pdf predictor columns");
+    }
+
+    let mut result = Vec::with_capacity(data.len());
+    let mut previous = vec![0u8; row_length];
+    for row in data.chunks_exact(row_length + 1) {
+        let mut current = row[1..].to_vec();
+        for i in 0..row_length {
+            let left = if pixel_length <= i {
+                current[i - pixel_length]
+            } else {
+                0
+            };
+            let up = previous[i];
+            let upper_left = if pixel_length <= i {
+                previous[i - pixel_length]
+            } else {
+                0
+            };
+            let prediction = match row[0] {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                4 => {
+                    let estimate = i16::from(left) + i16::from(up) - i16::from(upper_left);
+                    let distance = |value: u8| (estimate - i16::from(value)).abs();
+                    if distance(left) <= distance(up) && distance(left) <= distance(upper_left) {
+                        left
+                    } else if distance(up) <= distance(upper_left) {
+                        up
+                    } else {
+                        upper_left
+                    }
+                }
+                _ => bail!("invalid png predictor: {}", row[0]),
+            };
+            current[i] = current[i].wrapping_add(prediction);
+        }
+        result.extend_from_slice(&current);
+        previous = current;
+    }
+    Ok(result)
+}
+
+struct IndirectObject {
+    number: usize,
+    object: PdfObject,
+    // ストリームならそのデータの開始位置
+    stream_start: Option<usize>,
+}
+
+// offset にある "N G obj ... endobj" を読む
+fn parse_indirect_object(data: &[u8], offset: usize) -> Result<IndirectObject> {
+    let mut parser = Parser::new(data, offset);
+    let number = parse_usize(parser.read_token()?)?;
+    parse_usize(parser.read_token()?)?;
+    parser.expect_keyword("obj")?;
+    let object = parser.parse_object(0)?;
+
+    parser.skip_whitespace();
+    let stream_start = match object {
+        PdfObject::Dictionary(_) if data[parser.position..].starts_with(b"stream") => {
+            // stream の後は CRLF か LF
+            let mut start = parser.position + b"stream".len();
+            if data.get(start) == Some(&b'\r') {
+                start += 1;
+            }
+            if data.get(start) == Some(&b'\n') {
+                start += 1;
+            }
+            Some(start)
+        }
+        _ => None,
+    };
+    Ok(IndirectObject {
+        number,
+        object,
+        stream_start,
+    })
+}
+
+fn is_whitespace(c: u8) -> bool {
+    matches!(c, b'\0' | b'\t' | b'\n' | b'\x0c' | b'\r' | b' ')
+}
+
+fn is_delimiter(c: u8) -> bool {
+    matches!(
+        c,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(data: &'a [u8], position: usize) -> Parser<'a> {
+        Parser { data, position }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.position).copied()
+    }
+
+    // 空白とコメントを読み飛ばす
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if is_whitespace(c) {
+                self.position += 1;
+            } else if c == b'%' {
+                while !matches!(self.peek(), None | Some(b'\r' | b'\n')) {
+                    self.position += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    // 区切り文字までの語 (数や obj などのキーワード)
+    fn read_token(&mut self) -> Result<&'a [u8]> {
+        self.skip_whitespace();
+        let start = self.position;
+        while matches!(self.peek(), Some(c) if !is_whitespace(c) && !is_delimiter(c)) {
+            self.position += 1;
+        }
+        if start == self.position {
+            bail!("unexpected pdf token at offset {}", start);
+        }
+        Ok(&self.data[start..self.position])
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        let start = self.position;
+        if self.read_token().ok() != Some(keyword.as_bytes()) {
+            bail!("'{}' not found at pdf offset {}", keyword, start);
+        }
+        Ok(())
+    }
+
+    fn parse_object(&mut self, depth: usize) -> Result<PdfObject> {
+        if MAX_DEPTH < depth {
+            bail!("pdf objects are nested too deeply");
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            None => bail!("unexpected end of pdf"),
+            Some(b'/') => {
+                self.position += 1;
+                Ok(PdfObject::Name(self.read_name()))
+            }
+            Some(b'(') => self.parse_literal_string(),
+            Some(b'<') if self.data.get(self.position + 1) == Some(&b'<') => {
+                self.position += 2;
+                let mut dictionary = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.data[self.position..].starts_with(b">>") {
+                        self.position += 2;
+                        return Ok(PdfObject::Dictionary(dictionary));
+                    }
+                    let key = match self.parse_object(depth + 1)? {
+                        PdfObject::Name(key) => key,
+                        _ => bail!("invalid pdf dictionary key at offset {}", self.position),
+                    };
+                    let value = self.parse_object(depth + 1)?;
+                    dictionary.push((key, value));
+                }
+            }
+            Some(b'<') => self.parse_hex_string(),
+            Some(b'[') => {
+                self.position += 1;
+                let mut array = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() == Some(b']') {
+                        self.position += 1;
+                        return Ok(PdfObject::Array(array));
+                    }
+                    array.push(self.parse_object(depth + 1)?);
+                }
+            }
+            Some(_) => {
+                let start = self.position;
+                match self.read_token()? {
+                    b"true" => Ok(PdfObject::Boolean(true)),
+                    b"false" => Ok(PdfObject::Boolean(false)),
+                    b"null" => Ok(PdfObject::Null),
+                    token => self.parse_number(token, start),
+                }
+            }
+        }
+    }
+
+    // "1 0 R" は参照として読む
+    fn parse_number(&mut self, token: &[u8], start: usize) -> Result<PdfObject> {
+        let text = std::str::from_utf8(token).unwrap_or("");
+        if let Ok(number) = text.parse::<i64>() {
+            let mut lookahead = Parser::new(self.data, self.position);
+            if let (Ok(number), Some(generation)) = (
+                usize::try_from(number),
+                lookahead
+                    .read_token()
+                    .ok()
+                    .and_then(|token| parse_usize(token).ok()),
+            ) {
+                if lookahead.read_token().ok() == Some(&b"R"[..]) {
+                    self.position = lookahead.position;
+                    return Ok(PdfObject::Reference(number, generation));
+                }
+            }
+            return Ok(PdfObject::Integer(number));
+        }
+        if !text.is_empty() && text.bytes().all(|c| b"+-.0123456789".contains(&c)) {
+            if let Ok(number) = text.parse::<f64>() {
+                return Ok(PdfObject::Real(number));
+            }
+        }
+        bail!(
+            "unexpected pdf token '{}' at offset {}",
+            String::from_utf8_lossy(token),
+            start
+        )
+    }
+
+    // PDF の名前では #xx が 1 バイトを表す
+    fn read_name(&mut self) -> String {
+        let mut bytes = Vec::new();
+        while let Some(c) = self.peek() {
+            if is_whitespace(c) || is_delimiter(c) {
+                break;
+            }
+            self.position += 1;
+            let hex = self
+                .data
+                .get(self.position..self.position + 2)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            match hex {
+                Some(byte) if c == b'#' => {
+                    bytes.push(byte);
+                    self.position += 2;
+                }
+                _ => bytes.push(c),
+            }
+        }
+        String::from_utf8_lossy(&bytes).to_string()
+    }
+
+    fn parse_literal_string(&mut self) -> Result<PdfObject> {
+        self.position += 1;
+        let mut bytes = Vec::new();
+        // 対応の取れた括弧はそのまま含められる
+        let mut depth = 1;
+        loop {
+            let c = self.peek().context("unterminated pdf string")?;
+            self.position += 1;
+            match c {
+                b'(' => {
+                    depth += 1;
+                    bytes.push(c);
+                }
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(PdfObject::String(bytes));
+                    }
+                    bytes.push(c);
+                }
+                b'\\' => {
+                    let escaped = self.peek().context("unterminated pdf string")?;
+                    self.position += 1;
+                    match escaped {
+                        b'n' => bytes.push(b'\n'),
+                        b'r' => bytes.push(b'\r'),
+                        b't' => bytes.push(b'\t'),
+                        b'b' => bytes.push(b'\x08'),
+                        b'f' => bytes.push(b'\x0c'),
+                        b'0'..=b'7' => {
+                            let mut value = u32::from(escaped - b'0');
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(digit @ b'0'..=b'7') => {
+                                        value = value * 8 + u32::from(digit - b'0');
+                                        self.position += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            bytes.push(value as u8);
+                        }
+                        // 行末の \ は改行を含めずに続ける
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.position += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        _ => bytes.push(escaped),
+                    }
+                }
+                // 文字列中の改行は LF とみなす
+                b'\r' => {
+                    if self.peek() == Some(b'\n') {
+                        self.position += 1;
+                    }
+                    bytes.push(b'\n');
+                }
+                _ => bytes.push(c),
+            }
+        }
+    }
+
+    fn parse_hex_string(&mut self) -> Result<PdfObject> {
+        self.position += 1;
+        let mut digits = Vec::new();
+        loop {
+            let c = self.peek().context("unterminated pdf hex string")?;
+            self.position += 1;
+            match c {
+                b'>' => break,
+                _ if is_whitespace(c) => {}
+                _ => match (c as char).to_digit(16) {
+                    Some(digit) => digits.push(digit as u8),
+                    None => bail!("invalid pdf hex string at offset {}", self.position - 1),
+                },
+            }
+        }
+        // 桁数が奇数なら最後に 0 を補う
+        if digits.len() % 2 == 1 {
+            digits.push(0);
+        }
+        Ok(PdfObject::String(
+            digits
+                .chunks(2)
+                .map(|pair| pair[0] << 4 | pair[1])
+                .collect(),
+        ))
+    }
+}
+
+fn write_object(buffer: &mut Vec<u8>, object: &PdfObject) {
+    match object {
+        PdfObject::Null => buffer.extend_from_slice(b"null"),
+        PdfObject::Boolean(value) => buffer.extend_from_slice(value.to_string().as_bytes()),
+        PdfObject::Integer(value) => buffer.extend_from_slice(value.to_string().as_bytes()),
+        PdfObject::Real(value) => buffer.extend_from_slice(value.to_string().as_bytes()),
+        PdfObject::String(bytes) => {
+            buffer.push(b'<');
+            for byte in bytes {
+                buffer.extend_from_slice(format!("{:02X}", byte).as_bytes());
+            }
+            buffer.push(b'>');
+        }
+        PdfObject::Name(name) => write_name(buffer, name),
+        PdfObject::Array(items) => {
+            buffer.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if 0 < i {
+                    buffer.push(b' ');
+                }
+                write_object(buffer, item);
+            }
+            buffer.push(b']');
+        }
+        PdfObject::Dictionary(dictionary) => {
+            buffer.extend_from_slice(b"<<");
+            for (i, (key, value)) in dictionary.iter().enumerate() {
+                if 0 < i {
+                    buffer.push(b' ');
+                }
+                write_name(buffer, key);
+                buffer.push(b' ');
+                write_object(buffer, value);
+            }
+            buffer.extend_from_slice(b">>");
+        }
+        PdfObject::Reference(number, generation) => {
+            buffer.extend_from_slice(format!("{} {} R", number, generation).as_bytes())
+        }
+    }
+}
+
+fn write_name(buffer: &mut Vec<u8>, name: &str) {
+    buffer.push(b'/');
+    for byte in name.bytes() {
+        if (b'!'..=b'~').contains(&byte) && !is_delimiter(byte) && byte != b'#' {
+            buffer.push(byte);
+        } else {
+            buffer.extend_from_slice(format!("#{:02X}", byte).as_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        html_evaluator::litedown::{evaluate_litedown_to_html, Ld2HtmlInput},
+        parser::litedown::parse_litedown,
+        utility::html::print_html_to_pdf,
+    };
+
+    // オブジェクトを並べ，正しい xref と trailer を持つ PDF を作る
+    fn build_pdf(objects: &[&str]) -> Vec<u8> {
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+        }
+        let xref_offset = pdf.len();
+        pdf.extend_from_slice(
+            format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+        );
+        for offset in offsets {
+            pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        pdf.extend_from_slice(
+            format!(
+                "trailer\n<</Size {} /Root 1 0 R>>\nstartxref\n{}\n%%EOF",
+                objects.len() + 1,
+                xref_offset
+            )
+            .as_bytes(),
+        );
+        pdf
+    }
+
+    // Chrome (Skia) の出力と同じく，中間の /Pages を挟んだページツリーと /Dests を持つ
+    fn build_document_pdf() -> Vec<u8> {
+        build_pdf(&[
+            "<</Type /Catalog\n/Pages 2 0 R\n/Dests 7 0 R>>",
+            "<</Type /Pages\n/Count 3\n/Kids [3 0 R 6 0 R]>>",
+            "<</Type /Pages\n/Count 2\n/Parent 2 0 R\n/Kids [4 0 R 5 0 R]>>",
+            "<</Type /Page\n/Parent 3 0 R>>",
+            "<</Type /Page\n/Parent 3 0 R>>",
+            "<</Type /Page\n/Parent 2 0 R>>",
+            "<</section-1 [4 0 R /XYZ 72 720 0]\n/section-2 [6 0 R /XYZ 72 400 0]\n/figure#2D1 <</D [5 0 R /XYZ 0 0 0]>>>>",
+        ])
+    }
+
+    // 圧縮なしのブロックだけからなる zlib データ
+    fn zlib_stored(data: &[u8]) -> Vec<u8> {
+        let mut result = vec![0x78, 0x01];
+        let chunks: Vec<&[u8]> = data.chunks(0xffff).collect();
+        for (i, chunk) in chunks.iter().enumerate() {
+            result.push(u8::from(i + 1 == chunks.len()));
+            let length = chunk.len() as u16;
+            result.extend_from_slice(&length.to_le_bytes());
+            result.extend_from_slice(&(!length).to_le_bytes());
+            result.extend_from_slice(chunk);
+        }
+        if chunks.is_empty() {
+            result.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+        }
+        let (mut a, mut b) = (1u32, 0u32);
+        for &byte in data {
+            a = (a + u32::from(byte)) % 65521;
+            b = (b + a) % 65521;
+        }
+        result.extend_from_slice(&(b << 16 | a).to_be_bytes());
+        result
+    }
+
+    // Chrome (Skia) の出力と同じく，PDF 1.5 の相互参照ストリームとオブジェクトストリームを使う
+    // カタログやページツリーは圧縮されたオブジェクトストリームの中にある
+    fn build_stream_pdf(objects: &[(usize, &str)], dests: &str) -> Vec<u8> {
+        let mut pdf = b"%PDF-1.5\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut entries = Vec::new();
+
+        // 1: 中身に trailer や obj に見えるバイト列を含むストリーム
+        let content = [
+            b"BT (trailer\n<</Size 2 /Root 9 0 R>>\n2 0 obj\n<</Type /Catalog>>\nendobj\nstartxref\n0\n) Tj ET\n"
+                .to_vec(),
+            (0..=255).collect(),
+        ]
+        .concat();
+        let content = zlib_stored(&content);
+        entries.push((1, pdf.len()));
+        pdf.extend_from_slice(b"1 0 obj\n<</Filter /FlateDecode /Length 2 0 R>> stream\r\n");
+        pdf.extend_from_slice(&content);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n");
+
+        // 2 (長さ) と objects をオブジェクトストリーム 3 に入れる
+        let length = content.len().to_string();
+        let mut compressed = vec![(2, length.as_str())];
+        compressed.extend_from_slice(objects);
+        compressed.push((10, dests));
+        let mut header = String::new();
+        let mut body = String::new();
+        for (number, object) in &compressed {
+            header.push_str(&format!("{} {} ", number, body.len()));
+            body.push_str(object);
+            body.push('\n');
+        }
+        let stream = zlib_stored(format!("{}{}", header, body).as_bytes());
+        entries.push((3, pdf.len()));
+        pdf.extend_from_slice(
+            format!(
+                "3 0 obj\n<</Type /ObjStm /N {} /First {} /Filter /FlateDecode /Length {}>>\nstream\n",
+                compressed.len(),
+                header.len(),
+                stream.len()
+            )
+            .as_bytes(),
+        );
+        pdf.extend_from_slice(&stream);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n");
+
+        // 相互参照ストリーム (PNG の Up 予測子を使う)
+        let size = compressed
+            .iter()
+            .map(|(number, _)| number + 1)
+            .max()
+            .unwrap()
+            + 1;
+        let xref_number = size - 1;
+        let xref_offset = pdf.len();
+        entries.push((xref_number, xref_offset));
+        let mut rows = vec![[0u8, 0, 0, 0, 0xff, 0xff]];
+        for number in 1..size {
+            let row = match entries.iter().find(|(n, _)| *n == number) {
+                Some((_, offset)) => {
+                    let offset = (*offset as u32).to_be_bytes();
+                    [1, offset[1], offset[2], offset[3], 0, 0]
+                }
+                None => match compressed.iter().position(|(n, _)| *n == number) {
+                    Some(index) => [2, 0, 0, 3, 0, index as u8],
+                    None => [0, 0, 0, 0, 0, 0],
+                },
+            };
+            rows.push(row);
+        }
+        let mut data = Vec::new();
+        let mut previous = [0u8; 6];
+        for row in rows {
+            data.push(2);
+            for (byte, previous) in row.iter().zip(previous) {
+                data.push(byte.wrapping_sub(previous));
+            }
+            previous = row;
+        }
+        let data = zlib_stored(&data);
+        pdf.extend_from_slice(
+            format!(
+                "{} 0 obj\n<</Type /XRef /Size {} /Root 4 0 R /W [1 3 2] /Filter /FlateDecode /DecodeParms <</Columns 6 /Predictor 12>> /Length {}>>\nstream\n",
+                xref_number,
+                size,
+                data.len()
+            )
+            .as_bytes(),
+        );
+        pdf.extend_from_slice(&data);
+        pdf.extend_from_slice(
+            format!("\nendstream\nendobj\nstartxref\n{}\n%%EOF\n", xref_offset).as_bytes(),
+        );
+        pdf
+    }
+
+    fn build_document_stream_pdf() -> Vec<u8> {
+        build_stream_pdf(
+            &[
+                (4, "<</Type /Catalog /Pages 5 0 R /Dests 10 0 R>>"),
+                (5, "<</Type /Pages /Count 3 /Kids [6 0 R 7 0 R 8 0 R]>>"),
+                (6, "<</Type /Page /Parent 5 0 R /Contents 1 0 R>>"),
+                (7, "<</Type /Page /Parent 5 0 R>>"),
+                (8, "<</Type /Page /Parent 5 0 R>>"),
+            ],
+            "<</section-1 [7 0 R /XYZ 0 0 0] /section-2 [8 0 R /Fit]>>",
+        )
+    }
+
+    #[test]
+    fn parse_objects() {
+        let mut parser = Parser::new(
+            b"<</A [1 -2 +3.5 .5 (a\\(b\\)\\061 (c)) <4a 4> true null /N#41me 12 0 R] % comment\n/B<<>>>>",
+            0,
+        );
+        assert_eq!(
+            parser.parse_object(0).unwrap(),
+            PdfObject::Dictionary(vec![
+                (
+                    "A".to_string(),
+                    PdfObject::Array(vec![
+                        PdfObject::Integer(1),
+                        PdfObject::Integer(-2),
+                        PdfObject::Real(3.5),
+                        PdfObject::Real(0.5),
+                        PdfObject::String(b"a(b)1 (c)".to_vec()),
+                        PdfObject::String(vec![0x4a, 0x40]),
+                        PdfObject::Boolean(true),
+                        PdfObject::Null,
+                        PdfObject::Name("NAme".to_string()),
+                        PdfObject::Reference(12, 0),
+                    ])
+                ),
+                ("B".to_string(), PdfObject::Dictionary(Vec::new())),
+            ])
+        );
+
+        // 書き出したものを読み直すと同じになる
+        let object = PdfObject::Dictionary(vec![
+            ("A b".to_string(), PdfObject::String(b"(\n)".to_vec())),
+            (
+                "C".to_string(),
+                PdfObject::Array(vec![PdfObject::Reference(1, 0)]),
+            ),
+        ]);
+        let mut buffer = Vec::new();
+        write_object(&mut buffer, &object);
+        assert_eq!(Parser::new(&buffer, 0).parse_object(0).unwrap(), object);
+
+        assert!(Parser::new(b"<</A 1", 0).parse_object(0).is_err());
+        assert!(Parser::new(b"[1 2", 0).parse_object(0).is_err());
+        assert!(Parser::new(b"<</A>>", 0).parse_object(0).is_err());
+        assert!(Parser::new(&b"[".repeat(100), 0).parse_object(0).is_err());
+    }
+
+    #[test]
+    fn right_to_left_direction() {
+        let pdf = build_document_pdf();
+        let patched = set_right_to_left_direction(pdf.clone()).unwrap();
+        assert!(patched.starts_with(&pdf));
+
+        // 追記した相互参照表と trailer
+        let document = PdfDocument::load(&patched).unwrap();
+        assert!(!document.xref_stream);
+        assert!(patched[document.startxref..].starts_with(b"xref\n1 1\n"));
+        assert_eq!(get_integer(&document.trailer, "Size"), Some(8));
+        assert_eq!(
+            get(&document.trailer, "Root"),
+            Some(&PdfObject::Reference(1, 0))
+        );
+        assert_eq!(
+            get_integer(&document.trailer, "Prev"),
+            Some(find_startxref(&pdf).unwrap())
+        );
+
+        // 相互参照表のオフセットが新しいカタログを指す
+        match document.entries[&1] {
+            XrefEntry::Offset(offset) => assert!(offset >= pdf.len()),
+            entry => panic!("unexpected entry: {:?}", entry),
+        }
+        let catalog = document.get_catalog().unwrap();
+        assert_eq!(
+            get(&catalog, "ViewerPreferences"),
+            Some(&PdfObject::Dictionary(vec![(
+                "Direction".to_string(),
+                PdfObject::Name("R2L".to_string())
+            )]))
+        );
+        assert_eq!(get(&catalog, "Pages"), Some(&PdfObject::Reference(2, 0)));
+        assert_eq!(get(&catalog, "Dests"), Some(&PdfObject::Reference(7, 0)));
+        assert_eq!(
+            get_named_destination_pages(&patched).unwrap(),
+            get_named_destination_pages(&pdf).unwrap()
+        );
+
+        // すでに右から左なら変更しない
+        assert_eq!(
+            set_right_to_left_direction(patched.clone()).unwrap(),
+            patched
+        );
+    }
+
+    #[test]
+    fn right_to_left_direction_keeps_info_and_id() {
+        let mut pdf = build_pdf(&[
+            "<</Type /Catalog /Pages 2 0 R /ViewerPreferences <</HideToolbar true>>>>",
+            "<</Type /Pages /Count 0 /Kids []>>",
+            "<</Title (Test)>>",
+        ]);
+        let trailer_start = pdf.windows(5).rposition(|w| w == b"/Root").unwrap();
+        pdf.splice(
+            trailer_start..trailer_start,
+            b"/Info 3 0 R /ID [<0123> <4567>] ".iter().copied(),
+        );
+        let patched = set_right_to_left_direction(pdf).unwrap();
+        let document = PdfDocument::load(&patched).unwrap();
+        assert_eq!(
+            get(&document.trailer, "Info"),
+            Some(&PdfObject::Reference(3, 0))
+        );
+        assert_eq!(
+            get(&document.trailer, "ID"),
+            Some(&PdfObject::Array(vec![
+                PdfObject::String(vec![0x01, 0x23]),
+                PdfObject::String(vec![0x45, 0x67]),
+            ]))
+        );
+        // 既存の /ViewerPreferences に /Direction を加える
+        let catalog = document.get_catalog().unwrap();
+        assert_eq!(
+            get(&catalog, "ViewerPreferences"),
+            Some(&PdfObject::Dictionary(vec![
+                ("HideToolbar".to_string(), PdfObject::Boolean(true)),
+                ("Direction".to_string(), PdfObject::Name("R2L".to_string())),
+            ]))
+        );
+    }
+
+    #[test]
+    fn right_to_left_direction_with_xref_stream() {
+        let pdf = build_document_stream_pdf();
+        let patched = set_right_to_left_direction(pdf.clone()).unwrap();
+        assert!(patched.starts_with(&pdf));
+
+        // 追記した相互参照もストリームになる
+        let document = PdfDocument::load(&patched).unwrap();
+        assert!(document.xref_stream);
+        assert_eq!(get_integer(&document.trailer, "Size"), Some(13));
+        assert_eq!(
+            get(&document.trailer, "Root"),
+            Some(&PdfObject::Reference(4, 0))
+        );
+        assert_eq!(
+            get_integer(&document.trailer, "Prev"),
+            Some(find_startxref(&pdf).unwrap())
+        );
+        assert!(patched[document.startxref..].starts_with(b"12 0 obj\n"));
+
+        let catalog = document.get_catalog().unwrap();
+        assert_eq!(
+            get(&catalog, "ViewerPreferences"),
+            Some(&PdfObject::Dictionary(vec![(
+                "Direction".to_string(),
+                PdfObject::Name("R2L".to_string())
+            )]))
+        );
+        assert_eq!(get(&catalog, "Pages"), Some(&PdfObject::Reference(5, 0)));
+        // 圧縮されたままの古いオブジェクトも読める
+        assert_eq!(
+            get_named_destination_pages(&patched).unwrap(),
+            get_named_destination_pages(&pdf).unwrap()
+        );
+        assert_eq!(
+            set_right_to_left_direction(patched.clone()).unwrap(),
+            patched
+        );
+    }
+
+    #[test]
+    fn xref_stream() {
+        let pdf = build_document_stream_pdf();
+        let document = PdfDocument::load(&pdf).unwrap();
+        assert!(document.xref_stream);
+        // /Length が圧縮されたオブジェクトへの参照でも読める
+        let (_, content) = document.get_stream(1).unwrap();
+        assert!(content.starts_with(b"BT (trailer\n"));
+        assert!(content.ends_with(&(0..=255).collect::<Vec<u8>>()));
+
+        assert_eq!(
+            get_named_destination_pages(&pdf).unwrap(),
+            vec![("section-1".to_string(), 2), ("section-2".to_string(), 3)]
+        );
+    }
+
+    #[test]
+    fn invalid_pdf() {
+        assert_eq!(
+            set_right_to_left_direction(b"%PDF-1.4\n".to_vec())
+                .unwrap_err()
+                .to_string(),
+            "pdf startxref not found"
+        );
+        assert!(get_named_destination_pages(b"%PDF-1.4\nstartxref\n100\n%%EOF").is_err());
+
+        // startxref が相互参照を指していない
+        let mut pdf = build_document_pdf();
+        let xref_offset = find_startxref(&pdf).unwrap();
+        let startxref = pdf.windows(9).rposition(|w| w == b"startxref").unwrap();
+        pdf.truncate(startxref);
+        pdf.extend_from_slice(format!("startxref\n{}\n%%EOF", xref_offset - 10).as_bytes());
+        assert!(get_named_destination_pages(&pdf).is_err());
+
+        // /Prev が循環している
+        let mut pdf = build_document_pdf();
+        let trailer_start = pdf.windows(5).rposition(|w| w == b"/Root").unwrap();
+        let prev = format!("/Prev {} ", xref_offset);
+        pdf.splice(trailer_start..trailer_start, prev.bytes());
+        assert_eq!(
+            get_named_destination_pages(&pdf).unwrap_err().to_string(),
+            "pdf cross-reference sections form a loop"
+        );
+
+        // 暗号化された PDF は書き換えない
+        let mut pdf = build_document_pdf();
+        pdf.splice(
+            trailer_start..trailer_start,
+            b"/Encrypt 3 0 R ".iter().copied(),
+        );
+        assert_eq!(
+            set_right_to_left_direction(pdf).unwrap_err().to_string(),
+            "encrypted pdf is not supported"
+        );
+    }
+
+    // litedown の文書を実際に Chrome で印刷する
+    fn print_litedown(name: &str, source: &str) -> Vec<u8> {
+        let output = evaluate_litedown_to_html(Ld2HtmlInput {
+            ast: parse_litedown(source).unwrap(),
+            source_path: None,
+        })
+        .unwrap();
+        let html_path = std::env::temp_dir().join(format!("litedown-{}.html", name));
+        std::fs::write(&html_path, output.html.to_string().merge()).unwrap();
+        let pdf = print_html_to_pdf(html_path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&html_path).unwrap();
+        pdf
+    }
+
+    #[test]
+    #[ignore = "requires Chrome"]
+    fn chrome_right_to_left_direction() {
+        let pdf = print_litedown(
+            "pdf-right-to-left",
+            "@preamble@\n  @page_size[\"a4\"]\n\n@document@\n  a\n\n  @pagebreak\n\n  b\n",
+        );
+        let document = PdfDocument::load(&pdf).unwrap();
+        let catalog = document.get_catalog().unwrap();
+        assert_eq!(document.get_pages(&catalog).unwrap().len(), 2);
+
+        let patched = set_right_to_left_direction(pdf.clone()).unwrap();
+        assert!(patched.starts_with(&pdf));
+        let document = PdfDocument::load(&patched).unwrap();
+        assert_eq!(
+            document.xref_stream,
+            PdfDocument::load(&pdf).unwrap().xref_stream
+        );
+        let catalog = document.get_catalog().unwrap();
+        assert_eq!(
+            get(&catalog, "ViewerPreferences"),
+            Some(&PdfObject::Dictionary(vec![(
+                "Direction".to_string(),
+                PdfObject::Name("R2L".to_string())
+            )]))
+        );
+        assert_eq!(document.get_pages(&catalog).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn named_destination_pages() {
+        let pages = get_named_destination_pages(&build_document_pdf()).unwrap();
+        assert_eq!(
+            pages,
+            vec![
+                ("section-1".to_string(), 1),
+                ("section-2".to_string(), 3),
+                ("figure-1".to_string(), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn named_destination_pages_in_name_tree() {
+        let pdf = build_pdf(&[
+            "<</Type /Catalog /Pages 2 0 R /Names <</Dests 5 0 R>>>>",
+            "<</Type /Pages /Count 2 /Kids [3 0 R 4 0 R]>>",
+            "<</Type /Page /Parent 2 0 R>>",
+            "<</Type /Page /Parent 2 0 R>>",
+            "<</Kids [6 0 R 7 0 R]>>",
+            "<</Limits [(section-1) (section-2)] /Names [(section-1) [3 0 R /XYZ 0 0 0] (section-2) 8 0 R]>>",
+            "<</Limits [<FEFF00E9> <FEFF00E9>] /Names [<FEFF00E9> <</D [4 0 R /Fit]>>]>>",
+            "[4 0 R /Fit]",
+        ]);
+        assert_eq!(
+            get_named_destination_pages(&pdf).unwrap(),
+            vec![
+                ("section-1".to_string(), 1),
+                ("section-2".to_string(), 2),
+                ("\u{e9}".to_string(), 2),
+            ]
+        );
+    }
+
+    #[test]
+    #[ignore = "requires Chrome"]
+    fn chrome_listing_pages() {
+        // 目次の項目のリンク先が何ページ目にあるか
+        let pdf = print_litedown(
+            "pdf-listing",
+            "@preamble@\n  @page_size[\"a4\"]\n\n@document@\n  @toc@\n\n  @pagebreak\n\n  @section[title=\"A\"]@\n    a\n\n  @pagebreak\n\n  @section[title=\"B\"]@\n    b\n\n    @section[title=\"C\"]@\n      c\n",
+        );
+        let mut pages = get_named_destination_pages(&pdf).unwrap();
+        pages.sort();
+        assert_eq!(
+            pages,
+            vec![
+                ("section-1".to_string(), 2),
+                ("section-2".to_string(), 3),
+                ("section-3".to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn named_destination_pages_without_dests() {
+        let pdf = build_pdf(&[
+            "<</Type /Catalog /Pages 2 0 R>>",
+            "<</Type /Pages /Count 1 /Kids [3 0 R]>>",
+            "<</Type /Page /Parent 2 0 R>>",
+        ]);
+        assert!(get_named_destination_pages(&pdf).unwrap().is_empty());
+    }
+}
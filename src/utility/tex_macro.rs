@@ -0,0 +1,444 @@
+use anyhow::{bail, Context, Result};
+
+#[derive(Debug, Clone)]
+pub struct TexMacro {
+    // 先頭の \ を含まない
+    pub name: String,
+    pub parameters: usize,
+    pub body: String,
+}
+
+// 既に定義されている名前に対する扱い
+enum Definition {
+    // \newcommand, \DeclareMathOperator (再定義はエラー)
+    New,
+    // \renewcommand, \def (上書きする)
+    Renew,
+    // \providecommand (既存の定義を残す)
+    Provide,
+}
+
+// \newcommand などの定義の並びを解析する
+pub fn parse_tex_macros(source: &str) -> Result<Vec<TexMacro>> {
+    let mut macros = Vec::new();
+    parse_tex_macros_into(source, &mut macros)?;
+    Ok(macros)
+}
+
+// 定義済みのマクロ (別のファイルなどで定義されたもの) に続けて定義を解析する
+pub fn parse_tex_macros_into(source: &str, macros: &mut Vec<TexMacro>) -> Result<()> {
+    let mut parser = TexMacroParser {
+        chars: source.chars().collect(),
+        index: 0,
+    };
+    loop {
+        parser.skip_whitespaces();
+        if parser.peek().is_none() {
+            break;
+        }
+        let line = parser.line();
+        let definition = parser
+            .parse_definition()
+            .with_context(|| format!("invalid macro definition: line {}", line))?;
+        if let Some((tex_macro, definition)) = definition {
+            let defined = macros.iter().position(|m| m.name == tex_macro.name);
+            match (defined, definition) {
+                (Some(_), Definition::New) => bail!(
+                    "invalid macro definition: line {}: '\\{}' is already defined",
+                    line,
+                    tex_macro.name
+                ),
+                (Some(index), Definition::Renew) => macros[index] = tex_macro,
+                (Some(_), Definition::Provide) => {}
+                (None, _) => macros.push(tex_macro),
+            }
+        }
+    }
+    Ok(())
+}
+
+struct TexMacroParser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+impl TexMacroParser {
+    fn line(&self) -> usize {
+        self.chars[..self.index]
+            .iter()
+            .filter(|&&c| c == '\n')
+            .count()
+            + 1
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.index += 1;
+            } else if c == '%' {
+                while let Some(c) = self.peek() {
+                    self.index += 1;
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    // \name の name 部分
+    fn parse_control_sequence(&mut self) -> Result<String> {
+        self.skip_whitespaces();
+        if self.peek() != Some('\\') {
+            bail!("expected control sequence");
+        }
+        self.index += 1;
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_alphabetic() {
+                break;
+            }
+            name.push(c);
+            self.index += 1;
+        }
+        if name.is_empty() {
+            match self.peek() {
+                Some(c) if !c.is_whitespace() => {
+                    name.push(c);
+                    self.index += 1;
+                }
+                _ => bail!("expected control sequence"),
+            }
+        }
+        Ok(name)
+    }
+
+    // 外側の括弧を含まない
+    fn parse_group(&mut self) -> Result<String> {
+        self.skip_whitespaces();
+        if self.peek() != Some('{') {
+            bail!("expected '{{'");
+        }
+        self.index += 1;
+        let mut group = String::new();
+        let mut depth = 0;
+        loop {
+            let c = match self.peek() {
+                Some(c) => c,
+                None => bail!("unbalanced braces"),
+            };
+            self.index += 1;
+            match c {
+                '\\' => {
+                    group.push(c);
+                    if let Some(c) = self.peek() {
+                        group.push(c);
+                        self.index += 1;
+                    }
+                    continue;
+                }
+                '{' => depth += 1,
+                '}' if depth == 0 => return Ok(group),
+                '}' => depth -= 1,
+                _ => {}
+            }
+            group.push(c);
+        }
+    }
+
+    fn parse_optional(&mut self) -> Result<Option<String>> {
+        self.skip_whitespaces();
+        if self.peek() != Some('[') {
+            return Ok(None);
+        }
+        self.index += 1;
+        let mut optional = String::new();
+        loop {
+            match self.peek() {
+                Some(']') => {
+                    self.index += 1;
+                    return Ok(Some(optional));
+                }
+                Some(c) => {
+                    optional.push(c);
+                    self.index += 1;
+                }
+                None => bail!("expected ']'"),
+            }
+        }
+    }
+
+    // {\name} または \name
+    fn parse_macro_name(&mut self) -> Result<String> {
+        self.skip_whitespaces();
+        if self.peek() == Some('{') {
+            let group = self.parse_group()?;
+            let mut parser = TexMacroParser {
+                chars: group.chars().collect(),
+                index: 0,
+            };
+            let name = parser.parse_control_sequence()?;
+            parser.skip_whitespaces();
+            if parser.peek().is_some() {
+                bail!("invalid macro name: {}", group);
+            }
+            Ok(name)
+        } else {
+            self.parse_control_sequence()
+        }
+    }
+
+    // 定義以外の許容されるコマンドは None を返す
+    fn parse_definition(&mut self) -> Result<Option<(TexMacro, Definition)>> {
+        let command = self.parse_control_sequence()?;
+        match command.as_str() {
+            "newcommand" | "renewcommand" | "providecommand" => {
+                self.skip_whitespaces();
+                // \newcommand* は \newcommand と同じ扱い
+                if self.peek() == Some('*') {
+                    self.index += 1;
+                }
+                let name = self.parse_macro_name()?;
+                let parameters = match self.parse_optional()? {
+                    Some(parameters) => match parameters.trim().parse::<usize>() {
+                        Ok(parameters) if parameters <= 9 => parameters,
+                        _ => bail!("invalid number of parameters: {}", parameters),
+                    },
+                    None => 0,
+                };
+                if self.parse_optional()?.is_some() {
+                    bail!("optional parameters are not supported: '\\{}'", name);
+                }
+                let body = self.parse_group()?;
+                Ok(Some((
+                    validate(TexMacro {
+                        name,
+                        parameters,
+                        body,
+                    })?,
+                    match command.as_str() {
+                        "newcommand" => Definition::New,
+                        "renewcommand" => Definition::Renew,
+                        _ => Definition::Provide,
+                    },
+                )))
+            }
+            "def" => {
+                let name = self.parse_control_sequence()?;
+                // \def\name#1#2{...}
+                let mut parameters = 0;
+                loop {
+                    self.skip_whitespaces();
+                    if self.peek() != Some('#') {
+                        break;
+                    }
+                    self.index += 1;
+                    match self.peek().and_then(|c| c.to_digit(10)) {
+                        Some(n) if n as usize == parameters + 1 => parameters += 1,
+                        _ => bail!("invalid parameter in '\\{}'", name),
+                    }
+                    self.index += 1;
+                }
+                let body = self.parse_group()?;
+                Ok(Some((
+                    validate(TexMacro {
+                        name,
+                        parameters,
+                        body,
+                    })?,
+                    Definition::Renew,
+                )))
+            }
+            "DeclareMathOperator" => {
+                self.skip_whitespaces();
+                let starred = self.peek() == Some('*');
+                if starred {
+                    self.index += 1;
+                }
+                let name = self.parse_macro_name()?;
+                let operator = self.parse_group()?;
+                let body = if starred {
+                    format!("\\operatorname*{{{}}}", operator)
+                } else {
+                    format!("\\operatorname{{{}}}", operator)
+                };
+                Ok(Some((
+                    validate(TexMacro {
+                        name,
+                        parameters: 0,
+                        body,
+                    })?,
+                    Definition::New,
+                )))
+            }
+            // パッケージファイルの定型句
+            "ProvidesPackage" | "NeedsTeXFormat" | "RequirePackage" | "usepackage" => {
+                self.parse_optional()?;
+                self.parse_group()?;
+                self.parse_optional()?;
+                Ok(None)
+            }
+            "makeatletter" | "makeatother" | "endinput" => Ok(None),
+            _ => bail!("unsupported command: '\\{}'", command),
+        }
+    }
+}
+
+fn validate(tex_macro: TexMacro) -> Result<TexMacro> {
+    let chars: Vec<char> = tex_macro.body.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '#' => match chars.get(i + 1).and_then(|c| c.to_digit(10)) {
+                Some(n) if 1 <= n && n as usize <= tex_macro.parameters => i += 1,
+                _ => bail!(
+                    "'\\{}' takes {} parameters but its body uses an invalid parameter",
+                    tex_macro.name,
+                    tex_macro.parameters
+                ),
+            },
+            _ => {}
+        }
+        i += 1;
+    }
+    Ok(tex_macro)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(source: &str) -> String {
+        format!("{:#}", parse_tex_macros(source).unwrap_err())
+    }
+
+    fn get<'a>(macros: &'a [TexMacro], name: &str) -> &'a TexMacro {
+        macros.iter().find(|m| m.name == name).unwrap()
+    }
+
+    #[test]
+    fn newcommand() {
+        let macros = parse_tex_macros(
+            r"\newcommand{\R}{\mathbb{R}}
+\newcommand*\norm[1]{\left\| #1 \right\|}",
+        )
+        .unwrap();
+        assert_eq!(macros.len(), 2);
+        assert_eq!(get(&macros, "R").parameters, 0);
+        assert_eq!(get(&macros, "R").body, r"\mathbb{R}");
+        assert_eq!(get(&macros, "norm").parameters, 1);
+        assert_eq!(get(&macros, "norm").body, r"\left\| #1 \right\|");
+    }
+
+    #[test]
+    fn def() {
+        let macros = parse_tex_macros(r"\def\pair#1#2{(#1, #2)} \def\e{\mathrm{e}}").unwrap();
+        assert_eq!(get(&macros, "pair").parameters, 2);
+        assert_eq!(get(&macros, "pair").body, "(#1, #2)");
+        assert_eq!(get(&macros, "e").parameters, 0);
+        // \def は上書きする
+        let macros = parse_tex_macros(r"\def\e{a} \def\e{b}").unwrap();
+        assert_eq!(macros.len(), 1);
+        assert_eq!(get(&macros, "e").body, "b");
+    }
+
+    #[test]
+    fn declare_math_operator() {
+        let macros = parse_tex_macros(
+            r"\DeclareMathOperator{\Tr}{Tr}
+\DeclareMathOperator*{\argmax}{arg\,max}",
+        )
+        .unwrap();
+        assert_eq!(get(&macros, "Tr").body, r"\operatorname{Tr}");
+        assert_eq!(get(&macros, "argmax").body, r"\operatorname*{arg\,max}");
+    }
+
+    #[test]
+    fn redefinition() {
+        let macros = parse_tex_macros(r"\newcommand{\a}{1} \renewcommand{\a}{2}").unwrap();
+        assert_eq!(get(&macros, "a").body, "2");
+        // \providecommand は既存の定義を残す
+        let macros = parse_tex_macros(r"\newcommand{\a}{1} \providecommand{\a}{2}").unwrap();
+        assert_eq!(get(&macros, "a").body, "1");
+        let macros = parse_tex_macros(r"\providecommand{\a}{2}").unwrap();
+        assert_eq!(get(&macros, "a").body, "2");
+        assert_eq!(
+            parse_error("\\newcommand{\\a}{1}\n\\newcommand{\\a}{2}"),
+            r"invalid macro definition: line 2: '\a' is already defined"
+        );
+        assert!(parse_tex_macros(r"\newcommand{\a}{1} \DeclareMathOperator{\a}{a}").is_err());
+    }
+
+    #[test]
+    fn redefinition_across_sources() {
+        let mut macros = parse_tex_macros(r"\newcommand{\a}{1}").unwrap();
+        parse_tex_macros_into(r"\providecommand{\a}{2} \newcommand{\b}{3}", &mut macros).unwrap();
+        assert_eq!(get(&macros, "a").body, "1");
+        assert_eq!(get(&macros, "b").body, "3");
+        assert_eq!(
+            format!(
+                "{:#}",
+                parse_tex_macros_into(r"\newcommand{\b}{4}", &mut macros).unwrap_err()
+            ),
+            r"invalid macro definition: line 1: '\b' is already defined"
+        );
+    }
+
+    #[test]
+    fn parameter_validation() {
+        assert!(parse_tex_macros(r"\newcommand{\f}[2]{#1 + #2}").is_ok());
+        assert_eq!(
+            parse_error(r"\newcommand{\f}[1]{#1 + #2}"),
+            r"invalid macro definition: line 1: '\f' takes 1 parameters but its body uses an invalid parameter"
+        );
+        assert_eq!(
+            parse_error(r"\newcommand{\f}[10]{#1}"),
+            "invalid macro definition: line 1: invalid number of parameters: 10"
+        );
+        assert_eq!(
+            parse_error(r"\newcommand{\f}[1][0]{#1}"),
+            r"invalid macro definition: line 1: optional parameters are not supported: '\f'"
+        );
+        assert_eq!(
+            parse_error(r"\def\f#2{#2}"),
+            r"invalid macro definition: line 1: invalid parameter in '\f'"
+        );
+        // \# はパラメータではない
+        assert!(parse_tex_macros(r"\newcommand{\hash}{\#}").is_ok());
+    }
+
+    #[test]
+    fn error_line_numbers() {
+        let source = "% macros\n\\newcommand{\\a}{1}\n\n\\newcommand{\\b}{2\n";
+        assert_eq!(
+            parse_error(source),
+            "invalid macro definition: line 4: unbalanced braces"
+        );
+        assert_eq!(
+            parse_error("\\newcommand{\\a}{1}\n\\foo"),
+            r"invalid macro definition: line 2: unsupported command: '\foo'"
+        );
+    }
+
+    #[test]
+    fn package_boilerplate() {
+        let macros = parse_tex_macros(
+            r"\NeedsTeXFormat{LaTeX2e}
+\ProvidesPackage{macros}[2024/01/01]
+\RequirePackage{amsmath}
+\makeatletter
+\newcommand{\a}{1}
+\makeatother
+\endinput",
+        )
+        .unwrap();
+        assert_eq!(macros.len(), 1);
+    }
+}
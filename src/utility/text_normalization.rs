@@ -0,0 +1,154 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum PunctuationStyle {
+    // 、。
+    Touten,
+    // ，．
+    Comma,
+}
+
+impl PunctuationStyle {
+    pub fn from_name(name: &str) -> Option<PunctuationStyle> {
+        match name {
+            "、。" | "touten" => Some(PunctuationStyle::Touten),
+            "，．" | "comma" => Some(PunctuationStyle::Comma),
+            _ => None,
+        }
+    }
+}
+
+// 和文の文字 (かな・漢字)
+pub fn is_japanese(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{309F}' // ひらがな
+        | '\u{30A0}'..='\u{30FF}' // カタカナ
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{3005}' // 々
+    )
+}
+
+// 全角英数字を半角にする (Ａ１ => A1)
+pub fn unify_alphanumerics(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '０'..='９' | 'Ａ'..='Ｚ' | 'ａ'..='ｚ' => {
+                char::from_u32(c as u32 - 0xFEE0).unwrap_or(c)
+            }
+            _ => c,
+        })
+        .collect()
+}
+
+// 句読点を揃える
+// 半角の , と . は和文の直後にあるものだけを句読点とみなす
+pub fn normalize_punctuation(text: &str, style: &PunctuationStyle) -> String {
+    let (comma, period) = match style {
+        PunctuationStyle::Touten => ('、', '。'),
+        PunctuationStyle::Comma => ('，', '．'),
+    };
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        let previous = if i == 0 { None } else { chars.get(i - 1) };
+        let after_japanese = previous.is_some_and(|&c| is_japanese(c));
+        // 3．14 のような小数点は半角にし，1，000 のような桁区切りはそのままにする
+        let between_digits = previous.is_some_and(|c| c.is_ascii_digit())
+            && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit());
+        result.push(match c {
+            '，' if between_digits => c,
+            '、' | '，' => comma,
+            ',' if after_japanese => comma,
+            '。' => period,
+            '．' if between_digits => '.',
+            '．' => period,
+            '.' if after_japanese => period,
+            _ => c,
+        });
+    }
+    result
+}
+
+// 和文と欧文・数字の間に spacing を入れる (既に空白があれば入れない)
+pub fn insert_spacing(text: &str, spacing: &str) -> String {
+    let mut result = String::new();
+    let mut previous: Option<char> = None;
+    for c in text.chars() {
+        if let Some(previous) = previous {
+            if (is_japanese(previous) && c.is_ascii_alphanumeric())
+                || (previous.is_ascii_alphanumeric() && is_japanese(c))
+            {
+                result.push_str(spacing);
+            }
+        }
+        result.push(c);
+        previous = Some(c);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alphanumerics() {
+        assert_eq!(unify_alphanumerics("Ｗｉｎｄｏｗｓ１１"), "Windows11");
+        assert_eq!(unify_alphanumerics("ＡＢＣ－１２３　ｘ"), "ABC－123　x");
+        assert_eq!(unify_alphanumerics("かな漢字"), "かな漢字");
+    }
+
+    #[test]
+    fn punctuation_touten() {
+        let style = PunctuationStyle::Touten;
+        assert_eq!(
+            normalize_punctuation("これは，例です．", &style),
+            "これは、例です。"
+        );
+        assert_eq!(normalize_punctuation("和文,和文.", &style), "和文、和文。");
+        // 欧文の , と . はそのまま
+        assert_eq!(
+            normalize_punctuation("e.g., foo. 和文", &style),
+            "e.g., foo. 和文"
+        );
+        // 数字の間の小数点と桁区切り
+        assert_eq!(
+            normalize_punctuation("円周率は3．14，人口は1，000人", &style),
+            "円周率は3.14、人口は1，000人"
+        );
+        assert_eq!(normalize_punctuation("1，", &style), "1、");
+    }
+
+    #[test]
+    fn punctuation_comma() {
+        let style = PunctuationStyle::Comma;
+        assert_eq!(
+            normalize_punctuation("これは、例です。", &style),
+            "これは，例です．"
+        );
+        assert_eq!(normalize_punctuation("1，000．5", &style), "1，000.5");
+    }
+
+    #[test]
+    fn punctuation_style_names() {
+        assert_eq!(
+            PunctuationStyle::from_name("、。"),
+            Some(PunctuationStyle::Touten)
+        );
+        assert_eq!(
+            PunctuationStyle::from_name("comma"),
+            Some(PunctuationStyle::Comma)
+        );
+        assert_eq!(PunctuationStyle::from_name("、．"), None);
+    }
+
+    #[test]
+    fn spacing() {
+        assert_eq!(insert_spacing("Rustで書く", " "), "Rust で書く");
+        assert_eq!(insert_spacing("第3章とAPI", " "), "第 3 章と API");
+        // 既に空白や記号があれば入れない
+        assert_eq!(insert_spacing("Rust で書く", " "), "Rust で書く");
+        assert_eq!(insert_spacing("（Rust）を", " "), "（Rust）を");
+        assert_eq!(insert_spacing("ーAー", "\u{2009}"), "ー\u{2009}A\u{2009}ー");
+    }
+}
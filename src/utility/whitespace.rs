@@ -1,5 +1,7 @@
 use std::slice::Iter;
 
+use super::text_normalization::is_japanese;
+
 pub enum Whitespace {
     SingleByteSpace,
     Tab,
@@ -42,3 +44,66 @@ pub fn len_as_whitespace(c: char) -> Option<usize> {
         .find(|ws| ws.to_char() == c)
         .map(|ws| ws.len())
 }
+
+// 本文中の空白と改行の扱い
+#[derive(Debug, Clone, PartialEq)]
+pub enum WhitespaceMode {
+    // 空白と改行をそのまま出力する
+    Preserve,
+    // HTML と同様に，連続する空白と改行を 1 つの空白にまとめる
+    Collapse,
+    // Collapse に加えて，和文の間の改行を取り除く
+    Cjk,
+}
+
+impl WhitespaceMode {
+    pub fn from_name(name: &str) -> Option<WhitespaceMode> {
+        match name {
+            "preserve" => Some(WhitespaceMode::Preserve),
+            "collapse" => Some(WhitespaceMode::Collapse),
+            "cjk" => Some(WhitespaceMode::Cjk),
+            _ => None,
+        }
+    }
+}
+
+// 改行を詰めてよい文字 (かな・漢字・和文の約物・全角文字)
+fn is_cjk(c: char) -> bool {
+    is_japanese(c) || matches!(c, '\u{3000}'..='\u{303F}' | '\u{FF00}'..='\u{FFEF}')
+}
+
+// 連続する空白と改行をまとめる (Preserve ではそのまま返す)
+// 文字列の端では，前後の関数の中身が見えないため，見えている側の文字だけで判断する
+pub fn collapse_whitespaces(text: &str, mode: &WhitespaceMode) -> String {
+    if *mode == WhitespaceMode::Preserve {
+        return text.to_string();
+    }
+    let mut result = String::new();
+    let mut previous: Option<char> = None;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !matches!(c, ' ' | '\t' | '\n') {
+            result.push(c);
+            previous = Some(c);
+            continue;
+        }
+        let mut has_newline = c == '\n';
+        while let Some(&next) = chars.peek() {
+            if !matches!(next, ' ' | '\t' | '\n') {
+                break;
+            }
+            has_newline |= next == '\n';
+            chars.next();
+        }
+        let next = chars.peek().copied();
+        let between_cjk = match (previous, next) {
+            (Some(previous), Some(next)) => is_cjk(previous) && is_cjk(next),
+            (Some(c), None) | (None, Some(c)) => is_cjk(c),
+            (None, None) => false,
+        };
+        if !(*mode == WhitespaceMode::Cjk && has_newline && between_cjk) {
+            result.push(' ');
+        }
+    }
+    result
+}